@@ -0,0 +1,45 @@
+use proc_macro::TokenStream;
+
+/// Puts a query function in a `debug` span when the `tracing` feature is on, with an empty
+/// `rows` field for `record_rows` to fill in and errors recorded on the span.
+///
+/// The span leaves out the `connection` argument. Pass `skip_all` or `skip(...)` to leave
+/// out other arguments too, as `tracing::instrument` takes them:
+///
+/// ```ignore
+/// #[query_span]
+/// pub fn get_book_by_id(id: i32, connection: &mut DbConnection) -> Result<Option<Book>> {
+///     // ...
+/// }
+///
+/// #[query_span(skip(connection, body))]
+/// pub fn update_review(id: i32, body: &str, connection: &mut DbConnection) -> Result<()> {
+///     // ...
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn query_span(arguments: TokenStream, item: TokenStream) -> TokenStream {
+    let skip = if arguments.is_empty() {
+        "skip(connection)".to_owned()
+    } else {
+        arguments.to_string()
+    };
+    let attribute = format!(
+        r#"#[cfg_attr(
+            feature = "tracing",
+            tracing::instrument(
+                level = "debug",
+                {skip},
+                fields(rows = tracing::field::Empty),
+                err
+            )
+        )]"#
+    );
+    let mut output = attribute
+        .parse::<TokenStream>()
+        .expect("query_span builds a valid attribute");
+
+    output.extend(item);
+
+    output
+}
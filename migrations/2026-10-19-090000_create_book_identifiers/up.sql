@@ -0,0 +1,8 @@
+CREATE TABLE book_identifiers (
+    book_id INT NOT NULL REFERENCES books (id) ON DELETE CASCADE,
+    scheme VARCHAR(32) NOT NULL,
+    value VARCHAR(64) NOT NULL,
+    PRIMARY KEY (scheme, value)
+);
+
+CREATE INDEX book_identifiers_book_id_idx ON book_identifiers (book_id);
@@ -0,0 +1,12 @@
+DROP TRIGGER touch_books ON authors;
+DROP TRIGGER touch_books ON book_identifiers;
+DROP TRIGGER touch_books ON book_authors;
+
+DROP FUNCTION touch_books_of_author();
+DROP FUNCTION touch_book_of_row();
+
+DROP TRIGGER set_updated_at ON books;
+
+DROP INDEX books_updated_at_idx;
+
+ALTER TABLE books DROP COLUMN updated_at;
@@ -0,0 +1,37 @@
+ALTER TABLE books
+    ADD COLUMN updated_at TIMESTAMP NOT NULL DEFAULT (now() AT TIME ZONE 'utc');
+
+CREATE INDEX books_updated_at_idx ON books (updated_at, id);
+
+-- A book counts as changed when its own row changes or when anything an ONIX product is
+-- built from changes: its credits, identifiers and the names of the authors credited on it.
+-- The tables added later that a product is built from get their triggers where they are
+-- created.
+SELECT diesel_manage_updated_at('books');
+
+CREATE FUNCTION touch_book_of_row() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        UPDATE books SET updated_at = now() AT TIME ZONE 'utc' WHERE id = OLD.book_id;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        UPDATE books SET updated_at = now() AT TIME ZONE 'utc' WHERE id = NEW.book_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE FUNCTION touch_books_of_author() RETURNS trigger AS $$
+BEGIN
+    UPDATE books SET updated_at = now() AT TIME ZONE 'utc'
+    WHERE id IN (SELECT book_id FROM book_authors WHERE author_id = NEW.id);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER touch_books AFTER INSERT OR UPDATE OR DELETE ON book_authors
+    FOR EACH ROW EXECUTE PROCEDURE touch_book_of_row();
+CREATE TRIGGER touch_books AFTER INSERT OR UPDATE OR DELETE ON book_identifiers
+    FOR EACH ROW EXECUTE PROCEDURE touch_book_of_row();
+CREATE TRIGGER touch_books AFTER UPDATE OF name ON authors
+    FOR EACH ROW EXECUTE PROCEDURE touch_books_of_author();
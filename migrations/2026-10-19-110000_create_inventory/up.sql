@@ -0,0 +1,15 @@
+CREATE TABLE locations (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL UNIQUE
+);
+
+CREATE TABLE inventory (
+    book_id INT NOT NULL REFERENCES books (id) ON DELETE CASCADE,
+    location_id INT NOT NULL REFERENCES locations (id) ON DELETE CASCADE,
+    quantity INT NOT NULL DEFAULT 0 CHECK (quantity >= 0),
+    reserved INT NOT NULL DEFAULT 0 CHECK (reserved >= 0 AND reserved <= quantity),
+    reorder_threshold INT NOT NULL DEFAULT 0 CHECK (reorder_threshold >= 0),
+    PRIMARY KEY (book_id, location_id)
+);
+
+CREATE INDEX inventory_location_id_idx ON inventory (location_id);
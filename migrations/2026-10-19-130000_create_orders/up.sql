@@ -0,0 +1,29 @@
+CREATE TABLE customers (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    email VARCHAR(255) NOT NULL UNIQUE
+);
+
+CREATE TABLE orders (
+    id SERIAL PRIMARY KEY,
+    customer_id INT NOT NULL REFERENCES customers (id) ON DELETE CASCADE,
+    status VARCHAR(16) NOT NULL CHECK (
+        status IN ('cart', 'pending', 'paid', 'shipped', 'cancelled', 'refunded')
+    ),
+    currency VARCHAR(3) NOT NULL,
+    location_id INT REFERENCES locations (id) ON DELETE SET NULL,
+    total_minor BIGINT CHECK (total_minor >= 0),
+    created_at TIMESTAMP NOT NULL,
+    placed_at TIMESTAMP
+);
+
+CREATE INDEX orders_customer_id_idx ON orders (customer_id);
+
+CREATE TABLE order_lines (
+    id SERIAL PRIMARY KEY,
+    order_id INT NOT NULL REFERENCES orders (id) ON DELETE CASCADE,
+    book_id INT REFERENCES books (id) ON DELETE SET NULL,
+    quantity INT NOT NULL CHECK (quantity > 0),
+    unit_price_minor BIGINT CHECK (unit_price_minor >= 0),
+    UNIQUE (order_id, book_id)
+);
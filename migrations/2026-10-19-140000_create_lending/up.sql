@@ -0,0 +1,45 @@
+CREATE TABLE members (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    email VARCHAR(255) NOT NULL UNIQUE
+);
+
+CREATE TABLE copies (
+    id SERIAL PRIMARY KEY,
+    book_id INT NOT NULL REFERENCES books (id) ON DELETE CASCADE,
+    barcode VARCHAR(64) NOT NULL UNIQUE
+);
+
+CREATE INDEX copies_book_id_idx ON copies (book_id);
+
+CREATE TABLE loans (
+    id SERIAL PRIMARY KEY,
+    copy_id INT NOT NULL REFERENCES copies (id) ON DELETE CASCADE,
+    member_id INT NOT NULL REFERENCES members (id) ON DELETE CASCADE,
+    checked_out_at TIMESTAMP NOT NULL,
+    due_at TIMESTAMP NOT NULL,
+    returned_at TIMESTAMP,
+    renewals INT NOT NULL DEFAULT 0 CHECK (renewals >= 0),
+    CHECK (due_at > checked_out_at)
+);
+
+-- A copy can only be out on one loan at a time.
+CREATE UNIQUE INDEX loans_open_copy_idx ON loans (copy_id) WHERE returned_at IS NULL;
+CREATE INDEX loans_member_id_idx ON loans (member_id);
+
+CREATE TABLE holds (
+    id SERIAL PRIMARY KEY,
+    book_id INT NOT NULL REFERENCES books (id) ON DELETE CASCADE,
+    member_id INT NOT NULL REFERENCES members (id) ON DELETE CASCADE,
+    status VARCHAR(16) NOT NULL CHECK (
+        status IN ('waiting', 'ready', 'fulfilled', 'cancelled')
+    ),
+    copy_id INT REFERENCES copies (id) ON DELETE SET NULL,
+    placed_at TIMESTAMP NOT NULL,
+    ready_at TIMESTAMP,
+    closed_at TIMESTAMP
+);
+
+-- A member holds a book at most once until the hold is fulfilled or cancelled.
+CREATE UNIQUE INDEX holds_open_member_idx ON holds (book_id, member_id)
+    WHERE status IN ('waiting', 'ready');
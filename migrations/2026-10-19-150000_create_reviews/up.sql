@@ -0,0 +1,12 @@
+CREATE TABLE reviews (
+    id SERIAL PRIMARY KEY,
+    book_id INT NOT NULL REFERENCES books (id) ON DELETE CASCADE,
+    customer_id INT NOT NULL REFERENCES customers (id) ON DELETE CASCADE,
+    rating INT NOT NULL CHECK (rating BETWEEN 1 AND 5),
+    body TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL,
+    UNIQUE (book_id, customer_id)
+);
+
+CREATE INDEX reviews_customer_id_idx ON reviews (customer_id);
@@ -0,0 +1,13 @@
+CREATE TABLE series (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL
+);
+
+-- Positions can be fractional so a novella can sit between two novels, for example at 2.5.
+CREATE TABLE book_series (
+    book_id INT NOT NULL REFERENCES books (id) ON DELETE CASCADE,
+    series_id INT NOT NULL REFERENCES series (id) ON DELETE CASCADE,
+    position DOUBLE PRECISION NOT NULL CHECK (position >= 0),
+    PRIMARY KEY (book_id, series_id),
+    UNIQUE (series_id, position)
+);
@@ -0,0 +1,18 @@
+CREATE OR REPLACE FUNCTION touch_books_of_author() RETURNS trigger AS $$
+BEGIN
+    UPDATE books SET updated_at = now() AT TIME ZONE 'utc'
+    WHERE id IN (SELECT book_id FROM book_authors WHERE author_id = NEW.id);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DELETE FROM book_authors WHERE role <> 'author';
+ALTER TABLE book_authors DROP COLUMN position;
+ALTER TABLE book_authors DROP CONSTRAINT book_authors_pkey;
+ALTER TABLE book_authors ADD PRIMARY KEY (author_id, book_id);
+ALTER TABLE book_authors DROP COLUMN role;
+DROP TABLE work_authors;
+DROP FUNCTION touch_editions_of_row();
+DROP INDEX books_work_id_idx;
+ALTER TABLE books DROP COLUMN work_id;
+DROP TABLE works;
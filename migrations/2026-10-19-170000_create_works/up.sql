@@ -0,0 +1,51 @@
+CREATE TABLE works (
+    id SERIAL PRIMARY KEY,
+    title VARCHAR(255) NOT NULL
+);
+
+-- Books are editions. A book without a work stands on its own and keeps its credits.
+ALTER TABLE books ADD COLUMN work_id INT REFERENCES works (id) ON DELETE SET NULL;
+
+CREATE INDEX books_work_id_idx ON books (work_id);
+
+-- `position` is where each person appears in the list of credits, lowest first. Credits
+-- that share a position fall back to the order they were added in.
+CREATE TABLE work_authors (
+    work_id INT NOT NULL REFERENCES works (id) ON DELETE CASCADE,
+    author_id INT NOT NULL REFERENCES authors (id) ON DELETE CASCADE,
+    position INT NOT NULL DEFAULT 0,
+    PRIMARY KEY (work_id, author_id)
+);
+
+-- Credits on a book are edition credits: its authors while it has no work, and
+-- contributors such as translators that only apply to that edition.
+-- A person can hold several roles on the same edition, so the role is part of the key.
+ALTER TABLE book_authors ADD COLUMN role VARCHAR(32) NOT NULL DEFAULT 'author';
+ALTER TABLE book_authors DROP CONSTRAINT book_authors_pkey;
+ALTER TABLE book_authors ADD PRIMARY KEY (author_id, book_id, role);
+ALTER TABLE book_authors ADD COLUMN position INT NOT NULL DEFAULT 0;
+
+-- Credits on a work change every edition of it.
+CREATE FUNCTION touch_editions_of_row() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        UPDATE books SET updated_at = now() AT TIME ZONE 'utc' WHERE work_id = OLD.work_id;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        UPDATE books SET updated_at = now() AT TIME ZONE 'utc' WHERE work_id = NEW.work_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION touch_books_of_author() RETURNS trigger AS $$
+BEGIN
+    UPDATE books SET updated_at = now() AT TIME ZONE 'utc'
+    WHERE id IN (SELECT book_id FROM book_authors WHERE author_id = NEW.id)
+        OR work_id IN (SELECT work_id FROM work_authors WHERE author_id = NEW.id);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER touch_books AFTER INSERT OR UPDATE OR DELETE ON work_authors
+    FOR EACH ROW EXECUTE PROCEDURE touch_editions_of_row();
@@ -0,0 +1,14 @@
+-- Titles of a book by BCP 47 language tag, such as `de` or `de-AT`. At most one of them is
+-- the title the book was first published under.
+CREATE TABLE book_titles (
+    book_id INT NOT NULL REFERENCES books (id) ON DELETE CASCADE,
+    language VARCHAR(35) NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    is_original BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY (book_id, language)
+);
+
+CREATE UNIQUE INDEX book_titles_original_idx ON book_titles (book_id) WHERE is_original;
+
+CREATE TRIGGER touch_books AFTER INSERT OR UPDATE OR DELETE ON book_titles
+    FOR EACH ROW EXECUTE PROCEDURE touch_book_of_row();
@@ -0,0 +1,4 @@
+DROP TABLE book_author_names;
+DROP TABLE author_names;
+
+DROP FUNCTION unicode_lower(TEXT);
@@ -0,0 +1,74 @@
+-- Lower case that handles letters outside ASCII, for comparing names without regard to case.
+-- lower() only lowers ASCII under the C locale or a SQL_ASCII database, so this lowers the
+-- Latin, Greek and Cyrillic capitals itself, reading the UTF-8 bytes directly so it gives
+-- the same answer whatever the database encoding and locale.
+CREATE FUNCTION unicode_lower(value TEXT) RETURNS TEXT AS $$
+DECLARE
+    bytes BYTEA := convert_to(lower(value), 'UTF8');
+    lowered BYTEA := '';
+    at INT := 0;
+    lead INT;
+    code_point INT;
+BEGIN
+    WHILE at < length(bytes) LOOP
+        lead := get_byte(bytes, at);
+        IF lead BETWEEN 192 AND 223 THEN
+            code_point := ((lead & 31) << 6) | (get_byte(bytes, at + 1) & 63);
+            code_point := CASE
+                WHEN code_point BETWEEN 192 AND 222 AND code_point <> 215 THEN code_point + 32
+                WHEN code_point = 304 THEN 105
+                WHEN code_point = 376 THEN 255
+                WHEN code_point BETWEEN 256 AND 311 OR code_point BETWEEN 330 AND 375
+                    THEN code_point | 1
+                WHEN code_point BETWEEN 313 AND 328 OR code_point BETWEEN 377 AND 382
+                    THEN code_point + (code_point & 1)
+                WHEN code_point = 902 THEN 940
+                WHEN code_point BETWEEN 904 AND 906 THEN code_point + 37
+                WHEN code_point = 908 THEN 972
+                WHEN code_point BETWEEN 910 AND 911 THEN code_point + 63
+                WHEN code_point BETWEEN 913 AND 939 AND code_point <> 930 THEN code_point + 32
+                WHEN code_point BETWEEN 1024 AND 1039 THEN code_point + 80
+                WHEN code_point BETWEEN 1040 AND 1071 THEN code_point + 32
+                ELSE code_point
+            END;
+            IF code_point < 128 THEN
+                lowered := lowered || decode(lpad(to_hex(code_point), 2, '0'), 'hex');
+            ELSE
+                lowered := lowered || decode(
+                    to_hex(((192 | (code_point >> 6)) << 8) | 128 | (code_point & 63)),
+                    'hex'
+                );
+            END IF;
+            at := at + 2;
+        ELSE
+            lowered := lowered || substring(bytes FROM at + 1 FOR 1);
+            at := at + 1;
+        END IF;
+    END LOOP;
+
+    RETURN convert_from(lowered, 'UTF8');
+END;
+$$ LANGUAGE plpgsql IMMUTABLE STRICT PARALLEL SAFE;
+
+-- Other names an author is known or published under: variant spellings such as initials,
+-- and pseudonyms.
+CREATE TABLE author_names (
+    id SERIAL PRIMARY KEY,
+    author_id INT NOT NULL REFERENCES authors (id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    kind VARCHAR(16) NOT NULL DEFAULT 'variant' CHECK (kind IN ('variant', 'pseudonym'))
+);
+
+CREATE UNIQUE INDEX author_names_author_name_idx ON author_names (author_id, unicode_lower(name));
+CREATE INDEX author_names_name_idx ON author_names (unicode_lower(name));
+
+-- The name a credited author appears under on a particular book.
+CREATE TABLE book_author_names (
+    book_id INT NOT NULL REFERENCES books (id) ON DELETE CASCADE,
+    author_id INT NOT NULL REFERENCES authors (id) ON DELETE CASCADE,
+    author_name_id INT NOT NULL REFERENCES author_names (id) ON DELETE CASCADE,
+    PRIMARY KEY (book_id, author_id)
+);
+
+CREATE TRIGGER touch_books AFTER INSERT OR UPDATE OR DELETE ON book_author_names
+    FOR EACH ROW EXECUTE PROCEDURE touch_book_of_row();
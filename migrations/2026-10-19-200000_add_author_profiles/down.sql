@@ -0,0 +1,5 @@
+DROP TABLE author_identifiers;
+ALTER TABLE authors DROP COLUMN biography;
+ALTER TABLE authors DROP COLUMN nationality;
+ALTER TABLE authors DROP COLUMN death_date;
+ALTER TABLE authors DROP COLUMN birth_date;
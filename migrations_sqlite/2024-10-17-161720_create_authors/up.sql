@@ -0,0 +1,5 @@
+-- Your SQL goes here
+CREATE TABLE authors (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    name VARCHAR(255) NOT NULL
+);
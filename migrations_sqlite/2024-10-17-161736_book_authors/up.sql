@@ -0,0 +1,6 @@
+-- Your SQL goes here
+CREATE TABLE book_authors (
+    author_id INTEGER NOT NULL REFERENCES authors (id),
+    book_id INTEGER NOT NULL REFERENCES books (id),
+    PRIMARY KEY (author_id, book_id)
+);
@@ -0,0 +1,15 @@
+DROP TRIGGER authors_touch_books_on_update;
+DROP TRIGGER book_identifiers_touch_books_on_insert;
+DROP TRIGGER book_identifiers_touch_books_on_update;
+DROP TRIGGER book_identifiers_touch_books_on_delete;
+DROP TRIGGER book_authors_touch_books_on_insert;
+DROP TRIGGER book_authors_touch_books_on_update;
+DROP TRIGGER book_authors_touch_books_on_delete;
+
+DROP TRIGGER books_set_updated_at;
+
+DROP TRIGGER books_set_updated_at_on_insert;
+
+DROP INDEX books_updated_at_idx;
+
+ALTER TABLE books DROP COLUMN updated_at;
@@ -0,0 +1,59 @@
+ALTER TABLE books
+    ADD COLUMN updated_at TIMESTAMP NOT NULL DEFAULT '1970-01-01 00:00:00';
+
+UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now');
+
+CREATE INDEX books_updated_at_idx ON books (updated_at, id);
+
+CREATE TRIGGER books_set_updated_at_on_insert AFTER INSERT ON books
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = NEW.id;
+END;
+
+-- A book counts as changed when its own row changes or when anything an ONIX product is
+-- built from changes: its credits, identifiers and the names of the authors credited on it.
+-- The tables added later that a product is built from get their triggers where they are
+-- created. This is what `diesel_manage_updated_at` sets up on Postgres.
+CREATE TRIGGER books_set_updated_at AFTER UPDATE ON books
+WHEN NEW.updated_at IS OLD.updated_at
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER book_authors_touch_books_on_insert AFTER INSERT ON book_authors
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = NEW.book_id;
+END;
+
+CREATE TRIGGER book_authors_touch_books_on_update AFTER UPDATE ON book_authors
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = OLD.book_id;
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = NEW.book_id;
+END;
+
+CREATE TRIGGER book_authors_touch_books_on_delete AFTER DELETE ON book_authors
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = OLD.book_id;
+END;
+
+CREATE TRIGGER book_identifiers_touch_books_on_insert AFTER INSERT ON book_identifiers
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = NEW.book_id;
+END;
+
+CREATE TRIGGER book_identifiers_touch_books_on_update AFTER UPDATE ON book_identifiers
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = OLD.book_id;
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = NEW.book_id;
+END;
+
+CREATE TRIGGER book_identifiers_touch_books_on_delete AFTER DELETE ON book_identifiers
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = OLD.book_id;
+END;
+
+CREATE TRIGGER authors_touch_books_on_update AFTER UPDATE OF name ON authors
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now')
+    WHERE id IN (SELECT book_id FROM book_authors WHERE author_id = NEW.id);
+END;
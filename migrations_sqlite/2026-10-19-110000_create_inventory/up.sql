@@ -0,0 +1,15 @@
+CREATE TABLE locations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    name VARCHAR(255) NOT NULL UNIQUE
+);
+
+CREATE TABLE inventory (
+    book_id INTEGER NOT NULL REFERENCES books (id) ON DELETE CASCADE,
+    location_id INTEGER NOT NULL REFERENCES locations (id) ON DELETE CASCADE,
+    quantity INTEGER NOT NULL DEFAULT 0 CHECK (quantity >= 0),
+    reserved INTEGER NOT NULL DEFAULT 0 CHECK (reserved >= 0 AND reserved <= quantity),
+    reorder_threshold INTEGER NOT NULL DEFAULT 0 CHECK (reorder_threshold >= 0),
+    PRIMARY KEY (book_id, location_id)
+);
+
+CREATE INDEX inventory_location_id_idx ON inventory (location_id);
@@ -0,0 +1,9 @@
+CREATE TABLE book_prices (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    book_id INTEGER NOT NULL REFERENCES books (id) ON DELETE CASCADE,
+    currency VARCHAR(3) NOT NULL,
+    amount_minor BIGINT NOT NULL CHECK (amount_minor >= 0),
+    effective_from TIMESTAMP NOT NULL,
+    effective_to TIMESTAMP CHECK (effective_to > effective_from),
+    UNIQUE (book_id, currency, effective_from)
+);
@@ -0,0 +1,29 @@
+CREATE TABLE customers (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    email VARCHAR(255) NOT NULL UNIQUE
+);
+
+CREATE TABLE orders (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    customer_id INTEGER NOT NULL REFERENCES customers (id) ON DELETE CASCADE,
+    status VARCHAR(16) NOT NULL CHECK (
+        status IN ('cart', 'pending', 'paid', 'shipped', 'cancelled', 'refunded')
+    ),
+    currency VARCHAR(3) NOT NULL,
+    location_id INTEGER REFERENCES locations (id) ON DELETE SET NULL,
+    total_minor BIGINT CHECK (total_minor >= 0),
+    created_at TIMESTAMP NOT NULL,
+    placed_at TIMESTAMP
+);
+
+CREATE INDEX orders_customer_id_idx ON orders (customer_id);
+
+CREATE TABLE order_lines (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    order_id INTEGER NOT NULL REFERENCES orders (id) ON DELETE CASCADE,
+    book_id INTEGER REFERENCES books (id) ON DELETE SET NULL,
+    quantity INTEGER NOT NULL CHECK (quantity > 0),
+    unit_price_minor BIGINT CHECK (unit_price_minor >= 0),
+    UNIQUE (order_id, book_id)
+);
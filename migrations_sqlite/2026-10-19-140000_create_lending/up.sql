@@ -0,0 +1,45 @@
+CREATE TABLE members (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    email VARCHAR(255) NOT NULL UNIQUE
+);
+
+CREATE TABLE copies (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    book_id INTEGER NOT NULL REFERENCES books (id) ON DELETE CASCADE,
+    barcode VARCHAR(64) NOT NULL UNIQUE
+);
+
+CREATE INDEX copies_book_id_idx ON copies (book_id);
+
+CREATE TABLE loans (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    copy_id INTEGER NOT NULL REFERENCES copies (id) ON DELETE CASCADE,
+    member_id INTEGER NOT NULL REFERENCES members (id) ON DELETE CASCADE,
+    checked_out_at TIMESTAMP NOT NULL,
+    due_at TIMESTAMP NOT NULL,
+    returned_at TIMESTAMP,
+    renewals INTEGER NOT NULL DEFAULT 0 CHECK (renewals >= 0),
+    CHECK (due_at > checked_out_at)
+);
+
+-- A copy can only be out on one loan at a time.
+CREATE UNIQUE INDEX loans_open_copy_idx ON loans (copy_id) WHERE returned_at IS NULL;
+CREATE INDEX loans_member_id_idx ON loans (member_id);
+
+CREATE TABLE holds (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    book_id INTEGER NOT NULL REFERENCES books (id) ON DELETE CASCADE,
+    member_id INTEGER NOT NULL REFERENCES members (id) ON DELETE CASCADE,
+    status VARCHAR(16) NOT NULL CHECK (
+        status IN ('waiting', 'ready', 'fulfilled', 'cancelled')
+    ),
+    copy_id INTEGER REFERENCES copies (id) ON DELETE SET NULL,
+    placed_at TIMESTAMP NOT NULL,
+    ready_at TIMESTAMP,
+    closed_at TIMESTAMP
+);
+
+-- A member holds a book at most once until the hold is fulfilled or cancelled.
+CREATE UNIQUE INDEX holds_open_member_idx ON holds (book_id, member_id)
+    WHERE status IN ('waiting', 'ready');
@@ -0,0 +1,40 @@
+DROP TRIGGER authors_touch_books_on_update;
+
+CREATE TABLE book_authors_without_roles (
+    author_id INTEGER NOT NULL REFERENCES authors (id),
+    book_id INTEGER NOT NULL REFERENCES books (id),
+    PRIMARY KEY (author_id, book_id)
+);
+
+INSERT INTO book_authors_without_roles (author_id, book_id)
+SELECT author_id, book_id FROM book_authors WHERE role = 'author';
+
+DROP TABLE book_authors;
+ALTER TABLE book_authors_without_roles RENAME TO book_authors;
+
+CREATE TRIGGER book_authors_touch_books_on_insert AFTER INSERT ON book_authors
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = NEW.book_id;
+END;
+
+CREATE TRIGGER book_authors_touch_books_on_update AFTER UPDATE ON book_authors
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = OLD.book_id;
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = NEW.book_id;
+END;
+
+CREATE TRIGGER book_authors_touch_books_on_delete AFTER DELETE ON book_authors
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = OLD.book_id;
+END;
+
+CREATE TRIGGER authors_touch_books_on_update AFTER UPDATE OF name ON authors
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now')
+    WHERE id IN (SELECT book_id FROM book_authors WHERE author_id = NEW.id);
+END;
+
+DROP TABLE work_authors;
+DROP INDEX books_work_id_idx;
+ALTER TABLE books DROP COLUMN work_id;
+DROP TABLE works;
@@ -0,0 +1,79 @@
+CREATE TABLE works (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    title VARCHAR(255) NOT NULL
+);
+
+-- Books are editions. A book without a work stands on its own and keeps its credits.
+ALTER TABLE books ADD COLUMN work_id INTEGER REFERENCES works (id) ON DELETE SET NULL;
+
+CREATE INDEX books_work_id_idx ON books (work_id);
+
+-- `position` is where each person appears in the list of credits, lowest first. Credits
+-- that share a position fall back to the order they were added in.
+CREATE TABLE work_authors (
+    work_id INTEGER NOT NULL REFERENCES works (id) ON DELETE CASCADE,
+    author_id INTEGER NOT NULL REFERENCES authors (id) ON DELETE CASCADE,
+    position INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (work_id, author_id)
+);
+
+-- Credits on a book are edition credits: its authors while it has no work, and
+-- contributors such as translators that only apply to that edition.
+-- A person can hold several roles on the same edition, so the role is part of the key.
+-- SQLite cannot change a primary key in place, so the table is rebuilt, and the triggers
+-- that read it are created again afterwards.
+DROP TRIGGER authors_touch_books_on_update;
+
+CREATE TABLE book_authors_with_roles (
+    author_id INTEGER NOT NULL REFERENCES authors (id),
+    book_id INTEGER NOT NULL REFERENCES books (id),
+    role VARCHAR(32) NOT NULL DEFAULT 'author',
+    position INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (author_id, book_id, role)
+);
+
+INSERT INTO book_authors_with_roles (author_id, book_id)
+SELECT author_id, book_id FROM book_authors;
+
+DROP TABLE book_authors;
+ALTER TABLE book_authors_with_roles RENAME TO book_authors;
+
+CREATE TRIGGER book_authors_touch_books_on_insert AFTER INSERT ON book_authors
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = NEW.book_id;
+END;
+
+CREATE TRIGGER book_authors_touch_books_on_update AFTER UPDATE ON book_authors
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = OLD.book_id;
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = NEW.book_id;
+END;
+
+CREATE TRIGGER book_authors_touch_books_on_delete AFTER DELETE ON book_authors
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = OLD.book_id;
+END;
+
+-- Credits on a work change every edition of it.
+CREATE TRIGGER work_authors_touch_books_on_insert AFTER INSERT ON work_authors
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE work_id = NEW.work_id;
+END;
+
+CREATE TRIGGER work_authors_touch_books_on_update AFTER UPDATE ON work_authors
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE work_id = OLD.work_id;
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE work_id = NEW.work_id;
+END;
+
+CREATE TRIGGER work_authors_touch_books_on_delete AFTER DELETE ON work_authors
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE work_id = OLD.work_id;
+END;
+
+CREATE TRIGGER authors_touch_books_on_update AFTER UPDATE OF name ON authors
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now')
+    WHERE id IN (SELECT book_id FROM book_authors WHERE author_id = NEW.id)
+        OR work_id IN (SELECT work_id FROM work_authors WHERE author_id = NEW.id);
+END;
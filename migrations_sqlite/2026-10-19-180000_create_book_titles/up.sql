@@ -0,0 +1,27 @@
+-- Titles of a book by BCP 47 language tag, such as `de` or `de-AT`. At most one of them is
+-- the title the book was first published under.
+CREATE TABLE book_titles (
+    book_id INTEGER NOT NULL REFERENCES books (id) ON DELETE CASCADE,
+    language VARCHAR(35) NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    is_original BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY (book_id, language)
+);
+
+CREATE UNIQUE INDEX book_titles_original_idx ON book_titles (book_id) WHERE is_original;
+
+CREATE TRIGGER book_titles_touch_books_on_insert AFTER INSERT ON book_titles
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = NEW.book_id;
+END;
+
+CREATE TRIGGER book_titles_touch_books_on_update AFTER UPDATE ON book_titles
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = OLD.book_id;
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = NEW.book_id;
+END;
+
+CREATE TRIGGER book_titles_touch_books_on_delete AFTER DELETE ON book_titles
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = OLD.book_id;
+END;
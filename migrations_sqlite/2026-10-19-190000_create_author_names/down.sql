@@ -0,0 +1,2 @@
+DROP TABLE book_author_names;
+DROP TABLE author_names;
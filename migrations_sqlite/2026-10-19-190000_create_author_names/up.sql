@@ -0,0 +1,37 @@
+-- Other names an author is known or published under: variant spellings such as initials,
+-- and pseudonyms.
+CREATE TABLE author_names (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    author_id INTEGER NOT NULL REFERENCES authors (id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    kind VARCHAR(16) NOT NULL DEFAULT 'variant' CHECK (kind IN ('variant', 'pseudonym'))
+);
+
+-- Names are compared ignoring case with the unicode_lower function every connection opened
+-- through `establish` registers, so writing to this table needs one of those connections.
+CREATE UNIQUE INDEX author_names_author_name_idx ON author_names (author_id, unicode_lower(name));
+CREATE INDEX author_names_name_idx ON author_names (unicode_lower(name));
+
+-- The name a credited author appears under on a particular book.
+CREATE TABLE book_author_names (
+    book_id INTEGER NOT NULL REFERENCES books (id) ON DELETE CASCADE,
+    author_id INTEGER NOT NULL REFERENCES authors (id) ON DELETE CASCADE,
+    author_name_id INTEGER NOT NULL REFERENCES author_names (id) ON DELETE CASCADE,
+    PRIMARY KEY (book_id, author_id)
+);
+
+CREATE TRIGGER book_author_names_touch_books_on_insert AFTER INSERT ON book_author_names
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = NEW.book_id;
+END;
+
+CREATE TRIGGER book_author_names_touch_books_on_update AFTER UPDATE ON book_author_names
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = OLD.book_id;
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = NEW.book_id;
+END;
+
+CREATE TRIGGER book_author_names_touch_books_on_delete AFTER DELETE ON book_author_names
+BEGIN
+    UPDATE books SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = OLD.book_id;
+END;
@@ -0,0 +1,15 @@
+-- Dates may be partial or approximate, so they are stored as text: 1835, 1835-11 or
+-- 1835-11-30, with a trailing ~ for circa.
+ALTER TABLE authors ADD COLUMN birth_date VARCHAR(16);
+ALTER TABLE authors ADD COLUMN death_date VARCHAR(16);
+ALTER TABLE authors ADD COLUMN nationality VARCHAR(2);
+ALTER TABLE authors ADD COLUMN biography TEXT;
+
+CREATE TABLE author_identifiers (
+    author_id INTEGER NOT NULL REFERENCES authors (id) ON DELETE CASCADE,
+    scheme VARCHAR(32) NOT NULL,
+    value VARCHAR(64) NOT NULL,
+    PRIMARY KEY (scheme, value)
+);
+
+CREATE INDEX author_identifiers_author_id_idx ON author_identifiers (author_id);
@@ -0,0 +1,101 @@
+use super::{ApiError, AppState, JsonBody};
+use crate::{
+    models::{Author, NewAuthor},
+    queries::author_queries,
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use diesel::Connection;
+use eyre::eyre;
+
+pub(super) async fn list_authors(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<Author>>, ApiError> {
+    let authors = state
+        .with_connection(author_queries::get_all_authors)
+        .await?;
+
+    Ok(Json(authors))
+}
+
+pub(super) async fn create_author(
+    State(state): State<AppState>,
+    JsonBody(payload): JsonBody<NewAuthor>,
+) -> Result<(StatusCode, Json<Author>), ApiError> {
+    let author = state
+        .with_connection(move |connection| {
+            let id = author_queries::create_author(&payload.name, connection)?;
+
+            author_queries::get_author_by_id(id, connection)?
+                .ok_or_else(|| eyre!("created author {id} disappeared"))
+        })
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(author)))
+}
+
+pub(super) async fn get_author(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<Json<Author>, ApiError> {
+    let author = state
+        .with_connection(move |connection| author_queries::get_author_by_id(id, connection))
+        .await?
+        .ok_or_else(|| author_not_found(id))?;
+
+    Ok(Json(author))
+}
+
+pub(super) async fn update_author(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    JsonBody(payload): JsonBody<NewAuthor>,
+) -> Result<Json<Author>, ApiError> {
+    let author = state
+        .with_connection(move |connection| {
+            connection.transaction(|connection| {
+                if author_queries::get_author_by_id(id, connection)?.is_none() {
+                    return Ok(None);
+                }
+
+                author_queries::update_author(id, &payload.name, connection)?;
+                author_queries::get_author_by_id(id, connection)
+            })
+        })
+        .await?
+        .ok_or_else(|| author_not_found(id))?;
+
+    Ok(Json(author))
+}
+
+pub(super) async fn delete_author(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, ApiError> {
+    let deleted = state
+        .with_connection(move |connection| {
+            connection.transaction(|connection| {
+                if author_queries::get_author_by_id(id, connection)?.is_none() {
+                    return Ok(false);
+                }
+
+                author_queries::delete_author(id, connection)?;
+
+                Ok(true)
+            })
+        })
+        .await?;
+
+    if !deleted {
+        return Err(author_not_found(id));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn author_not_found(id: i32) -> ApiError {
+    ApiError::NotFound(format!("author {id} not found"))
+}
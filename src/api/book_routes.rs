@@ -0,0 +1,167 @@
+use super::{ApiError, AppState, BookAuthorPayload, JsonBody};
+use crate::{
+    models::{Author, Book, BookAuthor, NewBook},
+    queries::{author_queries, book_author_queries, book_queries},
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use diesel::Connection;
+use eyre::eyre;
+
+pub(super) async fn list_books(State(state): State<AppState>) -> Result<Json<Vec<Book>>, ApiError> {
+    let books = state.with_connection(book_queries::get_all_books).await?;
+
+    Ok(Json(books))
+}
+
+pub(super) async fn create_book(
+    State(state): State<AppState>,
+    JsonBody(payload): JsonBody<NewBook>,
+) -> Result<(StatusCode, Json<Book>), ApiError> {
+    let book = state
+        .with_connection(move |connection| {
+            let id = book_queries::create_book(&payload.name, connection)?;
+
+            book_queries::get_book_by_id(id, connection)?
+                .ok_or_else(|| eyre!("created book {id} disappeared"))
+        })
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(book)))
+}
+
+pub(super) async fn get_book(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<Json<Book>, ApiError> {
+    let book = state
+        .with_connection(move |connection| book_queries::get_book_by_id(id, connection))
+        .await?
+        .ok_or_else(|| book_not_found(id))?;
+
+    Ok(Json(book))
+}
+
+pub(super) async fn update_book(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    JsonBody(payload): JsonBody<NewBook>,
+) -> Result<Json<Book>, ApiError> {
+    let book = state
+        .with_connection(move |connection| {
+            connection.transaction(|connection| {
+                if book_queries::get_book_by_id(id, connection)?.is_none() {
+                    return Ok(None);
+                }
+
+                book_queries::update_book(id, &payload.name, connection)?;
+                book_queries::get_book_by_id(id, connection)
+            })
+        })
+        .await?
+        .ok_or_else(|| book_not_found(id))?;
+
+    Ok(Json(book))
+}
+
+pub(super) async fn delete_book(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, ApiError> {
+    let deleted = state
+        .with_connection(move |connection| {
+            connection.transaction(|connection| {
+                if book_queries::get_book_by_id(id, connection)?.is_none() {
+                    return Ok(false);
+                }
+
+                book_queries::delete_book(id, connection)?;
+
+                Ok(true)
+            })
+        })
+        .await?;
+
+    if !deleted {
+        return Err(book_not_found(id));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub(super) async fn list_book_authors(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<Author>>, ApiError> {
+    let book_with_authors = state
+        .with_connection(move |connection| {
+            book_author_queries::get_book_with_authors(id, connection)
+        })
+        .await?
+        .ok_or_else(|| book_not_found(id))?;
+    let (_book, authors) = book_with_authors.into_parts();
+
+    Ok(Json(authors))
+}
+
+pub(super) async fn add_book_author(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    JsonBody(payload): JsonBody<BookAuthorPayload>,
+) -> Result<(StatusCode, Json<BookAuthor>), ApiError> {
+    let author_id = payload.author_id;
+
+    state
+        .with_connection(move |connection| {
+            connection.transaction(|connection| {
+                if book_queries::get_book_by_id(id, connection)?.is_none() {
+                    return Ok(Err(book_not_found(id)));
+                }
+
+                if author_queries::get_author_by_id(author_id, connection)?.is_none() {
+                    return Ok(Err(ApiError::Unprocessable(format!(
+                        "author {author_id} does not exist"
+                    ))));
+                }
+
+                book_author_queries::associate_book_with_author(id, author_id, connection)?;
+
+                Ok(Ok(()))
+            })
+        })
+        .await??;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(BookAuthor {
+            book_id: id,
+            author_id,
+        }),
+    ))
+}
+
+pub(super) async fn remove_book_author(
+    State(state): State<AppState>,
+    Path((id, author_id)): Path<(i32, i32)>,
+) -> Result<StatusCode, ApiError> {
+    let removed = state
+        .with_connection(move |connection| {
+            book_author_queries::dissociate_book_from_author(id, author_id, connection)
+        })
+        .await?;
+
+    if !removed {
+        return Err(ApiError::NotFound(format!(
+            "author {author_id} is not associated with book {id}"
+        )));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn book_not_found(id: i32) -> ApiError {
+    ApiError::NotFound(format!("book {id} not found"))
+}
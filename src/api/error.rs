@@ -0,0 +1,88 @@
+use crate::{instrumentation::record_error, validation::ValidationError};
+use axum::{
+    extract::rejection::JsonRejection,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use serde_json::json;
+
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(String),
+    NotFound(String),
+    Conflict(String),
+    Unprocessable(String),
+    Validation(ValidationError),
+    Internal(eyre::Report),
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, body) = match self {
+            ApiError::BadRequest(message) => (StatusCode::BAD_REQUEST, json!({ "error": message })),
+            ApiError::NotFound(message) => (StatusCode::NOT_FOUND, json!({ "error": message })),
+            ApiError::Conflict(message) => (StatusCode::CONFLICT, json!({ "error": message })),
+            ApiError::Unprocessable(message) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                json!({ "error": message }),
+            ),
+            ApiError::Validation(error) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                json!({ "error": error.to_string(), "field": error.field }),
+            ),
+            ApiError::Internal(error) => {
+                record_error("internal server error", &error);
+
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    json!({ "error": "internal server error" }),
+                )
+            }
+        };
+
+        (status, Json(body)).into_response()
+    }
+}
+
+impl From<eyre::Report> for ApiError {
+    fn from(error: eyre::Report) -> Self {
+        if let Some(validation_error) = error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<ValidationError>())
+        {
+            return ApiError::Validation(validation_error.clone());
+        }
+
+        let database_error_kind =
+            error
+                .chain()
+                .find_map(|cause| match cause.downcast_ref::<DieselError>() {
+                    Some(DieselError::DatabaseError(kind, _)) => Some(kind),
+                    _ => None,
+                });
+
+        match database_error_kind {
+            Some(DatabaseErrorKind::UniqueViolation) => {
+                ApiError::Conflict("the resource already exists".to_owned())
+            }
+            Some(DatabaseErrorKind::ForeignKeyViolation) => {
+                ApiError::Conflict("the resource is still referenced by another record".to_owned())
+            }
+            Some(DatabaseErrorKind::NotNullViolation | DatabaseErrorKind::CheckViolation) => {
+                ApiError::Unprocessable(error.root_cause().to_string())
+            }
+            _ => ApiError::Internal(error),
+        }
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        match rejection {
+            JsonRejection::JsonDataError(error) => ApiError::Unprocessable(error.body_text()),
+            rejection => ApiError::BadRequest(rejection.body_text()),
+        }
+    }
+}
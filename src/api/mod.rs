@@ -0,0 +1,99 @@
+mod author_routes;
+mod book_routes;
+mod error;
+
+use crate::{
+    connect::{DbConnection, DbPool},
+    opds::OpdsCatalog,
+};
+use axum::{
+    extract::FromRequest,
+    routing::{delete, get},
+    Json, Router,
+};
+use eyre::Context;
+use serde::Deserialize;
+
+pub use error::ApiError;
+
+#[derive(Clone)]
+pub struct AppState {
+    pool: DbPool,
+}
+
+impl AppState {
+    async fn with_connection<T, F>(&self, query: F) -> Result<T, ApiError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut DbConnection) -> eyre::Result<T> + Send + 'static,
+    {
+        run_query(&self.pool, query).await.map_err(ApiError::from)
+    }
+}
+
+/// Runs `query` on a pooled connection, off the async runtime.
+pub(crate) async fn run_query<T, F>(pool: &DbPool, query: F) -> eyre::Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut DbConnection) -> eyre::Result<T> + Send + 'static,
+{
+    let pool = pool.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let connection = &mut *pool.get().context("getting database connection")?;
+
+        query(connection)
+    })
+    .await?
+}
+
+/// Every route shares `pool`, as do the OPDS and GraphQL routes merged in. The OPDS feeds
+/// serve `catalog`.
+pub fn router(pool: DbPool, catalog: OpdsCatalog) -> Router {
+    let state = AppState { pool: pool.clone() };
+    let router = Router::new()
+        .route(
+            "/books",
+            get(book_routes::list_books).post(book_routes::create_book),
+        )
+        .route(
+            "/books/{id}",
+            get(book_routes::get_book)
+                .put(book_routes::update_book)
+                .delete(book_routes::delete_book),
+        )
+        .route(
+            "/books/{id}/authors",
+            get(book_routes::list_book_authors).post(book_routes::add_book_author),
+        )
+        .route(
+            "/books/{id}/authors/{author_id}",
+            delete(book_routes::remove_book_author),
+        )
+        .route(
+            "/authors",
+            get(author_routes::list_authors).post(author_routes::create_author),
+        )
+        .route(
+            "/authors/{id}",
+            get(author_routes::get_author)
+                .put(author_routes::update_author)
+                .delete(author_routes::delete_author),
+        )
+        .with_state(state)
+        .merge(crate::opds::router(pool.clone(), catalog));
+
+    #[cfg(feature = "graphql")]
+    let router = router.merge(crate::graphql::router(pool));
+
+    router
+}
+
+#[derive(FromRequest)]
+#[from_request(via(Json), rejection(ApiError))]
+pub struct JsonBody<T>(pub T);
+
+#[derive(Deserialize)]
+pub struct BookAuthorPayload {
+    pub author_id: i32,
+}
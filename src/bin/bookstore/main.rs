@@ -0,0 +1,487 @@
+mod output;
+
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Write},
+    path::PathBuf,
+};
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use clap::{Parser, Subcommand, ValueEnum};
+use diesel_bookstore_assessment::{
+    citation::{cite_book, CitationFormat},
+    connect::{connect, DbConnection},
+    export::{export_csv, export_json, export_json_lines, ExportTargets, DEFAULT_BATCH_SIZE},
+    import::{import_csv, ImportSources},
+    marc::{export_marc, import_marc, read_marc, MarcSyntax},
+    models::{Author, Book},
+    onix::{export_onix, OnixOptions},
+    pricing::update_prices_csv,
+    queries::{
+        author_queries::{
+            create_author, delete_author, get_all_authors, get_author_by_id, update_author,
+        },
+        book_author_queries::{
+            associate_book_with_author, dissociate_book_from_author, get_author_with_books,
+            get_book_with_authors,
+        },
+        book_queries::{create_book, delete_book, get_all_books, get_book_by_id, update_book},
+        price_queries::current_price,
+    },
+};
+use eyre::{bail, eyre, Context, Result};
+use output::{print, print_done, print_one, Format};
+use serde_json::json;
+
+#[derive(Parser)]
+#[command(name = "bookstore", about = "Inspect and edit the bookstore catalog")]
+struct Cli {
+    #[arg(long, value_enum, global = true, default_value = "table")]
+    format: Format,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(subcommand)]
+    Book(BookCommand),
+    #[command(subcommand)]
+    Author(AuthorCommand),
+    Link {
+        book_id: i32,
+        author_id: i32,
+    },
+    Unlink {
+        book_id: i32,
+        author_id: i32,
+    },
+    /// Load books, authors and links from CSV files
+    Import {
+        /// CSV with `book_id,name` columns
+        #[arg(long)]
+        books: Option<PathBuf>,
+        /// CSV with `author_id,name` columns
+        #[arg(long)]
+        authors: Option<PathBuf>,
+        /// CSV with `author_id` or `author_name` and `book_id` or `book_name` columns
+        #[arg(long)]
+        book_authors: Option<PathBuf>,
+        /// Check every row and roll back instead of saving
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Load books and authors from MARC21 or MARCXML records
+    ImportMarc {
+        path: PathBuf,
+        /// Read MARCXML instead of binary MARC21
+        #[arg(long)]
+        xml: bool,
+    },
+    /// Update prices from a CSV with `book_id,currency,price` columns
+    ImportPrices {
+        path: PathBuf,
+        /// UTC time the new prices start, e.g. 2024-10-17T12:00:00Z; defaults to now
+        #[arg(long, value_parser = parse_timestamp)]
+        effective_from: Option<NaiveDateTime>,
+        /// Show the changes without saving them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Write the whole catalog as CSV, JSON Lines, JSON, MARC or ONIX
+    Export {
+        /// Number of books fetched per query
+        #[arg(
+            long,
+            default_value_t = DEFAULT_BATCH_SIZE,
+            value_parser = clap::value_parser!(i64).range(1..)
+        )]
+        batch_size: i64,
+        #[command(subcommand)]
+        command: ExportCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum ExportCommand {
+    /// Write books.csv, authors.csv and book_authors.csv into a directory
+    Csv { directory: PathBuf },
+    /// Write one book with its authors per line
+    Jsonl {
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Write a single JSON array of books with their authors
+    Json {
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Write one MARC21 record per book
+    Marc {
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// Write MARCXML instead of binary MARC21
+        #[arg(long)]
+        xml: bool,
+    },
+    /// Write an ONIX 3.0 message with one product per book
+    Onix {
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// Name sent in the message header
+        #[arg(long)]
+        sender: String,
+        #[arg(long)]
+        publisher: Option<String>,
+        /// Only include books changed at or after this UTC time, e.g. 2024-10-17T12:00:00Z
+        #[arg(long, value_parser = parse_timestamp)]
+        since: Option<NaiveDateTime>,
+        /// Reject books without an ISBN
+        #[arg(long)]
+        require_isbn: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum BookCommand {
+    /// Create a book
+    Add { name: String },
+    /// List every book
+    List,
+    /// Show a book with its authors
+    Show { id: i32 },
+    /// Change a book's name
+    Rename { id: i32, name: String },
+    /// Delete a book
+    Rm { id: i32 },
+    /// Show a book's price in a currency
+    Price {
+        id: i32,
+        currency: String,
+        /// UTC time to look the price up at; defaults to now
+        #[arg(long, value_parser = parse_timestamp)]
+        at: Option<NaiveDateTime>,
+    },
+    /// Print a citation for a book
+    Cite {
+        id: i32,
+        #[arg(long, value_enum, default_value = "bibtex")]
+        style: CitationStyle,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum CitationStyle {
+    Bibtex,
+    Ris,
+    CslJson,
+}
+
+impl From<CitationStyle> for CitationFormat {
+    fn from(style: CitationStyle) -> Self {
+        match style {
+            CitationStyle::Bibtex => Self::BibTex,
+            CitationStyle::Ris => Self::Ris,
+            CitationStyle::CslJson => Self::CslJson,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum AuthorCommand {
+    /// Create an author
+    Add { name: String },
+    /// List every author
+    List,
+    /// Show an author with its books
+    Show { id: i32 },
+    /// Change an author's name
+    Rename { id: i32, name: String },
+    /// Delete an author
+    Rm { id: i32 },
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let connection = &mut connect()?;
+
+    match cli.command {
+        Command::Book(command) => run_book_command(command, cli.format, connection),
+        Command::Author(command) => run_author_command(command, cli.format, connection),
+        Command::Link { book_id, author_id } => {
+            find_book(book_id, connection)?;
+            find_author(author_id, connection)?;
+            associate_book_with_author(book_id, author_id, connection)?;
+
+            print_done(
+                cli.format,
+                &format!("linked book {book_id} with author {author_id}"),
+                &json!({ "book_id": book_id, "author_id": author_id, "linked": true }),
+            )
+        }
+        Command::Unlink { book_id, author_id } => {
+            if !dissociate_book_from_author(book_id, author_id, connection)? {
+                bail!("author {author_id} is not linked to book {book_id}");
+            }
+
+            print_done(
+                cli.format,
+                &format!("unlinked book {book_id} from author {author_id}"),
+                &json!({ "book_id": book_id, "author_id": author_id, "linked": false }),
+            )
+        }
+        Command::Import {
+            books,
+            authors,
+            book_authors,
+            dry_run,
+        } => {
+            let sources = ImportSources {
+                books: open_csv(books)?,
+                authors: open_csv(authors)?,
+                book_authors: open_csv(book_authors)?,
+            };
+
+            print_one(cli.format, &import_csv(sources, dry_run, connection)?)
+        }
+        Command::ImportMarc { path, xml } => {
+            let file = File::open(&path).with_context(|| format!("opening {}", path.display()))?;
+            let records = read_marc(marc_syntax(xml), BufReader::new(file))?;
+            let report = import_marc(&records, connection)?;
+
+            print_one(cli.format, &report)?;
+
+            if matches!(cli.format, Format::Table) {
+                for skipped in &report.skipped_records {
+                    println!("record {}: skipped, {}", skipped.record, skipped.reason);
+                }
+
+                for skipped in &report.skipped_fields {
+                    println!(
+                        "record {} field {}: {}",
+                        skipped.record, skipped.tag, skipped.reason
+                    );
+                }
+            }
+
+            Ok(())
+        }
+        Command::ImportPrices {
+            path,
+            effective_from,
+            dry_run,
+        } => {
+            let file = File::open(&path).with_context(|| format!("opening {}", path.display()))?;
+            let effective_from = effective_from.unwrap_or_else(|| Utc::now().naive_utc());
+            let report = update_prices_csv(file, effective_from, dry_run, connection)?;
+
+            if matches!(cli.format, Format::Table) {
+                print(cli.format, &report.changes)?;
+            }
+
+            print_one(cli.format, &report)
+        }
+        Command::Export {
+            batch_size,
+            command,
+        } => run_export_command(command, batch_size, cli.format, connection),
+    }
+}
+
+fn run_book_command(
+    command: BookCommand,
+    format: Format,
+    connection: &mut DbConnection,
+) -> Result<()> {
+    match command {
+        BookCommand::Add { name } => {
+            let id = create_book(&name, connection)?;
+
+            print_one(format, &find_book(id, connection)?)
+        }
+        BookCommand::List => print(format, &get_all_books(connection)?),
+        BookCommand::Show { id } => {
+            let book_with_authors = get_book_with_authors(id, connection)?
+                .ok_or_else(|| eyre!("book {id} not found"))?;
+
+            print_one(format, &book_with_authors)
+        }
+        BookCommand::Rename { id, name } => {
+            find_book(id, connection)?;
+            update_book(id, &name, connection)?;
+
+            print_one(format, &find_book(id, connection)?)
+        }
+        BookCommand::Rm { id } => {
+            find_book(id, connection)?;
+            delete_book(id, connection)?;
+
+            print_done(
+                format,
+                &format!("removed book {id}"),
+                &json!({ "id": id, "removed": true }),
+            )
+        }
+        BookCommand::Price { id, currency, at } => {
+            find_book(id, connection)?;
+
+            let at = at.unwrap_or_else(|| Utc::now().naive_utc());
+            let price = current_price(id, &currency, at, connection)?
+                .ok_or_else(|| eyre!("book {id} has no {currency} price"))?;
+
+            print_one(format, &price)
+        }
+        BookCommand::Cite { id, style } => {
+            let citation = cite_book(id, style.into(), connection)?
+                .ok_or_else(|| eyre!("book {id} not found"))?;
+
+            print!("{citation}");
+
+            Ok(())
+        }
+    }
+}
+
+fn run_author_command(
+    command: AuthorCommand,
+    format: Format,
+    connection: &mut DbConnection,
+) -> Result<()> {
+    match command {
+        AuthorCommand::Add { name } => {
+            let id = create_author(&name, connection)?;
+
+            print_one(format, &find_author(id, connection)?)
+        }
+        AuthorCommand::List => print(format, &get_all_authors(connection)?),
+        AuthorCommand::Show { id } => {
+            let author_with_books = get_author_with_books(id, connection)?
+                .ok_or_else(|| eyre!("author {id} not found"))?;
+
+            print_one(format, &author_with_books)
+        }
+        AuthorCommand::Rename { id, name } => {
+            find_author(id, connection)?;
+            update_author(id, &name, connection)?;
+
+            print_one(format, &find_author(id, connection)?)
+        }
+        AuthorCommand::Rm { id } => {
+            find_author(id, connection)?;
+            delete_author(id, connection)?;
+
+            print_done(
+                format,
+                &format!("removed author {id}"),
+                &json!({ "id": id, "removed": true }),
+            )
+        }
+    }
+}
+
+fn run_export_command(
+    command: ExportCommand,
+    batch_size: i64,
+    format: Format,
+    connection: &mut DbConnection,
+) -> Result<()> {
+    match command {
+        ExportCommand::Csv { directory } => {
+            let targets = ExportTargets {
+                books: create_output(Some(directory.join("books.csv")))?,
+                authors: create_output(Some(directory.join("authors.csv")))?,
+                book_authors: create_output(Some(directory.join("book_authors.csv")))?,
+            };
+
+            print_one(format, &export_csv(targets, batch_size, connection)?)
+        }
+        ExportCommand::Jsonl { output } => {
+            export_json_lines(create_output(output)?, batch_size, connection)?;
+
+            Ok(())
+        }
+        ExportCommand::Json { output } => {
+            export_json(create_output(output)?, batch_size, connection)?;
+
+            Ok(())
+        }
+        ExportCommand::Onix {
+            output,
+            sender,
+            publisher,
+            since,
+            require_isbn,
+        } => {
+            let options = OnixOptions {
+                publisher_name: publisher,
+                since,
+                require_isbn,
+                ..OnixOptions::new(&sender)
+            };
+            let report = export_onix(create_output(output)?, &options, batch_size, connection)?;
+
+            for rejected in &report.rejected {
+                eprintln!(
+                    "book {} left out: {}",
+                    rejected.book_id,
+                    rejected.problems.join("; ")
+                );
+            }
+
+            Ok(())
+        }
+        ExportCommand::Marc { output, xml } => {
+            export_marc(
+                marc_syntax(xml),
+                create_output(output)?,
+                batch_size,
+                connection,
+            )?;
+
+            Ok(())
+        }
+    }
+}
+
+fn parse_timestamp(value: &str) -> Result<NaiveDateTime, String> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.naive_utc());
+    }
+
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y-%m-%d").map(NaiveDate::into))
+        .map_err(|_| format!("`{value}` is not an RFC 3339 timestamp or a YYYY-MM-DD date"))
+}
+
+fn marc_syntax(xml: bool) -> MarcSyntax {
+    if xml {
+        MarcSyntax::Xml
+    } else {
+        MarcSyntax::Binary
+    }
+}
+
+fn find_book(id: i32, connection: &mut DbConnection) -> Result<Book> {
+    get_book_by_id(id, connection)?.ok_or_else(|| eyre!("book {id} not found"))
+}
+
+fn find_author(id: i32, connection: &mut DbConnection) -> Result<Author> {
+    get_author_by_id(id, connection)?.ok_or_else(|| eyre!("author {id} not found"))
+}
+
+fn open_csv(path: Option<PathBuf>) -> Result<Option<File>> {
+    path.map(|path| File::open(&path).with_context(|| format!("opening {}", path.display())))
+        .transpose()
+}
+
+fn create_output(path: Option<PathBuf>) -> Result<BufWriter<Box<dyn Write>>> {
+    let output: Box<dyn Write> = match path {
+        Some(path) => {
+            Box::new(File::create(&path).with_context(|| format!("creating {}", path.display()))?)
+        }
+        None => Box::new(io::stdout().lock()),
+    };
+
+    Ok(BufWriter::new(output))
+}
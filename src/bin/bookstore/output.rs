@@ -0,0 +1,239 @@
+use clap::ValueEnum;
+use diesel_bookstore_assessment::{
+    export::ExportReport,
+    import::ImportReport,
+    marc::MarcImportReport,
+    models::{Author, AuthorWithBooks, Book, BookPrice, BookWithAuthors},
+    pricing::{format_amount, PriceChange, PriceUpdateReport},
+};
+use eyre::Result;
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum Format {
+    Table,
+    Json,
+}
+
+pub trait Tabular {
+    fn headers() -> &'static [&'static str];
+    fn row(&self) -> Vec<String>;
+}
+
+impl Tabular for Book {
+    fn headers() -> &'static [&'static str] {
+        &["id", "name"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![self.id.to_string(), self.name.clone()]
+    }
+}
+
+impl Tabular for Author {
+    fn headers() -> &'static [&'static str] {
+        &["id", "name"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![self.id.to_string(), self.name.clone()]
+    }
+}
+
+impl Tabular for BookWithAuthors {
+    fn headers() -> &'static [&'static str] {
+        &["id", "name", "authors"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        let mut row = self.book().row();
+
+        row.push(join_names(self.authors().iter().map(|author| &author.name)));
+
+        row
+    }
+}
+
+impl Tabular for AuthorWithBooks {
+    fn headers() -> &'static [&'static str] {
+        &["id", "name", "books"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        let mut row = self.author().row();
+
+        row.push(join_names(self.books().iter().map(|book| &book.name)));
+
+        row
+    }
+}
+
+impl Tabular for ExportReport {
+    fn headers() -> &'static [&'static str] {
+        &["books", "authors", "links"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.books.to_string(),
+            self.authors.to_string(),
+            self.links.to_string(),
+        ]
+    }
+}
+
+impl Tabular for ImportReport {
+    fn headers() -> &'static [&'static str] {
+        &["books", "authors", "links", "dry run"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.books_created.to_string(),
+            self.authors_created.to_string(),
+            self.links_created.to_string(),
+            self.dry_run.to_string(),
+        ]
+    }
+}
+
+impl Tabular for MarcImportReport {
+    fn headers() -> &'static [&'static str] {
+        &[
+            "books",
+            "authors",
+            "links",
+            "skipped records",
+            "skipped fields",
+        ]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.books_created.to_string(),
+            self.authors_created.to_string(),
+            self.links_created.to_string(),
+            self.skipped_records.len().to_string(),
+            self.skipped_fields.len().to_string(),
+        ]
+    }
+}
+
+impl Tabular for PriceUpdateReport {
+    fn headers() -> &'static [&'static str] {
+        &["changed", "unchanged", "dry run"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.changes.len().to_string(),
+            self.unchanged.to_string(),
+            self.dry_run.to_string(),
+        ]
+    }
+}
+
+impl Tabular for BookPrice {
+    fn headers() -> &'static [&'static str] {
+        &["book id", "price", "from", "until"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.book_id.to_string(),
+            format_amount(self.amount_minor, &self.currency),
+            self.effective_from.to_string(),
+            self.effective_to
+                .map(|effective_to| effective_to.to_string())
+                .unwrap_or_default(),
+        ]
+    }
+}
+
+impl Tabular for PriceChange {
+    fn headers() -> &'static [&'static str] {
+        &["book id", "name", "old price", "new price"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.book_id.to_string(),
+            self.book_name.clone(),
+            self.old_amount
+                .map(|old_amount| format_amount(old_amount, &self.currency))
+                .unwrap_or_else(|| "-".to_owned()),
+            format_amount(self.new_amount, &self.currency),
+        ]
+    }
+}
+
+pub fn print<T: Tabular + Serialize>(format: Format, items: &[T]) -> Result<()> {
+    match format {
+        Format::Table => print!("{}", render_table(items)),
+        Format::Json => println!("{}", serde_json::to_string_pretty(items)?),
+    }
+
+    Ok(())
+}
+
+pub fn print_one<T: Tabular + Serialize>(format: Format, item: &T) -> Result<()> {
+    match format {
+        Format::Table => print!("{}", render_table(std::slice::from_ref(item))),
+        Format::Json => println!("{}", serde_json::to_string_pretty(item)?),
+    }
+
+    Ok(())
+}
+
+/// Reports a change that has no record left to show: `message` for people, `outcome` as
+/// JSON for scripts.
+pub fn print_done(format: Format, message: &str, outcome: &Value) -> Result<()> {
+    match format {
+        Format::Table => println!("{message}"),
+        Format::Json => println!("{}", serde_json::to_string_pretty(outcome)?),
+    }
+
+    Ok(())
+}
+
+fn render_table<T: Tabular>(items: &[T]) -> String {
+    let headers = T::headers();
+    let rows = items.iter().map(Tabular::row).collect::<Vec<Vec<String>>>();
+    let widths = headers
+        .iter()
+        .enumerate()
+        .map(|(column, header)| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .chain([header.len()])
+                .max()
+                .unwrap_or_default()
+        })
+        .collect::<Vec<usize>>();
+    let render_row = |cells: Vec<String>| {
+        let line = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<String>>()
+            .join("  ");
+
+        format!("{}\n", line.trim_end())
+    };
+    let mut table = render_row(headers.iter().map(|header| header.to_string()).collect());
+
+    table.push_str(&render_row(
+        widths.iter().map(|width| "-".repeat(*width)).collect(),
+    ));
+
+    for row in rows {
+        table.push_str(&render_row(row));
+    }
+
+    table
+}
+
+fn join_names<'a>(names: impl Iterator<Item = &'a String>) -> String {
+    names.map(String::as_str).collect::<Vec<&str>>().join(", ")
+}
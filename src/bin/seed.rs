@@ -1,4 +1,4 @@
-use diesel::{Insertable, RunQueryDsl, SelectableHelper};
+use diesel::{Insertable, RunQueryDsl};
 use diesel_bookstore_assessment::{
     connect::connect,
     models::{Author, Book, NewAuthor, NewBook, NewBookAuthor},
@@ -61,8 +61,8 @@ fn main() {
         .map(|new_book| {
             new_book
                 .insert_into(schema::books::table)
-                .returning(Book::as_select())
-                .get_result(database_connection)
+                .returning((schema::books::id, schema::books::name))
+                .get_result::<Book>(database_connection)
                 .expect("inserting seed book}")
         })
         .collect::<Vec<Book>>();
@@ -72,8 +72,8 @@ fn main() {
         .map(|new_author| {
             new_author
                 .insert_into(schema::authors::table)
-                .returning(Author::as_select())
-                .get_result(database_connection)
+                .returning((schema::authors::id, schema::authors::name))
+                .get_result::<Author>(database_connection)
                 .expect("inserting seed author")
         })
         .collect::<Vec<Author>>();
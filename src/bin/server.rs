@@ -0,0 +1,25 @@
+use diesel_bookstore_assessment::{api::router, connect::build_pool, opds::OpdsCatalog};
+use dotenvy::dotenv;
+use eyre::{Context, Result};
+use std::env;
+use tokio::net::TcpListener;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv().ok();
+
+    let database_url =
+        env::var("DATABASE_URL").context("extracting DATABASE_URL environment variable")?;
+    let pool = build_pool(&database_url)?;
+    let bind_address = env::var("BIND_ADDRESS").unwrap_or_else(|_| "127.0.0.1:3000".to_owned());
+    // Links in the OPDS feeds have to be absolute, so they need the address clients use.
+    let public_url = env::var("PUBLIC_URL").unwrap_or_else(|_| format!("http://{bind_address}"));
+    let catalog = OpdsCatalog::new(&public_url).context("checking PUBLIC_URL")?;
+    let listener = TcpListener::bind(&bind_address)
+        .await
+        .context("binding server address")?;
+
+    axum::serve(listener, router(pool, catalog))
+        .await
+        .context("serving the catalog api")
+}
@@ -0,0 +1,120 @@
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+use super::PersonName;
+use crate::models::BookWithAuthors;
+
+pub fn to_bibtex(book_with_authors: &BookWithAuthors) -> String {
+    let book = book_with_authors.book();
+    let names = book_with_authors
+        .authors()
+        .iter()
+        .map(|author| PersonName::parse(&author.name))
+        .collect::<Vec<PersonName>>();
+    let mut entry = format!("@book{{{},\n", citation_key(book.id, names.first()));
+
+    if !names.is_empty() {
+        let authors = names
+            .iter()
+            .map(bibtex_name)
+            .collect::<Vec<String>>()
+            .join(" and ");
+
+        entry.push_str(&format!("  author = {{{authors}}},\n"));
+    }
+
+    entry.push_str(&format!("  title = {{{{{}}}}},\n", escape(&book.name)));
+    entry.push_str("}\n");
+
+    entry
+}
+
+fn citation_key(book_id: i32, first_author: Option<&PersonName>) -> String {
+    let family = first_author
+        .map(|name| {
+            transliterate(&name.family)
+                .chars()
+                .filter(char::is_ascii_alphanumeric)
+                .collect::<String>()
+                .to_ascii_lowercase()
+        })
+        .filter(|family| !family.is_empty())
+        .unwrap_or_else(|| "book".to_owned());
+
+    format!("{family}{book_id}")
+}
+
+/// Keys have to be plain ASCII, so accents are dropped ("García" becomes "Garcia") and the
+/// letters that have no decomposition are spelled out.
+fn transliterate(value: &str) -> String {
+    let mut ascii = String::with_capacity(value.len());
+
+    for character in value
+        .nfd()
+        .filter(|character| !is_combining_mark(*character))
+    {
+        match character {
+            'ß' => ascii.push_str("ss"),
+            'æ' => ascii.push_str("ae"),
+            'Æ' => ascii.push_str("AE"),
+            'œ' => ascii.push_str("oe"),
+            'Œ' => ascii.push_str("OE"),
+            'þ' => ascii.push_str("th"),
+            'Þ' => ascii.push_str("TH"),
+            'ø' => ascii.push('o'),
+            'Ø' => ascii.push('O'),
+            'ł' => ascii.push('l'),
+            'Ł' => ascii.push('L'),
+            'đ' | 'ð' => ascii.push('d'),
+            'Đ' | 'Ð' => ascii.push('D'),
+            'ı' => ascii.push('i'),
+            _ => ascii.push(character),
+        }
+    }
+
+    ascii
+}
+
+fn bibtex_name(name: &PersonName) -> String {
+    let family = protect(&name.full_family());
+    let suffix = name.suffix.as_deref().map(protect);
+    let given = name.given.as_deref().map(protect);
+
+    [Some(family), suffix, given]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Braces keep BibTeX from reading an "and" inside a name as an author separator.
+fn protect(part: &str) -> String {
+    let escaped = escape(part);
+
+    if escaped
+        .split_whitespace()
+        .any(|word| word.eq_ignore_ascii_case("and"))
+    {
+        format!("{{{escaped}}}")
+    } else {
+        escaped
+    }
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for character in value.chars() {
+        match character {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '{' | '}' | '$' | '&' | '%' | '#' | '_' => {
+                escaped.push('\\');
+                escaped.push(character);
+            }
+            _ => escaped.push(character),
+        }
+    }
+
+    escaped
+}
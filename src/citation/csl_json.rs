@@ -0,0 +1,56 @@
+use serde::Serialize;
+
+use super::{citation_id, PersonName};
+use crate::models::BookWithAuthors;
+
+#[derive(Serialize)]
+struct CslItem<'a> {
+    id: String,
+    #[serde(rename = "type")]
+    item_type: &'static str,
+    title: &'a str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    author: Vec<CslName>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct CslName {
+    family: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    given: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    non_dropping_particle: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    suffix: Option<String>,
+}
+
+impl From<PersonName> for CslName {
+    fn from(name: PersonName) -> Self {
+        Self {
+            family: name.family,
+            given: name.given,
+            non_dropping_particle: name.particle,
+            suffix: name.suffix,
+        }
+    }
+}
+
+/// Renders a CSL-JSON array holding the one book.
+pub fn to_csl_json(book_with_authors: &BookWithAuthors) -> String {
+    let item = CslItem {
+        id: citation_id(book_with_authors),
+        item_type: "book",
+        title: &book_with_authors.book().name,
+        author: book_with_authors
+            .authors()
+            .iter()
+            .map(|author| PersonName::parse(&author.name).into())
+            .collect(),
+    };
+    let mut json = serde_json::to_string_pretty(&[item]).expect("citation serializes to json");
+
+    json.push('\n');
+
+    json
+}
@@ -0,0 +1,53 @@
+mod bibtex;
+#[cfg(feature = "serde")]
+mod csl_json;
+mod names;
+mod ris;
+
+pub use bibtex::to_bibtex;
+#[cfg(feature = "serde")]
+pub use csl_json::to_csl_json;
+pub(crate) use names::is_suffix;
+pub use names::PersonName;
+pub use ris::to_ris;
+
+use eyre::Result;
+
+use crate::{
+    connect::DbConnection, models::BookWithAuthors,
+    queries::book_author_queries::get_book_with_authors,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CitationFormat {
+    BibTex,
+    Ris,
+    #[cfg(feature = "serde")]
+    CslJson,
+}
+
+impl CitationFormat {
+    /// Authors are cited in the order they appear in `book_with_authors`.
+    pub fn render(self, book_with_authors: &BookWithAuthors) -> String {
+        match self {
+            Self::BibTex => to_bibtex(book_with_authors),
+            Self::Ris => to_ris(book_with_authors),
+            #[cfg(feature = "serde")]
+            Self::CslJson => to_csl_json(book_with_authors),
+        }
+    }
+}
+
+pub fn cite_book(
+    book_id: i32,
+    format: CitationFormat,
+    connection: &mut DbConnection,
+) -> Result<Option<String>> {
+    let book_with_authors = get_book_with_authors(book_id, connection)?;
+
+    Ok(book_with_authors.map(|book_with_authors| format.render(&book_with_authors)))
+}
+
+fn citation_id(book_with_authors: &BookWithAuthors) -> String {
+    format!("book-{}", book_with_authors.book().id)
+}
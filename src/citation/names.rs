@@ -0,0 +1,115 @@
+const PARTICLES: &[&str] = &[
+    "da", "dal", "de", "del", "della", "der", "di", "du", "la", "le", "ten", "ter", "van", "von",
+];
+const SUFFIXES: &[&str] = &["Jr.", "Jr", "Sr.", "Sr", "II", "III", "IV"];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PersonName {
+    pub given: Option<String>,
+    pub particle: Option<String>,
+    pub family: String,
+    pub suffix: Option<String>,
+}
+
+impl PersonName {
+    /// Splits "Given Family" and "Family, Given" forms. Single words and corporate names
+    /// such as "Marks and Spencer" are kept whole as the family name, lowercase particles
+    /// such as "van" stay with the family name, and a trailing "Jr." becomes the suffix.
+    pub fn parse(name: &str) -> Self {
+        let parts = name.split(',').map(str::trim).collect::<Vec<&str>>();
+
+        match parts.as_slice() {
+            [family, given] if !is_suffix(given) => Self::from_family_first(family, given, None),
+            [family, suffix, given] if is_suffix(suffix) => {
+                Self::from_family_first(family, given, Some(suffix))
+            }
+            _ => Self::from_given_first(name),
+        }
+    }
+
+    /// The family name with its particle, e.g. "van Beethoven".
+    pub fn full_family(&self) -> String {
+        match &self.particle {
+            Some(particle) => format!("{particle} {}", self.family),
+            None => self.family.clone(),
+        }
+    }
+
+    /// "Family, Given" with the suffix between when present, as BibTeX and RIS expect.
+    pub fn inverted(&self) -> String {
+        let mut inverted = self.full_family();
+
+        if let Some(suffix) = &self.suffix {
+            inverted = format!("{inverted}, {suffix}");
+        }
+
+        if let Some(given) = &self.given {
+            inverted = format!("{inverted}, {given}");
+        }
+
+        inverted
+    }
+
+    fn from_family_first(family: &str, given: &str, suffix: Option<&str>) -> Self {
+        let words = family.split_whitespace().collect::<Vec<&str>>();
+        let particle_count = words
+            .iter()
+            .take(words.len().saturating_sub(1))
+            .take_while(|word| is_particle(word))
+            .count();
+
+        Self {
+            given: non_empty(given),
+            particle: non_empty(&words[..particle_count].join(" ")),
+            family: words[particle_count..].join(" "),
+            suffix: suffix.map(str::to_owned),
+        }
+    }
+
+    fn from_given_first(name: &str) -> Self {
+        let mut words = name.split_whitespace().collect::<Vec<&str>>();
+
+        if words.iter().any(|word| matches!(*word, "and" | "&")) {
+            return Self {
+                family: words.join(" "),
+                ..Self::default()
+            };
+        }
+
+        let suffix = match words.as_slice() {
+            [_, .., last] if words.len() > 2 && is_suffix(last) => words.pop(),
+            _ => None,
+        };
+        let Some(family) = words.pop() else {
+            return Self::default();
+        };
+        let particle_count = words
+            .iter()
+            .skip(1)
+            .rev()
+            .take_while(|word| is_particle(word))
+            .count();
+        let particle = words.split_off(words.len() - particle_count);
+
+        Self {
+            given: non_empty(&words.join(" ")),
+            particle: non_empty(&particle.join(" ")),
+            family: family.to_owned(),
+            suffix: suffix.map(str::to_owned),
+        }
+    }
+}
+
+fn is_particle(word: &str) -> bool {
+    PARTICLES.contains(&word)
+}
+
+pub(crate) fn is_suffix(word: &str) -> bool {
+    SUFFIXES.contains(&word)
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+
+    (!value.is_empty()).then(|| value.to_owned())
+}
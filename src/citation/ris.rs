@@ -0,0 +1,38 @@
+use super::{citation_id, PersonName};
+use crate::models::BookWithAuthors;
+
+pub fn to_ris(book_with_authors: &BookWithAuthors) -> String {
+    let mut record = String::new();
+
+    push_tag(&mut record, "TY", "BOOK");
+    push_tag(&mut record, "ID", &citation_id(book_with_authors));
+
+    for author in book_with_authors.authors() {
+        push_tag(
+            &mut record,
+            "AU",
+            &PersonName::parse(&author.name).inverted(),
+        );
+    }
+
+    push_tag(&mut record, "TI", &book_with_authors.book().name);
+    record.push_str("ER  - \n");
+
+    record
+}
+
+/// RIS is line based, so any control characters in a value are replaced with spaces.
+fn push_tag(record: &mut String, tag: &str, value: &str) {
+    let value = value
+        .chars()
+        .map(|character| {
+            if character.is_control() {
+                ' '
+            } else {
+                character
+            }
+        })
+        .collect::<String>();
+
+    record.push_str(&format!("{tag}  - {}\n", value.trim()));
+}
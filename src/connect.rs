@@ -1,13 +1,187 @@
-use diesel::{Connection, PgConnection};
+use crate::instrumentation::instrument;
+use diesel::{
+    define_sql_function,
+    r2d2::{self, ManageConnection, Pool, R2D2Connection},
+    result::ConnectionError,
+    Connection, PgConnection, RunQueryDsl, SqliteConnection,
+};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 use dotenvy::dotenv;
-use eyre::{Context, Result};
-use std::env;
+use eyre::{eyre, Context, Result};
+use std::{
+    collections::HashSet,
+    env,
+    fmt::{self, Display},
+    sync::{Mutex, OnceLock},
+};
 
-pub fn connect() -> Result<PgConnection> {
+define_sql_function! {
+    /// Lower case that handles letters outside ASCII, for comparing names and titles
+    /// without regard to case. SQLite's lower() only handles ASCII, so every SQLite
+    /// connection registers this one; Postgres defines it in a migration.
+    fn unicode_lower(value: diesel::sql_types::Text) -> diesel::sql_types::Text;
+}
+
+pub const SQLITE_MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations_sqlite");
+
+/// The SQLite databases this process has already migrated.
+static MIGRATED_SQLITE_DATABASES: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+#[derive(diesel::MultiConnection)]
+pub enum DbConnection {
+    Pg(PgConnection),
+    Sqlite(SqliteConnection),
+}
+
+/// Returned (inside the `eyre::Report`) when a database URL names a scheme other than
+/// `postgres://`, `postgresql://`, `sqlite://` or `file:`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedDatabaseUrl {
+    pub scheme: String,
+}
+
+impl Display for UnsupportedDatabaseUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unsupported database URL scheme `{}`, expected postgres://, postgresql://, \
+             sqlite://, file:, :memory: or a SQLite file path",
+            self.scheme
+        )
+    }
+}
+
+impl std::error::Error for UnsupportedDatabaseUrl {}
+
+/// A pool of connections set up the same way `establish` sets them up.
+pub type DbPool = Pool<DbConnectionManager>;
+
+#[derive(Debug, Clone)]
+pub struct DbConnectionManager {
+    database_url: String,
+}
+
+impl DbConnectionManager {
+    pub fn new(database_url: &str) -> Self {
+        Self {
+            database_url: database_url.to_owned(),
+        }
+    }
+}
+
+impl ManageConnection for DbConnectionManager {
+    type Connection = DbConnection;
+    type Error = r2d2::Error;
+
+    fn connect(&self) -> Result<DbConnection, r2d2::Error> {
+        establish(&self.database_url)
+            .map_err(|error| ConnectionError::BadConnection(format!("{error:#}")).into())
+    }
+
+    fn is_valid(&self, connection: &mut DbConnection) -> Result<(), r2d2::Error> {
+        connection.ping().map_err(r2d2::Error::QueryError)
+    }
+
+    fn has_broken(&self, connection: &mut DbConnection) -> bool {
+        std::thread::panicking() || connection.is_broken()
+    }
+}
+
+/// Opens the pool and its first connection, so a bad URL or a failing migration shows up
+/// at startup rather than on the first request.
+pub fn build_pool(database_url: &str) -> Result<DbPool> {
+    Pool::builder()
+        .min_idle(Some(1))
+        .build(DbConnectionManager::new(database_url))
+        .context("creating database connection pool")
+}
+
+pub fn connect() -> Result<DbConnection> {
     dotenv().ok();
 
     let database_url =
         env::var("DATABASE_URL").context("extracting DATABASE_URL environment variable")?;
 
-    PgConnection::establish(&database_url).context("Connecting to Postgres database")
+    establish(&database_url)
+}
+
+/// Connects to Postgres for `postgres://` and `postgresql://` URLs and to SQLite for
+/// `sqlite://` URLs, `file:` URIs, `:memory:` and plain file paths. Any other scheme is an
+/// `UnsupportedDatabaseUrl` error.
+pub fn establish(database_url: &str) -> Result<DbConnection> {
+    let mut connection =
+        if database_url.starts_with("postgres://") || database_url.starts_with("postgresql://") {
+            DbConnection::Pg(
+                PgConnection::establish(database_url).context("Connecting to Postgres database")?,
+            )
+        } else {
+            DbConnection::Sqlite(establish_sqlite(sqlite_database(database_url)?)?)
+        };
+
+    instrument(&mut connection).context("instrumenting database connection")?;
+
+    Ok(connection)
+}
+
+/// What SQLite should open for `database_url`.
+fn sqlite_database(database_url: &str) -> Result<&str, UnsupportedDatabaseUrl> {
+    if let Some(path) = database_url.strip_prefix("sqlite://") {
+        return Ok(path);
+    }
+
+    if database_url == ":memory:" || database_url.starts_with("file:") {
+        return Ok(database_url);
+    }
+
+    match database_url.split_once("://") {
+        Some((scheme, _)) => Err(UnsupportedDatabaseUrl {
+            scheme: scheme.to_owned(),
+        }),
+        None => Ok(database_url),
+    }
+}
+
+fn establish_sqlite(database: &str) -> Result<SqliteConnection> {
+    let mut connection =
+        SqliteConnection::establish(database).context("Connecting to SQLite database")?;
+
+    diesel::sql_query("PRAGMA foreign_keys = ON")
+        .execute(&mut connection)
+        .context("enabling SQLite foreign keys")?;
+    diesel::sql_query("PRAGMA busy_timeout = 5000")
+        .execute(&mut connection)
+        .context("setting SQLite busy timeout")?;
+    unicode_lower_utils::register_impl(&mut connection, |value: String| value.to_lowercase())
+        .context("registering SQLite unicode_lower function")?;
+
+    migrate_sqlite(database, &mut connection)?;
+
+    Ok(connection)
+}
+
+/// Brings a SQLite database up to date the first time this process opens it. Later
+/// connections skip the check. In-memory databases start empty every time, so they are
+/// always migrated.
+fn migrate_sqlite(database: &str, connection: &mut SqliteConnection) -> Result<()> {
+    let in_memory = database == ":memory:" || database.contains("mode=memory");
+    // Held while migrating, so connections opened at the same time wait for the first one.
+    let mut migrated = MIGRATED_SQLITE_DATABASES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    if !in_memory && migrated.contains(database) {
+        return Ok(());
+    }
+
+    connection
+        .run_pending_migrations(SQLITE_MIGRATIONS)
+        .map_err(|error| eyre!(error))
+        .context("running SQLite migrations")?;
+
+    if !in_memory {
+        migrated.insert(database.to_owned());
+    }
+
+    Ok(())
 }
@@ -0,0 +1,153 @@
+use std::io::Write;
+
+use csv::Writer;
+use diesel::{Connection, RunQueryDsl};
+use eyre::{Context, Result};
+
+use crate::{
+    connect::DbConnection,
+    instrumentation::query_span,
+    queries::stream_queries::{stream_authors, stream_books_with_authors},
+};
+
+pub const DEFAULT_BATCH_SIZE: i64 = 500;
+
+pub struct ExportTargets<W> {
+    pub books: W,
+    pub authors: W,
+    pub book_authors: W,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExportReport {
+    pub books: usize,
+    pub authors: usize,
+    pub links: usize,
+}
+
+/// Runs `export` in one read-only transaction, so every batch and every pass sees the
+/// catalog as it was when the export started.
+fn in_snapshot<T>(
+    connection: &mut DbConnection,
+    export: impl FnOnce(&mut DbConnection) -> Result<T>,
+) -> Result<T> {
+    connection.transaction(|connection| {
+        // A SQLite transaction already reads from a single snapshot.
+        if let DbConnection::Pg(connection) = connection {
+            diesel::sql_query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+                .execute(connection)
+                .context("starting export snapshot")?;
+        }
+
+        export(connection)
+    })
+}
+
+/// Writes the catalog as the three `seeds.md` tables, which `import_csv` reads back.
+#[query_span(skip(targets, connection))]
+pub fn export_csv<W: Write>(
+    targets: ExportTargets<W>,
+    batch_size: i64,
+    connection: &mut DbConnection,
+) -> Result<ExportReport> {
+    in_snapshot(connection, |connection| {
+        write_csv(targets, batch_size, connection)
+    })
+}
+
+fn write_csv<W: Write>(
+    targets: ExportTargets<W>,
+    batch_size: i64,
+    connection: &mut DbConnection,
+) -> Result<ExportReport> {
+    let mut report = ExportReport::default();
+    let mut books = Writer::from_writer(targets.books);
+    let mut book_authors = Writer::from_writer(targets.book_authors);
+    let mut authors = Writer::from_writer(targets.authors);
+
+    books.write_record(["book_id", "name"])?;
+    book_authors.write_record(["author_id", "book_id"])?;
+
+    for book_with_authors in stream_books_with_authors(batch_size, connection) {
+        let book_with_authors = book_with_authors?;
+        let book = book_with_authors.book();
+
+        books.write_record([book.id.to_string().as_str(), &book.name])?;
+
+        for author in book_with_authors.authors() {
+            book_authors.write_record([author.id.to_string(), book.id.to_string()])?;
+            report.links += 1;
+        }
+
+        report.books += 1;
+    }
+
+    authors.write_record(["author_id", "name"])?;
+
+    for author in stream_authors(batch_size, connection) {
+        let author = author?;
+
+        authors.write_record([author.id.to_string().as_str(), &author.name])?;
+        report.authors += 1;
+    }
+
+    books.flush().context("flushing books csv")?;
+    authors.flush().context("flushing authors csv")?;
+    book_authors.flush().context("flushing book authors csv")?;
+
+    Ok(report)
+}
+
+/// Writes one book with its authors per line.
+#[cfg(feature = "serde")]
+#[query_span(skip(writer, connection))]
+pub fn export_json_lines<W: Write>(
+    mut writer: W,
+    batch_size: i64,
+    connection: &mut DbConnection,
+) -> Result<usize> {
+    in_snapshot(connection, |connection| {
+        let mut written = 0;
+
+        for book_with_authors in stream_books_with_authors(batch_size, connection) {
+            serde_json::to_writer(&mut writer, &book_with_authors?)?;
+            writer.write_all(b"\n")?;
+            written += 1;
+        }
+
+        writer.flush().context("flushing json lines")?;
+
+        Ok(written)
+    })
+}
+
+/// Writes a single JSON array of books with their authors nested inside.
+#[cfg(feature = "serde")]
+#[query_span(skip(writer, connection))]
+pub fn export_json<W: Write>(
+    mut writer: W,
+    batch_size: i64,
+    connection: &mut DbConnection,
+) -> Result<usize> {
+    in_snapshot(connection, |connection| {
+        let mut written = 0;
+
+        writer.write_all(b"[")?;
+
+        for book_with_authors in stream_books_with_authors(batch_size, connection) {
+            if written > 0 {
+                writer.write_all(b",")?;
+            }
+
+            writer.write_all(b"\n  ")?;
+            serde_json::to_writer(&mut writer, &book_with_authors?)?;
+            written += 1;
+        }
+
+        writer.write_all(if written > 0 { b"\n]\n" } else { b"]\n" })?;
+        writer.flush().context("flushing json")?;
+
+        Ok(written)
+    })
+}
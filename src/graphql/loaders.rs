@@ -0,0 +1,136 @@
+use super::Database;
+use crate::{
+    models::{Author, Book},
+    queries::{
+        author_queries::get_authors_by_ids,
+        book_author_queries::{get_authors_for_books, get_books_for_authors},
+        book_queries::get_books_by_ids,
+    },
+};
+use async_graphql::dataloader::Loader;
+use std::collections::HashMap;
+
+pub(super) struct BookLoader {
+    database: Database,
+}
+
+impl BookLoader {
+    pub(super) fn new(database: Database) -> Self {
+        Self { database }
+    }
+}
+
+impl Loader<i32> for BookLoader {
+    type Value = Book;
+    type Error = async_graphql::Error;
+
+    async fn load(&self, keys: &[i32]) -> Result<HashMap<i32, Book>, Self::Error> {
+        let ids = keys.to_vec();
+        let books = self
+            .database
+            .run(move |connection| get_books_by_ids(&ids, connection))
+            .await?;
+
+        Ok(books.into_iter().map(|book| (book.id, book)).collect())
+    }
+}
+
+pub(super) struct AuthorLoader {
+    database: Database,
+}
+
+impl AuthorLoader {
+    pub(super) fn new(database: Database) -> Self {
+        Self { database }
+    }
+}
+
+impl Loader<i32> for AuthorLoader {
+    type Value = Author;
+    type Error = async_graphql::Error;
+
+    async fn load(&self, keys: &[i32]) -> Result<HashMap<i32, Author>, Self::Error> {
+        let ids = keys.to_vec();
+        let authors = self
+            .database
+            .run(move |connection| get_authors_by_ids(&ids, connection))
+            .await?;
+
+        Ok(authors
+            .into_iter()
+            .map(|author| (author.id, author))
+            .collect())
+    }
+}
+
+pub(super) struct AuthorsForBookLoader {
+    database: Database,
+}
+
+impl AuthorsForBookLoader {
+    pub(super) fn new(database: Database) -> Self {
+        Self { database }
+    }
+}
+
+impl Loader<i32> for AuthorsForBookLoader {
+    type Value = Vec<Author>;
+    type Error = async_graphql::Error;
+
+    async fn load(&self, keys: &[i32]) -> Result<HashMap<i32, Vec<Author>>, Self::Error> {
+        let ids = keys.to_vec();
+        let books_with_authors = self
+            .database
+            .run(move |connection| {
+                let books = get_books_by_ids(&ids, connection)?;
+
+                get_authors_for_books(books, connection)
+            })
+            .await?;
+
+        Ok(books_with_authors
+            .into_iter()
+            .map(|book_with_authors| {
+                let (book, authors) = book_with_authors.into_parts();
+
+                (book.id, authors)
+            })
+            .collect())
+    }
+}
+
+pub(super) struct BooksForAuthorLoader {
+    database: Database,
+}
+
+impl BooksForAuthorLoader {
+    pub(super) fn new(database: Database) -> Self {
+        Self { database }
+    }
+}
+
+impl Loader<i32> for BooksForAuthorLoader {
+    type Value = Vec<Book>;
+    type Error = async_graphql::Error;
+
+    async fn load(&self, keys: &[i32]) -> Result<HashMap<i32, Vec<Book>>, Self::Error> {
+        let ids = keys.to_vec();
+        let authors_with_books = self
+            .database
+            .run(move |connection| {
+                let authors = get_authors_by_ids(&ids, connection)?;
+
+                get_books_for_authors(authors, connection)
+            })
+            .await?;
+
+        Ok(authors_with_books
+            .into_iter()
+            .map(|author_with_books| {
+                let (author, books) = author_with_books.into_parts();
+
+                (author.id, books)
+            })
+            .collect())
+    }
+}
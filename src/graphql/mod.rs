@@ -0,0 +1,104 @@
+mod loaders;
+mod mutations;
+mod objects;
+mod queries;
+
+use crate::{
+    api::{run_query, ApiError},
+    connect::{DbConnection, DbPool},
+    instrumentation::record_error,
+};
+use async_graphql::{
+    dataloader::DataLoader, EmptySubscription, ErrorExtensions, Request, Response, Schema,
+};
+use axum::{extract::State, routing::post, Json, Router};
+use loaders::{AuthorLoader, AuthorsForBookLoader, BookLoader, BooksForAuthorLoader};
+
+pub use mutations::MutationRoot;
+pub use queries::QueryRoot;
+
+const MAX_QUERY_DEPTH: usize = 10;
+
+pub type BookstoreSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+
+#[derive(Clone)]
+struct Database {
+    pool: DbPool,
+}
+
+impl Database {
+    async fn run<T, F>(&self, query: F) -> async_graphql::Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut DbConnection) -> eyre::Result<T> + Send + 'static,
+    {
+        run_query(&self.pool, query).await.map_err(to_graphql_error)
+    }
+}
+
+/// The loaders live on the schema, so every way of executing it batches nested lookups.
+/// They keep no cache, so each request still sees current data.
+pub fn build_schema(pool: DbPool) -> BookstoreSchema {
+    let database = Database { pool };
+
+    Schema::build(QueryRoot, MutationRoot, EmptySubscription)
+        .data(DataLoader::new(
+            BookLoader::new(database.clone()),
+            tokio::spawn,
+        ))
+        .data(DataLoader::new(
+            AuthorLoader::new(database.clone()),
+            tokio::spawn,
+        ))
+        .data(DataLoader::new(
+            AuthorsForBookLoader::new(database.clone()),
+            tokio::spawn,
+        ))
+        .data(DataLoader::new(
+            BooksForAuthorLoader::new(database.clone()),
+            tokio::spawn,
+        ))
+        .data(database)
+        .limit_depth(MAX_QUERY_DEPTH)
+        .finish()
+}
+
+pub fn router(pool: DbPool) -> Router {
+    Router::new()
+        .route("/graphql", post(graphql_handler))
+        .with_state(build_schema(pool))
+}
+
+async fn graphql_handler(
+    State(schema): State<BookstoreSchema>,
+    Json(request): Json<Request>,
+) -> Json<Response> {
+    Json(schema.execute(request).await)
+}
+
+/// Turns a failed query into an error clients can act on: a message and a `code`
+/// extension, plus the `field` for validation errors. Internal errors are logged here and
+/// reach the client only as "internal server error".
+fn to_graphql_error(error: eyre::Report) -> async_graphql::Error {
+    let (message, code) = match ApiError::from(error) {
+        ApiError::Validation(validation_error) => {
+            return async_graphql::Error::new(validation_error.to_string()).extend_with(
+                |_, extensions| {
+                    extensions.set("code", "VALIDATION_FAILED");
+                    extensions.set("field", validation_error.field);
+                },
+            );
+        }
+        ApiError::BadRequest(message) => (message, "BAD_REQUEST"),
+        ApiError::NotFound(message) => (message, "NOT_FOUND"),
+        ApiError::Conflict(message) => (message, "CONFLICT"),
+        ApiError::Unprocessable(message) => (message, "UNPROCESSABLE"),
+        ApiError::Internal(error) => {
+            record_error("internal server error", &error);
+
+            ("internal server error".to_owned(), "INTERNAL_SERVER_ERROR")
+        }
+    };
+
+    async_graphql::Error::new(message).extend_with(|_, extensions| extensions.set("code", code))
+}
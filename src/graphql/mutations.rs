@@ -0,0 +1,143 @@
+use super::Database;
+use crate::{
+    models::{Author, Book, BookAuthor},
+    queries::{author_queries, book_author_queries, book_queries},
+};
+use async_graphql::{Context, Object, Result};
+use diesel::Connection;
+use eyre::eyre;
+
+pub struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    async fn create_book(&self, context: &Context<'_>, name: String) -> Result<Book> {
+        context
+            .data::<Database>()?
+            .run(move |connection| {
+                let id = book_queries::create_book(&name, connection)?;
+
+                book_queries::get_book_by_id(id, connection)?
+                    .ok_or_else(|| eyre!("created book {id} disappeared"))
+            })
+            .await
+    }
+
+    async fn update_book(
+        &self,
+        context: &Context<'_>,
+        id: i32,
+        name: String,
+    ) -> Result<Option<Book>> {
+        context
+            .data::<Database>()?
+            .run(move |connection| {
+                connection.transaction(|connection| {
+                    if book_queries::get_book_by_id(id, connection)?.is_none() {
+                        return Ok(None);
+                    }
+
+                    book_queries::update_book(id, &name, connection)?;
+                    book_queries::get_book_by_id(id, connection)
+                })
+            })
+            .await
+    }
+
+    async fn delete_book(&self, context: &Context<'_>, id: i32) -> Result<bool> {
+        context
+            .data::<Database>()?
+            .run(move |connection| {
+                connection.transaction(|connection| {
+                    if book_queries::get_book_by_id(id, connection)?.is_none() {
+                        return Ok(false);
+                    }
+
+                    book_queries::delete_book(id, connection)?;
+
+                    Ok(true)
+                })
+            })
+            .await
+    }
+
+    async fn create_author(&self, context: &Context<'_>, name: String) -> Result<Author> {
+        context
+            .data::<Database>()?
+            .run(move |connection| {
+                let id = author_queries::create_author(&name, connection)?;
+
+                author_queries::get_author_by_id(id, connection)?
+                    .ok_or_else(|| eyre!("created author {id} disappeared"))
+            })
+            .await
+    }
+
+    async fn update_author(
+        &self,
+        context: &Context<'_>,
+        id: i32,
+        name: String,
+    ) -> Result<Option<Author>> {
+        context
+            .data::<Database>()?
+            .run(move |connection| {
+                connection.transaction(|connection| {
+                    if author_queries::get_author_by_id(id, connection)?.is_none() {
+                        return Ok(None);
+                    }
+
+                    author_queries::update_author(id, &name, connection)?;
+                    author_queries::get_author_by_id(id, connection)
+                })
+            })
+            .await
+    }
+
+    async fn delete_author(&self, context: &Context<'_>, id: i32) -> Result<bool> {
+        context
+            .data::<Database>()?
+            .run(move |connection| {
+                connection.transaction(|connection| {
+                    if author_queries::get_author_by_id(id, connection)?.is_none() {
+                        return Ok(false);
+                    }
+
+                    author_queries::delete_author(id, connection)?;
+
+                    Ok(true)
+                })
+            })
+            .await
+    }
+
+    async fn associate_book_with_author(
+        &self,
+        context: &Context<'_>,
+        book_id: i32,
+        author_id: i32,
+    ) -> Result<BookAuthor> {
+        context
+            .data::<Database>()?
+            .run(move |connection| {
+                book_author_queries::associate_book_with_author(book_id, author_id, connection)?;
+
+                Ok(BookAuthor { book_id, author_id })
+            })
+            .await
+    }
+
+    async fn dissociate_book_from_author(
+        &self,
+        context: &Context<'_>,
+        book_id: i32,
+        author_id: i32,
+    ) -> Result<bool> {
+        context
+            .data::<Database>()?
+            .run(move |connection| {
+                book_author_queries::dissociate_book_from_author(book_id, author_id, connection)
+            })
+            .await
+    }
+}
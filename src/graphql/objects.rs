@@ -0,0 +1,68 @@
+use super::loaders::{AuthorLoader, AuthorsForBookLoader, BookLoader, BooksForAuthorLoader};
+use crate::models::{Author, Book, BookAuthor};
+use async_graphql::{dataloader::DataLoader, Context, Object, Result};
+
+#[Object]
+impl Book {
+    async fn id(&self) -> i32 {
+        self.id
+    }
+
+    async fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn authors(&self, context: &Context<'_>) -> Result<Vec<Author>> {
+        let authors = context
+            .data::<DataLoader<AuthorsForBookLoader>>()?
+            .load_one(self.id)
+            .await?;
+
+        Ok(authors.unwrap_or_default())
+    }
+}
+
+#[Object]
+impl Author {
+    async fn id(&self) -> i32 {
+        self.id
+    }
+
+    async fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn books(&self, context: &Context<'_>) -> Result<Vec<Book>> {
+        let books = context
+            .data::<DataLoader<BooksForAuthorLoader>>()?
+            .load_one(self.id)
+            .await?;
+
+        Ok(books.unwrap_or_default())
+    }
+}
+
+#[Object]
+impl BookAuthor {
+    async fn book_id(&self) -> i32 {
+        self.book_id
+    }
+
+    async fn author_id(&self) -> i32 {
+        self.author_id
+    }
+
+    async fn book(&self, context: &Context<'_>) -> Result<Option<Book>> {
+        context
+            .data::<DataLoader<BookLoader>>()?
+            .load_one(self.book_id)
+            .await
+    }
+
+    async fn author(&self, context: &Context<'_>) -> Result<Option<Author>> {
+        context
+            .data::<DataLoader<AuthorLoader>>()?
+            .load_one(self.author_id)
+            .await
+    }
+}
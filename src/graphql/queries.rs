@@ -0,0 +1,33 @@
+use super::{
+    loaders::{AuthorLoader, BookLoader},
+    Database,
+};
+use crate::{
+    models::{Author, Book},
+    queries::{author_queries::get_all_authors, book_queries::get_all_books},
+};
+use async_graphql::{dataloader::DataLoader, Context, Object, Result};
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    async fn books(&self, context: &Context<'_>) -> Result<Vec<Book>> {
+        context.data::<Database>()?.run(get_all_books).await
+    }
+
+    async fn book(&self, context: &Context<'_>, id: i32) -> Result<Option<Book>> {
+        context.data::<DataLoader<BookLoader>>()?.load_one(id).await
+    }
+
+    async fn authors(&self, context: &Context<'_>) -> Result<Vec<Author>> {
+        context.data::<Database>()?.run(get_all_authors).await
+    }
+
+    async fn author(&self, context: &Context<'_>, id: i32) -> Result<Option<Author>> {
+        context
+            .data::<DataLoader<AuthorLoader>>()?
+            .load_one(id)
+            .await
+    }
+}
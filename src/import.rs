@@ -0,0 +1,532 @@
+use std::{collections::HashMap, fmt, io::Read};
+
+use csv::{ReaderBuilder, StringRecord};
+use eyre::{bail, Result};
+
+use crate::{
+    connect::DbConnection,
+    instrumentation::query_span,
+    queries::{
+        author_queries::{create_author, get_author_by_id, get_authors_by_name},
+        book_author_queries::{associate_book_with_author, book_author_exists},
+        book_queries::{create_book, get_book_by_id, get_books_by_name},
+    },
+    validation::{normalize, validate_name},
+};
+
+pub struct ImportSources<R> {
+    pub books: Option<R>,
+    pub authors: Option<R>,
+    pub book_authors: Option<R>,
+}
+
+impl<R> Default for ImportSources<R> {
+    fn default() -> Self {
+        Self {
+            books: None,
+            authors: None,
+            book_authors: None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImportReport {
+    pub books_created: usize,
+    pub authors_created: usize,
+    pub links_created: usize,
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowError {
+    pub file: &'static str,
+    pub line: u64,
+    pub message: String,
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} line {}: {}", self.file, self.line, self.message)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportError {
+    pub errors: Vec<RowError>,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "import failed with {} error(s)", self.errors.len())?;
+
+        for error in &self.errors {
+            write!(f, "\n  {error}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+#[derive(Debug)]
+struct DryRunRollback;
+
+impl fmt::Display for DryRunRollback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "dry run rolled back")
+    }
+}
+
+impl std::error::Error for DryRunRollback {}
+
+const BOOKS: &str = "books";
+const AUTHORS: &str = "authors";
+const BOOK_AUTHORS: &str = "book_authors";
+
+struct NamedRow {
+    source_id: Option<i32>,
+    name: String,
+}
+
+enum Reference {
+    Id(i32),
+    Name(String),
+}
+
+struct LinkRow {
+    line: u64,
+    author: Reference,
+    book: Reference,
+}
+
+#[derive(Default)]
+struct Inserted {
+    by_source_id: HashMap<i32, i32>,
+    by_name: HashMap<String, Vec<i32>>,
+}
+
+/// Loads the given CSV files in a single transaction. Nothing is written when any
+/// row fails, and `dry_run` rolls back after every row has been checked.
+#[query_span(skip(sources, connection))]
+pub fn import_csv<R: Read>(
+    sources: ImportSources<R>,
+    dry_run: bool,
+    connection: &mut DbConnection,
+) -> Result<ImportReport> {
+    use diesel::Connection;
+
+    let mut errors = Vec::new();
+    let books = match sources.books {
+        Some(reader) => read_named_rows(reader, BOOKS, "book_id", &mut errors)?,
+        None => Vec::new(),
+    };
+    let authors = match sources.authors {
+        Some(reader) => read_named_rows(reader, AUTHORS, "author_id", &mut errors)?,
+        None => Vec::new(),
+    };
+    let links = match sources.book_authors {
+        Some(reader) => read_link_rows(reader, &mut errors)?,
+        None => Vec::new(),
+    };
+
+    if !errors.is_empty() {
+        bail!(ImportError { errors });
+    }
+
+    let mut dry_run_report = None;
+    let result = connection.transaction::<_, eyre::Report, _>(|connection| {
+        let inserted_books = insert_rows(&books, create_book, connection)?;
+        let inserted_authors = insert_rows(&authors, create_author, connection)?;
+        let mut links_created = 0;
+        let mut seen_links = HashMap::new();
+
+        for link in &links {
+            let book_id = resolve_book(&link.book, &inserted_books, connection);
+            let author_id = resolve_author(&link.author, &inserted_authors, connection);
+            let (book_id, author_id) = match (book_id?, author_id?) {
+                (Ok(book_id), Ok(author_id)) => (book_id, author_id),
+                (book_id, author_id) => {
+                    for message in [book_id.err(), author_id.err()].into_iter().flatten() {
+                        errors.push(row_error(BOOK_AUTHORS, link.line, message));
+                    }
+
+                    continue;
+                }
+            };
+
+            if let Some(first_line) = seen_links.insert((book_id, author_id), link.line) {
+                errors.push(row_error(
+                    BOOK_AUTHORS,
+                    link.line,
+                    format!("duplicates the link on line {first_line}"),
+                ));
+                continue;
+            }
+
+            if book_author_exists(book_id, author_id, connection)? {
+                errors.push(row_error(
+                    BOOK_AUTHORS,
+                    link.line,
+                    format!("book {book_id} is already linked to author {author_id}"),
+                ));
+                continue;
+            }
+
+            associate_book_with_author(book_id, author_id, connection)?;
+            links_created += 1;
+        }
+
+        if !errors.is_empty() {
+            bail!(ImportError {
+                errors: std::mem::take(&mut errors),
+            });
+        }
+
+        let report = ImportReport {
+            books_created: books.len(),
+            authors_created: authors.len(),
+            links_created,
+            dry_run,
+        };
+
+        if dry_run {
+            dry_run_report = Some(report);
+            bail!(DryRunRollback);
+        }
+
+        Ok(report)
+    });
+
+    match (result, dry_run_report) {
+        (Err(error), Some(report)) if error.is::<DryRunRollback>() => Ok(report),
+        (result, _) => result,
+    }
+}
+
+fn row_error(file: &'static str, line: u64, message: impl Into<String>) -> RowError {
+    RowError {
+        file,
+        line,
+        message: message.into(),
+    }
+}
+
+fn record_line(record: &StringRecord) -> u64 {
+    record.position().map_or(0, |position| position.line())
+}
+
+fn column(headers: &StringRecord, name: &str) -> Option<usize> {
+    headers.iter().position(|header| header.trim() == name)
+}
+
+fn check_unknown_columns(
+    headers: &StringRecord,
+    file: &'static str,
+    known: &[&str],
+    errors: &mut Vec<RowError>,
+) {
+    for header in headers.iter() {
+        if !known.contains(&header.trim()) {
+            errors.push(row_error(
+                file,
+                1,
+                format!("unexpected column `{}`", header.trim()),
+            ));
+        }
+    }
+}
+
+fn parse_id(value: &str, column_name: &str) -> Result<i32, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("{column_name} `{value}` is not a valid id"))
+}
+
+fn read_named_rows(
+    reader: impl Read,
+    file: &'static str,
+    id_column: &str,
+    errors: &mut Vec<RowError>,
+) -> Result<Vec<NamedRow>> {
+    let mut reader = ReaderBuilder::new().flexible(true).from_reader(reader);
+    let headers = reader.headers()?.clone();
+    let id_index = column(&headers, id_column);
+    let Some(name_index) = column(&headers, "name") else {
+        errors.push(row_error(file, 1, "missing required column `name`"));
+        return Ok(Vec::new());
+    };
+
+    check_unknown_columns(&headers, file, &[id_column, "name"], errors);
+
+    let mut rows = Vec::new();
+    let mut seen_ids = HashMap::new();
+
+    for record in reader.records() {
+        let record = match record {
+            Ok(record) => record,
+            Err(error) => {
+                let line = error.position().map_or(0, |position| position.line());
+                errors.push(row_error(file, line, error.to_string()));
+                continue;
+            }
+        };
+        let line = record_line(&record);
+
+        if record.len() != headers.len() {
+            errors.push(row_error(
+                file,
+                line,
+                format!("expected {} fields, found {}", headers.len(), record.len()),
+            ));
+            continue;
+        }
+
+        let source_id = match id_index.map(|index| parse_id(&record[index], id_column)) {
+            Some(Ok(source_id)) => {
+                if let Some(first_line) = seen_ids.insert(source_id, line) {
+                    errors.push(row_error(
+                        file,
+                        line,
+                        format!("{id_column} {source_id} already used on line {first_line}"),
+                    ));
+                    continue;
+                }
+
+                Some(source_id)
+            }
+            Some(Err(message)) => {
+                errors.push(row_error(file, line, message));
+                continue;
+            }
+            None => None,
+        };
+
+        match validate_name("name", &record[name_index]) {
+            Ok(name) => rows.push(NamedRow { source_id, name }),
+            Err(error) => errors.push(row_error(file, line, error.to_string())),
+        }
+    }
+
+    Ok(rows)
+}
+
+fn read_link_rows(reader: impl Read, errors: &mut Vec<RowError>) -> Result<Vec<LinkRow>> {
+    let mut reader = ReaderBuilder::new().flexible(true).from_reader(reader);
+    let headers = reader.headers()?.clone();
+    let author_column = reference_column(&headers, "author", errors);
+    let book_column = reference_column(&headers, "book", errors);
+
+    check_unknown_columns(
+        &headers,
+        BOOK_AUTHORS,
+        &["author_id", "author_name", "book_id", "book_name"],
+        errors,
+    );
+
+    let (Some(author_column), Some(book_column)) = (author_column, book_column) else {
+        return Ok(Vec::new());
+    };
+    let mut rows = Vec::new();
+
+    for record in reader.records() {
+        let record = match record {
+            Ok(record) => record,
+            Err(error) => {
+                let line = error.position().map_or(0, |position| position.line());
+                errors.push(row_error(BOOK_AUTHORS, line, error.to_string()));
+                continue;
+            }
+        };
+        let line = record_line(&record);
+
+        if record.len() != headers.len() {
+            errors.push(row_error(
+                BOOK_AUTHORS,
+                line,
+                format!("expected {} fields, found {}", headers.len(), record.len()),
+            ));
+            continue;
+        }
+
+        match (author_column.parse(&record), book_column.parse(&record)) {
+            (Ok(author), Ok(book)) => rows.push(LinkRow { line, author, book }),
+            (author, book) => {
+                for message in [author.err(), book.err()].into_iter().flatten() {
+                    errors.push(row_error(BOOK_AUTHORS, line, message));
+                }
+            }
+        }
+    }
+
+    Ok(rows)
+}
+
+struct ReferenceColumn {
+    index: usize,
+    name: &'static str,
+    by_id: bool,
+}
+
+impl ReferenceColumn {
+    fn parse(&self, record: &StringRecord) -> Result<Reference, String> {
+        let value = &record[self.index];
+
+        if self.by_id {
+            parse_id(value, self.name).map(Reference::Id)
+        } else {
+            let name = normalize(value);
+
+            if name.is_empty() {
+                return Err(format!("{} must not be empty", self.name));
+            }
+
+            Ok(Reference::Name(name))
+        }
+    }
+}
+
+fn reference_column(
+    headers: &StringRecord,
+    kind: &'static str,
+    errors: &mut Vec<RowError>,
+) -> Option<ReferenceColumn> {
+    let (id_name, name_name) = match kind {
+        "author" => ("author_id", "author_name"),
+        _ => ("book_id", "book_name"),
+    };
+
+    match (column(headers, id_name), column(headers, name_name)) {
+        (Some(index), None) => Some(ReferenceColumn {
+            index,
+            name: id_name,
+            by_id: true,
+        }),
+        (None, Some(index)) => Some(ReferenceColumn {
+            index,
+            name: name_name,
+            by_id: false,
+        }),
+        (Some(_), Some(_)) => {
+            errors.push(row_error(
+                BOOK_AUTHORS,
+                1,
+                format!("use only one of `{id_name}` and `{name_name}`"),
+            ));
+            None
+        }
+        (None, None) => {
+            errors.push(row_error(
+                BOOK_AUTHORS,
+                1,
+                format!("missing required column `{id_name}` or `{name_name}`"),
+            ));
+            None
+        }
+    }
+}
+
+fn insert_rows(
+    rows: &[NamedRow],
+    create: fn(&str, &mut DbConnection) -> Result<i32>,
+    connection: &mut DbConnection,
+) -> Result<Inserted> {
+    let mut inserted = Inserted::default();
+
+    for row in rows {
+        let id = create(&row.name, connection)?;
+
+        if let Some(source_id) = row.source_id {
+            inserted.by_source_id.insert(source_id, id);
+        }
+
+        inserted
+            .by_name
+            .entry(row.name.clone())
+            .or_default()
+            .push(id);
+    }
+
+    Ok(inserted)
+}
+
+fn resolve_book(
+    reference: &Reference,
+    inserted: &Inserted,
+    connection: &mut DbConnection,
+) -> Result<Result<i32, String>> {
+    resolve(
+        reference,
+        inserted,
+        "book",
+        |reference, connection| {
+            Ok(match reference {
+                Reference::Id(id) => get_book_by_id(*id, connection)?
+                    .into_iter()
+                    .map(|book| book.id)
+                    .collect(),
+                Reference::Name(name) => get_books_by_name(name, connection)?
+                    .into_iter()
+                    .map(|book| book.id)
+                    .collect(),
+            })
+        },
+        connection,
+    )
+}
+
+fn resolve_author(
+    reference: &Reference,
+    inserted: &Inserted,
+    connection: &mut DbConnection,
+) -> Result<Result<i32, String>> {
+    resolve(
+        reference,
+        inserted,
+        "author",
+        |reference, connection| {
+            Ok(match reference {
+                Reference::Id(id) => get_author_by_id(*id, connection)?
+                    .into_iter()
+                    .map(|author| author.id)
+                    .collect(),
+                Reference::Name(name) => get_authors_by_name(name, connection)?
+                    .into_iter()
+                    .map(|author| author.id)
+                    .collect(),
+            })
+        },
+        connection,
+    )
+}
+
+/// Ids and names from the imported files win over rows already in the database.
+fn resolve(
+    reference: &Reference,
+    inserted: &Inserted,
+    kind: &str,
+    existing: impl FnOnce(&Reference, &mut DbConnection) -> Result<Vec<i32>>,
+    connection: &mut DbConnection,
+) -> Result<Result<i32, String>> {
+    let imported = match reference {
+        Reference::Id(id) => inserted.by_source_id.get(id).map(|id| vec![*id]),
+        Reference::Name(name) => inserted.by_name.get(name).cloned(),
+    };
+    let candidates = match imported {
+        Some(candidates) => candidates,
+        None => existing(reference, connection)?,
+    };
+    let description = match reference {
+        Reference::Id(id) => format!("{kind} {id}"),
+        Reference::Name(name) => format!("{kind} `{name}`"),
+    };
+
+    Ok(match candidates.as_slice() {
+        [id] => Ok(*id),
+        [] => Err(format!("{description} not found")),
+        _ => Err(format!("{description} is ambiguous")),
+    })
+}
@@ -0,0 +1,38 @@
+#[cfg(feature = "tracing")]
+mod query_tracing;
+
+use diesel::Connection;
+use eyre::Result;
+
+pub(crate) use diesel_bookstore_macros::query_span;
+#[cfg(feature = "tracing")]
+pub use query_tracing::QueryTracing;
+
+#[cfg(feature = "tracing")]
+pub(crate) fn instrument(connection: &mut impl Connection) -> Result<()> {
+    connection.set_instrumentation(QueryTracing::from_env()?);
+
+    Ok(())
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn instrument(_connection: &mut impl Connection) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(feature = "tracing")]
+pub(crate) fn record_rows(rows: usize) {
+    tracing::Span::current().record("rows", rows);
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn record_rows(_rows: usize) {}
+
+/// Logs an error that is not shown to the client in full.
+#[cfg(all(feature = "server", feature = "tracing"))]
+pub(crate) fn record_error(context: &str, error: &eyre::Report) {
+    tracing::error!(error = ?error, "{context}");
+}
+
+#[cfg(all(feature = "server", not(feature = "tracing")))]
+pub(crate) fn record_error(_context: &str, _error: &eyre::Report) {}
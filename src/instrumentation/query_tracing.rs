@@ -0,0 +1,64 @@
+use diesel::connection::{Instrumentation, InstrumentationEvent};
+use eyre::{Context, Result};
+use std::{
+    env,
+    time::{Duration, Instant},
+};
+
+const DEFAULT_SLOW_QUERY_THRESHOLD: Duration = Duration::from_millis(500);
+
+pub struct QueryTracing {
+    slow_query_threshold: Duration,
+    started_queries: Vec<Instant>,
+}
+
+impl QueryTracing {
+    pub fn new(slow_query_threshold: Duration) -> Self {
+        Self {
+            slow_query_threshold,
+            started_queries: vec![],
+        }
+    }
+
+    pub fn from_env() -> Result<Self> {
+        let slow_query_threshold = match env::var("SLOW_QUERY_THRESHOLD_MS") {
+            Ok(milliseconds) => Duration::from_millis(
+                milliseconds
+                    .parse()
+                    .context("parsing SLOW_QUERY_THRESHOLD_MS environment variable")?,
+            ),
+            Err(_) => DEFAULT_SLOW_QUERY_THRESHOLD,
+        };
+
+        Ok(Self::new(slow_query_threshold))
+    }
+}
+
+impl Instrumentation for QueryTracing {
+    fn on_connection_event(&mut self, event: InstrumentationEvent<'_>) {
+        match event {
+            InstrumentationEvent::StartQuery { .. } => self.started_queries.push(Instant::now()),
+            InstrumentationEvent::FinishQuery { query, error, .. } => {
+                let duration = self
+                    .started_queries
+                    .pop()
+                    .map(|started| started.elapsed())
+                    .unwrap_or_default();
+                let query = query.to_string();
+                let sql = query
+                    .split_once(" -- binds: ")
+                    .map_or(query.as_str(), |(sql, _)| sql);
+                let duration_ms = duration.as_secs_f64() * 1000.0;
+
+                if let Some(error) = error {
+                    tracing::error!(sql, duration_ms, %error, "query failed");
+                } else if duration >= self.slow_query_threshold {
+                    tracing::warn!(sql, duration_ms, "slow query");
+                } else {
+                    tracing::debug!(sql, duration_ms, "query finished");
+                }
+            }
+            _ => {}
+        }
+    }
+}
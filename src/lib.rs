@@ -1,4 +1,18 @@
+#[cfg(feature = "server")]
+pub mod api;
+pub mod citation;
 pub mod connect;
+pub mod export;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+pub mod import;
+pub mod instrumentation;
+pub mod marc;
 pub mod models;
+pub mod onix;
+pub mod opds;
+pub mod pricing;
 pub mod queries;
 pub mod schema;
+pub mod validation;
+mod xml;
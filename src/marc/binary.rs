@@ -0,0 +1,187 @@
+use std::io::{Read, Write};
+
+use eyre::{bail, ensure, eyre, Context, Result};
+
+use super::record::{is_control_tag, MarcField, MarcRecord, Subfield};
+
+const LEADER_LENGTH: usize = 24;
+const DIRECTORY_ENTRY_LENGTH: usize = 12;
+const FIELD_TERMINATOR: u8 = 0x1e;
+const RECORD_TERMINATOR: u8 = 0x1d;
+const SUBFIELD_DELIMITER: u8 = 0x1f;
+
+/// Reads ISO 2709 records until the end of `reader`. Text is read as UTF-8.
+pub fn read_marc21(mut reader: impl Read) -> Result<Vec<MarcRecord>> {
+    let mut bytes = Vec::new();
+
+    reader
+        .read_to_end(&mut bytes)
+        .context("reading marc21 records")?;
+
+    let mut records = Vec::new();
+    let mut remaining = bytes.as_slice();
+
+    while !remaining.iter().all(u8::is_ascii_whitespace) {
+        let number = records.len() + 1;
+        let length = parse_number(remaining.get(..5), "record length")
+            .with_context(|| format!("record {number}"))?;
+
+        ensure!(
+            length > LEADER_LENGTH && length <= remaining.len(),
+            "record {number}: record length {length} does not fit the input"
+        );
+
+        let (record, rest) = remaining.split_at(length);
+
+        records.push(parse_record(record).with_context(|| format!("record {number}"))?);
+        remaining = rest;
+    }
+
+    Ok(records)
+}
+
+pub fn write_marc21(record: &MarcRecord, mut writer: impl Write) -> Result<()> {
+    let mut directory = Vec::new();
+    let mut data = Vec::new();
+
+    for field in &record.fields {
+        let start = data.len();
+
+        match field {
+            MarcField::Control { value, .. } => data.extend_from_slice(value.as_bytes()),
+            MarcField::Data {
+                indicators,
+                subfields,
+                ..
+            } => {
+                for indicator in indicators {
+                    data.extend_from_slice(indicator.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+
+                for subfield in subfields {
+                    data.push(SUBFIELD_DELIMITER);
+                    data.extend_from_slice(subfield.code.encode_utf8(&mut [0; 4]).as_bytes());
+                    data.extend_from_slice(subfield.value.as_bytes());
+                }
+            }
+        }
+
+        data.push(FIELD_TERMINATOR);
+
+        let length = data.len() - start;
+
+        ensure!(
+            field.tag().len() == 3 && length <= 9999 && start <= 99999,
+            "field {} is too long for marc21",
+            field.tag()
+        );
+
+        directory.extend_from_slice(format!("{:0>3}{length:04}{start:05}", field.tag()).as_bytes());
+    }
+
+    directory.push(FIELD_TERMINATOR);
+
+    let base_address = LEADER_LENGTH + directory.len();
+    let record_length = base_address + data.len() + 1;
+
+    ensure!(record_length <= 99999, "record is too long for marc21");
+
+    let leader = record
+        .leader
+        .chars()
+        .map(|character| if character.is_ascii() { character } else { ' ' })
+        .collect::<String>();
+    let mut leader = format!("{leader:<24.24}").into_bytes();
+
+    leader[..5].copy_from_slice(format!("{record_length:05}").as_bytes());
+    leader[9] = b'a';
+    leader[10..12].copy_from_slice(b"22");
+    leader[12..17].copy_from_slice(format!("{base_address:05}").as_bytes());
+    leader[20..24].copy_from_slice(b"4500");
+
+    writer.write_all(&leader)?;
+    writer.write_all(&directory)?;
+    writer.write_all(&data)?;
+    writer.write_all(&[RECORD_TERMINATOR])?;
+
+    Ok(())
+}
+
+fn parse_record(bytes: &[u8]) -> Result<MarcRecord> {
+    ensure!(
+        bytes.last() == Some(&RECORD_TERMINATOR),
+        "record does not end with a record terminator"
+    );
+
+    let leader = String::from_utf8_lossy(&bytes[..LEADER_LENGTH]).into_owned();
+    let base_address = parse_number(bytes.get(12..17), "base address")?;
+    let directory = bytes
+        .get(LEADER_LENGTH..base_address.saturating_sub(1))
+        .ok_or_else(|| eyre!("base address {base_address} is outside the record"))?;
+    let data = bytes
+        .get(base_address..bytes.len() - 1)
+        .ok_or_else(|| eyre!("base address {base_address} is outside the record"))?;
+    let mut fields = Vec::new();
+
+    ensure!(
+        directory.len() % DIRECTORY_ENTRY_LENGTH == 0,
+        "directory length is not a multiple of {DIRECTORY_ENTRY_LENGTH}"
+    );
+
+    for entry in directory.chunks(DIRECTORY_ENTRY_LENGTH) {
+        let tag = String::from_utf8_lossy(&entry[..3]).into_owned();
+        let length = parse_number(Some(&entry[3..7]), "field length")?;
+        let start = parse_number(Some(&entry[7..12]), "field start")?;
+        let Some(field) = data.get(start..start + length) else {
+            bail!("field {tag} is outside the record");
+        };
+        let field = field.strip_suffix(&[FIELD_TERMINATOR]).unwrap_or(field);
+
+        fields.push(parse_field(tag, field)?);
+    }
+
+    Ok(MarcRecord { leader, fields })
+}
+
+fn parse_field(tag: String, bytes: &[u8]) -> Result<MarcField> {
+    if is_control_tag(&tag) {
+        return Ok(MarcField::Control {
+            tag,
+            value: String::from_utf8_lossy(bytes).into_owned(),
+        });
+    }
+
+    let mut parts = bytes.split(|byte| *byte == SUBFIELD_DELIMITER);
+    let indicators = String::from_utf8_lossy(parts.next().unwrap_or_default());
+    let mut indicator_chars = indicators.chars().chain([' ', ' ']);
+    let indicators = [
+        indicator_chars.next().unwrap_or(' '),
+        indicator_chars.next().unwrap_or(' '),
+    ];
+    let subfields = parts
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let text = String::from_utf8_lossy(part);
+            let mut characters = text.chars();
+            let code = characters.next().unwrap_or(' ');
+
+            Subfield {
+                code,
+                value: characters.as_str().to_owned(),
+            }
+        })
+        .collect();
+
+    Ok(MarcField::Data {
+        tag,
+        indicators,
+        subfields,
+    })
+}
+
+fn parse_number(bytes: Option<&[u8]>, what: &str) -> Result<usize> {
+    bytes
+        .and_then(|bytes| std::str::from_utf8(bytes).ok())
+        .and_then(|digits| digits.parse().ok())
+        .ok_or_else(|| eyre!("{what} is not a number"))
+}
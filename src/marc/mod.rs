@@ -0,0 +1,392 @@
+mod binary;
+mod record;
+mod xml;
+
+pub use binary::{read_marc21, write_marc21};
+pub use record::{is_control_tag, MarcField, MarcRecord, Subfield, BOOK_LEADER};
+pub use xml::{read_marcxml, MarcXmlWriter};
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, Write},
+};
+
+use eyre::Result;
+
+use crate::{
+    citation::{is_suffix, PersonName},
+    connect::DbConnection,
+    instrumentation::query_span,
+    models::{BookIdentifier, BookWithAuthors, IdentifierScheme},
+    queries::{
+        author_queries::{create_author, get_authors_by_name},
+        book_author_queries::{associate_book_with_author, get_authors_for_books},
+        book_identifier_queries::{
+            add_book_identifier, get_book_by_identifier, get_identifiers_for_books,
+        },
+        book_queries::{create_book, get_books_page},
+        stream_queries::KeysetStream,
+    },
+    validation::{validate_isbn, validate_name},
+};
+
+const ISBN_TAG: &str = "020";
+const MAIN_ENTRY_TAG: &str = "100";
+const TITLE_TAG: &str = "245";
+const ADDED_ENTRY_TAG: &str = "700";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarcSyntax {
+    Binary,
+    Xml,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MarcImportReport {
+    pub books_created: usize,
+    pub authors_created: usize,
+    pub links_created: usize,
+    pub skipped_records: Vec<SkippedRecord>,
+    pub skipped_fields: Vec<SkippedField>,
+}
+
+/// `record` counts from 1 in the order records appear in the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SkippedRecord {
+    pub record: usize,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SkippedField {
+    pub record: usize,
+    pub tag: String,
+    pub reason: String,
+}
+
+pub fn read_marc(syntax: MarcSyntax, reader: impl BufRead) -> Result<Vec<MarcRecord>> {
+    match syntax {
+        MarcSyntax::Binary => read_marc21(reader),
+        MarcSyntax::Xml => read_marcxml(reader),
+    }
+}
+
+/// Creates a book for every record with a usable 245 title, reusing authors that already
+/// exist under the same name. Records whose ISBN is already in the catalog are skipped.
+#[query_span(skip_all)]
+pub fn import_marc(
+    records: &[MarcRecord],
+    connection: &mut DbConnection,
+) -> Result<MarcImportReport> {
+    use diesel::Connection;
+
+    connection.transaction(|connection| {
+        let mut report = MarcImportReport::default();
+        let mut authors_by_name = HashMap::new();
+
+        for (index, record) in records.iter().enumerate() {
+            import_record(
+                index + 1,
+                record,
+                &mut authors_by_name,
+                &mut report,
+                connection,
+            )?;
+        }
+
+        Ok(report)
+    })
+}
+
+fn import_record(
+    number: usize,
+    record: &MarcRecord,
+    authors_by_name: &mut HashMap<String, i32>,
+    report: &mut MarcImportReport,
+    connection: &mut DbConnection,
+) -> Result<()> {
+    let mut title = None;
+    let mut isbns = Vec::new();
+    let mut author_names = Vec::new();
+    let mut skip_field = |field: &MarcField, reason: String| {
+        report.skipped_fields.push(SkippedField {
+            record: number,
+            tag: field.tag().to_owned(),
+            reason,
+        });
+    };
+
+    for field in &record.fields {
+        match field.tag() {
+            TITLE_TAG if title.is_none() => match validate_name("title", &marc_title(field)) {
+                Ok(value) => title = Some(value),
+                Err(error) => skip_field(field, error.to_string()),
+            },
+            TITLE_TAG => skip_field(field, "repeated 245 ignored".to_owned()),
+            ISBN_TAG => {
+                for value in field.subfields('a') {
+                    let candidate = value.split_whitespace().next().unwrap_or_default();
+
+                    match validate_isbn("isbn", candidate) {
+                        Ok(isbn) if !isbns.contains(&isbn) => isbns.push(isbn),
+                        Ok(_) => {}
+                        Err(error) => skip_field(field, format!("{error}: `{value}`")),
+                    }
+                }
+
+                for value in field.subfields('z') {
+                    skip_field(field, format!("cancelled isbn `{value}`"));
+                }
+            }
+            MAIN_ENTRY_TAG | ADDED_ENTRY_TAG => {
+                match validate_name("author", &marc_person_name(field)) {
+                    Ok(name) if !author_names.contains(&name) => author_names.push(name),
+                    Ok(_) => {}
+                    Err(error) => skip_field(field, error.to_string()),
+                }
+            }
+            _ => skip_field(field, "not mapped".to_owned()),
+        }
+    }
+
+    let Some(title) = title else {
+        report.skipped_records.push(SkippedRecord {
+            record: number,
+            reason: "no usable 245 title".to_owned(),
+        });
+
+        return Ok(());
+    };
+
+    for isbn in &isbns {
+        if let Some(book) = get_book_by_identifier(IdentifierScheme::Isbn, isbn, connection)? {
+            report.skipped_records.push(SkippedRecord {
+                record: number,
+                reason: format!("isbn {isbn} already belongs to book {}", book.id),
+            });
+
+            return Ok(());
+        }
+    }
+
+    let book_id = create_book(&title, connection)?;
+
+    report.books_created += 1;
+
+    for isbn in &isbns {
+        add_book_identifier(book_id, IdentifierScheme::Isbn, isbn, connection)?;
+    }
+
+    for name in author_names {
+        let author_id = match authors_by_name.get(&name) {
+            Some(author_id) => *author_id,
+            None => {
+                let existing = get_authors_by_name(&name, connection)?;
+                let author_id = match existing.as_slice() {
+                    [author] => author.id,
+                    _ => {
+                        report.authors_created += 1;
+                        create_author(&name, connection)?
+                    }
+                };
+
+                authors_by_name.insert(name, author_id);
+                author_id
+            }
+        };
+
+        associate_book_with_author(book_id, author_id, connection)?;
+        report.links_created += 1;
+    }
+
+    Ok(())
+}
+
+/// Builds a MARC record with the book id in 001, ISBNs in 020, the first author in 100,
+/// the title in 245 and any further authors in 700.
+pub fn book_record(
+    book_with_authors: &BookWithAuthors,
+    identifiers: &[BookIdentifier],
+) -> MarcRecord {
+    let book = book_with_authors.book();
+    let mut fields = vec![MarcField::Control {
+        tag: "001".to_owned(),
+        value: book.id.to_string(),
+    }];
+
+    for identifier in identifiers {
+        if identifier.scheme == IdentifierScheme::Isbn.as_str() {
+            fields.push(data_field(
+                ISBN_TAG,
+                [' ', ' '],
+                [('a', identifier.value.clone())],
+            ));
+        }
+    }
+
+    let mut authors = book_with_authors.authors().iter();
+
+    if let Some(author) = authors.next() {
+        fields.push(person_field(MAIN_ENTRY_TAG, &author.name));
+    }
+
+    let title_indicator = if fields.iter().any(|field| field.tag() == MAIN_ENTRY_TAG) {
+        '1'
+    } else {
+        '0'
+    };
+
+    fields.push(data_field(
+        TITLE_TAG,
+        [title_indicator, '0'],
+        [('a', book.name.clone())],
+    ));
+    fields.extend(authors.map(|author| person_field(ADDED_ENTRY_TAG, &author.name)));
+
+    MarcRecord::new(fields)
+}
+
+#[query_span(skip(writer, connection))]
+pub fn export_marc<W: Write>(
+    syntax: MarcSyntax,
+    mut writer: W,
+    batch_size: i64,
+    connection: &mut DbConnection,
+) -> Result<usize> {
+    let records = KeysetStream::new(
+        batch_size,
+        |after_id, limit, connection| {
+            let books = get_books_page(after_id, limit, connection)?;
+            let identifiers = get_identifiers_for_books(&books, connection)?;
+            let books_with_authors = get_authors_for_books(books, connection)?;
+
+            Ok(books_with_authors.into_iter().zip(identifiers).collect())
+        },
+        |(book_with_authors, _)| book_with_authors.book().id,
+        connection,
+    )
+    .map(|item| item.map(|(book, identifiers)| book_record(&book, &identifiers)));
+    let mut written = 0;
+
+    match syntax {
+        MarcSyntax::Binary => {
+            for record in records {
+                write_marc21(&record?, &mut writer)?;
+                written += 1;
+            }
+
+            writer.flush()?;
+        }
+        MarcSyntax::Xml => {
+            let mut xml = MarcXmlWriter::new(writer)?;
+
+            for record in records {
+                xml.write_record(&record?)?;
+                written += 1;
+            }
+
+            xml.finish()?;
+        }
+    }
+
+    Ok(written)
+}
+
+fn data_field<const N: usize>(
+    tag: &str,
+    indicators: [char; 2],
+    subfields: [(char, String); N],
+) -> MarcField {
+    MarcField::Data {
+        tag: tag.to_owned(),
+        indicators,
+        subfields: subfields
+            .into_iter()
+            .map(|(code, value)| Subfield { code, value })
+            .collect(),
+    }
+}
+
+fn person_field(tag: &str, name: &str) -> MarcField {
+    let name = PersonName::parse(name);
+    let Some(given) = &name.given else {
+        return data_field(tag, ['0', ' '], [('a', name.family.clone())]);
+    };
+    let heading = format!("{}, {given}", name.full_family());
+
+    match &name.suffix {
+        Some(suffix) => data_field(tag, ['1', ' '], [('a', heading), ('c', suffix.clone())]),
+        None => data_field(tag, ['1', ' '], [('a', heading)]),
+    }
+}
+
+/// Joins 245 $a and $b and drops the ISBD punctuation that separates subfields.
+fn marc_title(field: &MarcField) -> String {
+    field
+        .subfields('a')
+        .chain(field.subfields('b'))
+        .map(trim_isbd_punctuation)
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// Turns an inverted "Melville, Herman," heading back into "Herman Melville".
+fn marc_person_name(field: &MarcField) -> String {
+    let heading = field
+        .subfields('a')
+        .next()
+        .map(|value| value.trim().trim_end_matches(',').trim())
+        .unwrap_or_default();
+    let inverted = matches!(
+        field,
+        MarcField::Data {
+            indicators: ['1', _],
+            ..
+        }
+    );
+    let mut name = match heading.split_once(',') {
+        Some((family, given)) if inverted => format!("{} {}", given.trim(), family.trim()),
+        _ => heading.to_owned(),
+    };
+
+    for suffix in field.subfields('c') {
+        let suffix = suffix.trim().trim_end_matches(',');
+
+        if is_suffix(suffix) {
+            name = format!("{name} {suffix}");
+        }
+    }
+
+    name
+}
+
+/// Drops the ISBD mark that ends a subfield: " /", " :", " ;", " =" or a closing
+/// period. The period stays when it ends an initialism like "U.S.A." or a suffix like "Jr.".
+fn trim_isbd_punctuation(value: &str) -> &str {
+    let value = value.trim_end();
+
+    for mark in [" /", " :", " ;", " ="] {
+        if let Some(trimmed) = value.strip_suffix(mark) {
+            return trimmed.trim_end();
+        }
+    }
+
+    match value.strip_suffix('.') {
+        Some(trimmed) if !ends_with_abbreviation(trimmed) => trimmed,
+        _ => value,
+    }
+}
+
+fn ends_with_abbreviation(value: &str) -> bool {
+    let word = value.rsplit(' ').next().unwrap_or_default();
+    let mut initial = word.rsplit('.').next().unwrap_or_default().chars();
+    let single_letter = matches!(
+        (initial.next(), initial.next()),
+        (Some(letter), None) if letter.is_alphabetic()
+    );
+
+    single_letter || is_suffix(&format!("{word}."))
+}
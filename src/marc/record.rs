@@ -0,0 +1,62 @@
+/// Leader for a new UTF-8 book record. Length and base address are filled in on write.
+pub const BOOK_LEADER: &str = "00000nam a2200000 i 4500";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarcRecord {
+    pub leader: String,
+    pub fields: Vec<MarcField>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarcField {
+    Control {
+        tag: String,
+        value: String,
+    },
+    Data {
+        tag: String,
+        indicators: [char; 2],
+        subfields: Vec<Subfield>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subfield {
+    pub code: char,
+    pub value: String,
+}
+
+impl MarcRecord {
+    pub fn new(fields: Vec<MarcField>) -> Self {
+        Self {
+            leader: BOOK_LEADER.to_owned(),
+            fields,
+        }
+    }
+}
+
+impl MarcField {
+    pub fn tag(&self) -> &str {
+        match self {
+            Self::Control { tag, .. } | Self::Data { tag, .. } => tag,
+        }
+    }
+
+    /// Values of every subfield with `code`, in order. Control fields have none.
+    pub fn subfields<'a>(&'a self, code: char) -> impl Iterator<Item = &'a str> + 'a {
+        let subfields = match self {
+            Self::Control { .. } => &[][..],
+            Self::Data { subfields, .. } => subfields.as_slice(),
+        };
+
+        subfields
+            .iter()
+            .filter(move |subfield| subfield.code == code)
+            .map(|subfield| subfield.value.as_str())
+    }
+}
+
+/// Tags 001 through 009 hold a single value instead of indicators and subfields.
+pub fn is_control_tag(tag: &str) -> bool {
+    tag.starts_with("00")
+}
@@ -0,0 +1,213 @@
+use std::io::{BufRead, Write};
+
+use eyre::{eyre, Context, Result};
+use quick_xml::{
+    escape::escape,
+    events::{BytesStart, Event},
+    Reader,
+};
+
+use super::record::{MarcField, MarcRecord, Subfield};
+
+const MARCXML_NAMESPACE: &str = "http://www.loc.gov/MARC21/slim";
+
+/// Reads every `record` element, with or without the `marc:` prefix.
+pub fn read_marcxml(reader: impl BufRead) -> Result<Vec<MarcRecord>> {
+    let mut xml = Reader::from_reader(reader);
+    let mut buffer = Vec::new();
+    let mut records = Vec::new();
+    let mut record: Option<MarcRecord> = None;
+    let mut text = String::new();
+    let mut control_tag = String::new();
+    let mut subfield_code = ' ';
+
+    xml.config_mut().trim_text(true);
+
+    loop {
+        let position = xml.buffer_position();
+        let event = xml
+            .read_event_into(&mut buffer)
+            .with_context(|| format!("reading marcxml at byte {position}"))?;
+
+        match event {
+            Event::Start(element) if record.is_none() => {
+                record = (element.local_name().as_ref() == b"record").then(|| MarcRecord {
+                    leader: String::new(),
+                    fields: Vec::new(),
+                });
+            }
+            Event::Empty(_) if record.is_none() => {}
+            Event::Start(element) => {
+                text.clear();
+
+                match element.local_name().as_ref() {
+                    b"controlfield" => control_tag = attribute(&element, "tag")?,
+                    b"datafield" => push_data_field(&element, record.as_mut())?,
+                    b"subfield" => subfield_code = subfield_code_of(&element)?,
+                    _ => {}
+                }
+            }
+            Event::Empty(element) => match element.local_name().as_ref() {
+                b"controlfield" => push_field(
+                    record.as_mut(),
+                    MarcField::Control {
+                        tag: attribute(&element, "tag")?,
+                        value: String::new(),
+                    },
+                ),
+                b"datafield" => push_data_field(&element, record.as_mut())?,
+                b"subfield" => push_subfield(record.as_mut(), subfield_code_of(&element)?, ""),
+                _ => {}
+            },
+            Event::Text(content) => text.push_str(&content.unescape()?),
+            Event::CData(content) => text.push_str(&String::from_utf8_lossy(&content)),
+            Event::End(element) => match element.local_name().as_ref() {
+                b"record" => records.extend(record.take()),
+                b"leader" => {
+                    if let Some(record) = record.as_mut() {
+                        record.leader = std::mem::take(&mut text);
+                    }
+                }
+                b"controlfield" => push_field(
+                    record.as_mut(),
+                    MarcField::Control {
+                        tag: std::mem::take(&mut control_tag),
+                        value: std::mem::take(&mut text),
+                    },
+                ),
+                b"subfield" => push_subfield(record.as_mut(), subfield_code, &text),
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+
+        buffer.clear();
+    }
+
+    Ok(records)
+}
+
+pub struct MarcXmlWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> MarcXmlWriter<W> {
+    pub fn new(mut writer: W) -> Result<Self> {
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(writer, r#"<collection xmlns="{MARCXML_NAMESPACE}">"#)?;
+
+        Ok(Self { writer })
+    }
+
+    pub fn write_record(&mut self, record: &MarcRecord) -> Result<()> {
+        let writer = &mut self.writer;
+
+        writeln!(writer, "  <record>")?;
+        writeln!(writer, "    <leader>{}</leader>", escape(&record.leader))?;
+
+        for field in &record.fields {
+            match field {
+                MarcField::Control { tag, value } => writeln!(
+                    writer,
+                    r#"    <controlfield tag="{}">{}</controlfield>"#,
+                    escape(tag),
+                    escape(value)
+                )?,
+                MarcField::Data {
+                    tag,
+                    indicators: [first, second],
+                    subfields,
+                } => {
+                    writeln!(
+                        writer,
+                        r#"    <datafield tag="{}" ind1="{}" ind2="{}">"#,
+                        escape(tag),
+                        escape(first.to_string()),
+                        escape(second.to_string())
+                    )?;
+
+                    for subfield in subfields {
+                        writeln!(
+                            writer,
+                            r#"      <subfield code="{}">{}</subfield>"#,
+                            escape(subfield.code.to_string()),
+                            escape(&subfield.value)
+                        )?;
+                    }
+
+                    writeln!(writer, "    </datafield>")?;
+                }
+            }
+        }
+
+        writeln!(writer, "  </record>")?;
+
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<W> {
+        writeln!(self.writer, "</collection>")?;
+        self.writer.flush().context("flushing marcxml")?;
+
+        Ok(self.writer)
+    }
+}
+
+fn attribute(element: &BytesStart, name: &str) -> Result<String> {
+    let value = element
+        .try_get_attribute(name)?
+        .ok_or_else(|| {
+            eyre!(
+                "{} is missing the {name} attribute",
+                String::from_utf8_lossy(element.local_name().as_ref())
+            )
+        })?
+        .unescape_value()?
+        .into_owned();
+
+    Ok(value)
+}
+
+fn subfield_code_of(element: &BytesStart) -> Result<char> {
+    Ok(attribute(element, "code")?.chars().next().unwrap_or(' '))
+}
+
+fn indicator(element: &BytesStart, name: &str) -> Result<char> {
+    let value = match element.try_get_attribute(name)? {
+        Some(value) => value.unescape_value()?.chars().next().unwrap_or(' '),
+        None => ' ',
+    };
+
+    Ok(value)
+}
+
+fn push_field(record: Option<&mut MarcRecord>, field: MarcField) {
+    if let Some(record) = record {
+        record.fields.push(field);
+    }
+}
+
+fn push_data_field(element: &BytesStart, record: Option<&mut MarcRecord>) -> Result<()> {
+    push_field(
+        record,
+        MarcField::Data {
+            tag: attribute(element, "tag")?,
+            indicators: [indicator(element, "ind1")?, indicator(element, "ind2")?],
+            subfields: Vec::new(),
+        },
+    );
+
+    Ok(())
+}
+
+fn push_subfield(record: Option<&mut MarcRecord>, code: char, value: &str) {
+    if let Some(MarcField::Data { subfields, .. }) =
+        record.and_then(|record| record.fields.last_mut())
+    {
+        subfields.push(Subfield {
+            code,
+            value: value.to_owned(),
+        });
+    }
+}
@@ -1,34 +1,49 @@
+use std::{
+    fmt::{self, Display},
+    ops::RangeInclusive,
+};
+
+use chrono::{NaiveDate, NaiveDateTime};
 use diesel::{
     associations::Associations, deserialize::Queryable, prelude::Insertable, Identifiable,
     Selectable,
 };
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-#[derive(Queryable, Selectable, Identifiable, Debug)]
+pub const MAX_NAME_LENGTH: usize = 255;
+
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[diesel(table_name = crate::schema::books)]
-#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
 pub struct Book {
     pub id: i32,
     pub name: String,
 }
 
-#[derive(Debug, Insertable)]
+#[derive(Debug, PartialEq, Insertable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[diesel(table_name = crate::schema::books)]
-#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
 pub struct NewBook {
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_name"))]
     pub name: String,
 }
 
-#[derive(Queryable, Selectable, Identifiable, Debug)]
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[diesel(table_name = crate::schema::authors)]
-#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
 pub struct Author {
     pub id: i32,
     pub name: String,
 }
 
-#[derive(Queryable, Selectable, Associations, Debug, Identifiable)]
+#[derive(Queryable, Selectable, Associations, Debug, Identifiable, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[diesel(table_name = crate::schema::book_authors)]
-#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
 #[diesel(primary_key(book_id, author_id))]
 #[diesel(belongs_to(Book))]
 #[diesel(belongs_to(Author))]
@@ -37,17 +52,953 @@ pub struct BookAuthor {
     pub author_id: i32,
 }
 
-#[derive(Insertable, Debug)]
+#[derive(Insertable, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[diesel(table_name = crate::schema::authors)]
-#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
 pub struct NewAuthor {
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_name"))]
     pub name: String,
 }
 
-#[derive(Insertable, Debug)]
+#[derive(Insertable, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[diesel(table_name = crate::schema::book_authors)]
-#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
 pub struct NewBookAuthor {
     pub book_id: i32,
     pub author_id: i32,
 }
+
+/// An author with their profile. `birth_date` and `death_date` are `PartialDate` strings and
+/// `nationality` is an ISO 3166-1 alpha-2 country code.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[diesel(table_name = crate::schema::authors)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+pub struct AuthorProfile {
+    pub id: i32,
+    pub name: String,
+    pub birth_date: Option<String>,
+    pub death_date: Option<String>,
+    pub nationality: Option<String>,
+    pub biography: Option<String>,
+}
+
+/// Profile fields to set on an author. Fields left as `None` are cleared.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuthorDetails<'a> {
+    pub birth_date: Option<&'a str>,
+    pub death_date: Option<&'a str>,
+    pub nationality: Option<&'a str>,
+    pub biography: Option<&'a str>,
+}
+
+/// A date that may be known only to the year or month, or only roughly. It is written the
+/// EDTF way: `1835`, `1835-11` or `1835-11-30`, with a trailing `~` for circa.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PartialDate {
+    pub year: i32,
+    pub month: Option<u32>,
+    pub day: Option<u32>,
+    pub circa: bool,
+}
+
+impl PartialDate {
+    /// Reads the EDTF form, and also accepts a leading `~`, `c.`, `ca.` or `circa` for
+    /// approximate dates. Years before 1000 may leave out the leading zeros.
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut rest = value.trim().to_lowercase();
+        let mut circa = false;
+
+        for prefix in ["circa", "ca.", "c.", "~"] {
+            if let Some(stripped) = rest.strip_prefix(prefix) {
+                rest = stripped.trim_start().to_owned();
+                circa = true;
+                break;
+            }
+        }
+
+        if let Some(stripped) = rest.strip_suffix('~') {
+            rest = stripped.to_owned();
+            circa = true;
+        }
+
+        let (negative, rest) = match rest.strip_prefix('-') {
+            Some(stripped) => (true, stripped),
+            None => (false, rest.as_str()),
+        };
+        let parts = rest.split('-').collect::<Vec<&str>>();
+
+        if parts.len() > 3 {
+            return None;
+        }
+
+        let number = |part: &str, lengths: RangeInclusive<usize>| -> Option<u32> {
+            if lengths.contains(&part.len())
+                && part.chars().all(|character| character.is_ascii_digit())
+            {
+                part.parse().ok()
+            } else {
+                None
+            }
+        };
+        let year_lengths = if parts.len() == 1 { 1..=4 } else { 4..=4 };
+        let year = i32::try_from(number(parts[0], year_lengths)?).ok()?;
+        let year = if negative { -year } else { year };
+        let month = match parts.get(1) {
+            Some(part) => Some(number(part, 2..=2).filter(|month| (1..=12).contains(month))?),
+            None => None,
+        };
+        let day = match parts.get(2) {
+            Some(part) => Some(number(part, 2..=2)?),
+            None => None,
+        };
+
+        if let (Some(month), Some(day)) = (month, day) {
+            NaiveDate::from_ymd_opt(year, month, day)?;
+        }
+
+        Some(Self {
+            year,
+            month,
+            day,
+            circa,
+        })
+    }
+}
+
+impl Display for PartialDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.year < 0 {
+            write!(f, "-")?;
+        }
+
+        write!(f, "{:04}", self.year.unsigned_abs())?;
+
+        if let Some(month) = self.month {
+            write!(f, "-{month:02}")?;
+        }
+
+        if let Some(day) = self.day {
+            write!(f, "-{day:02}")?;
+        }
+
+        if self.circa {
+            write!(f, "~")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Queryable, Selectable, Associations, Debug, Identifiable, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[diesel(table_name = crate::schema::author_identifiers)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+#[diesel(primary_key(scheme, value))]
+#[diesel(belongs_to(Author))]
+pub struct AuthorIdentifier {
+    pub author_id: i32,
+    pub scheme: String,
+    pub value: String,
+}
+
+#[derive(Insertable, Debug, PartialEq)]
+#[diesel(table_name = crate::schema::author_identifiers)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+pub struct NewAuthorIdentifier<'a> {
+    pub author_id: i32,
+    pub scheme: &'a str,
+    pub value: &'a str,
+}
+
+/// Authority files that identify people.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum AuthorIdentifierScheme {
+    Orcid,
+    Isni,
+    Viaf,
+    Wikidata,
+}
+
+impl AuthorIdentifierScheme {
+    pub const ALL: [Self; 4] = [Self::Orcid, Self::Isni, Self::Viaf, Self::Wikidata];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Orcid => "orcid",
+            Self::Isni => "isni",
+            Self::Viaf => "viaf",
+            Self::Wikidata => "wikidata",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|scheme| scheme.as_str() == value)
+    }
+}
+
+#[derive(Queryable, Selectable, Associations, Debug, Identifiable, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[diesel(table_name = crate::schema::book_identifiers)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+#[diesel(primary_key(scheme, value))]
+#[diesel(belongs_to(Book))]
+pub struct BookIdentifier {
+    pub book_id: i32,
+    pub scheme: String,
+    pub value: String,
+}
+
+#[derive(Insertable, Debug, PartialEq)]
+#[diesel(table_name = crate::schema::book_identifiers)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+pub struct NewBookIdentifier<'a> {
+    pub book_id: i32,
+    pub scheme: &'a str,
+    pub value: &'a str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum IdentifierScheme {
+    Isbn,
+}
+
+impl IdentifierScheme {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Isbn => "isbn",
+        }
+    }
+}
+
+/// A price in minor units (cents for USD) that applies from `effective_from` until just
+/// before `effective_to`, or indefinitely when `effective_to` is `None`.
+#[derive(Queryable, Selectable, Identifiable, Associations, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[diesel(table_name = crate::schema::book_prices)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+#[diesel(belongs_to(Book))]
+pub struct BookPrice {
+    pub id: i32,
+    pub book_id: i32,
+    pub currency: String,
+    pub amount_minor: i64,
+    pub effective_from: NaiveDateTime,
+    pub effective_to: Option<NaiveDateTime>,
+}
+
+#[derive(Insertable, Debug, PartialEq)]
+#[diesel(table_name = crate::schema::book_prices)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+pub struct NewBookPrice<'a> {
+    pub book_id: i32,
+    pub currency: &'a str,
+    pub amount_minor: i64,
+    pub effective_from: NaiveDateTime,
+    pub effective_to: Option<NaiveDateTime>,
+}
+
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[diesel(table_name = crate::schema::locations)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+pub struct Location {
+    pub id: i32,
+    pub name: String,
+}
+
+#[derive(Insertable, Debug, PartialEq)]
+#[diesel(table_name = crate::schema::locations)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+pub struct NewLocation<'a> {
+    pub name: &'a str,
+}
+
+/// Copies of a book at one location. `reserved` copies are on hand but promised to
+/// someone, so only `available()` copies can be reserved or sold.
+#[derive(
+    Queryable, Selectable, Identifiable, Associations, Insertable, Debug, Clone, PartialEq,
+)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[diesel(table_name = crate::schema::inventory)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+#[diesel(primary_key(book_id, location_id))]
+#[diesel(belongs_to(Book))]
+#[diesel(belongs_to(Location))]
+pub struct StockLevel {
+    pub book_id: i32,
+    pub location_id: i32,
+    pub quantity: i32,
+    pub reserved: i32,
+    pub reorder_threshold: i32,
+}
+
+impl StockLevel {
+    pub fn available(&self) -> i32 {
+        self.quantity - self.reserved
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LowStockItem {
+    pub book_id: i32,
+    pub book_name: String,
+    pub authors: Vec<String>,
+    pub location_id: i32,
+    pub location_name: String,
+    pub quantity: i32,
+    pub reserved: i32,
+    pub reorder_threshold: i32,
+}
+
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[diesel(table_name = crate::schema::customers)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+pub struct Customer {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+}
+
+#[derive(Insertable, Debug, PartialEq)]
+#[diesel(table_name = crate::schema::customers)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+pub struct NewCustomer<'a> {
+    pub name: &'a str,
+    pub email: &'a str,
+}
+
+/// An order starts as a cart. `location_id`, `total_minor` and `placed_at` are filled in
+/// when it is placed.
+#[derive(Queryable, Selectable, Identifiable, Associations, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[diesel(table_name = crate::schema::orders)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+#[diesel(belongs_to(Customer))]
+pub struct Order {
+    pub id: i32,
+    pub customer_id: i32,
+    pub status: String,
+    pub currency: String,
+    pub location_id: Option<i32>,
+    pub total_minor: Option<i64>,
+    pub created_at: NaiveDateTime,
+    pub placed_at: Option<NaiveDateTime>,
+}
+
+#[derive(Insertable, Debug, PartialEq)]
+#[diesel(table_name = crate::schema::orders)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+pub struct NewOrder<'a> {
+    pub customer_id: i32,
+    pub status: &'a str,
+    pub currency: &'a str,
+    pub created_at: NaiveDateTime,
+}
+
+/// `book_id` becomes `None` if the book is deleted after the order was placed.
+/// `unit_price_minor` is the price charged, recorded when the order is placed.
+#[derive(Queryable, Selectable, Identifiable, Associations, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[diesel(table_name = crate::schema::order_lines)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+#[diesel(belongs_to(Order))]
+#[diesel(belongs_to(Book))]
+pub struct OrderLine {
+    pub id: i32,
+    pub order_id: i32,
+    pub book_id: Option<i32>,
+    pub quantity: i32,
+    pub unit_price_minor: Option<i64>,
+}
+
+#[derive(Insertable, Debug, PartialEq)]
+#[diesel(table_name = crate::schema::order_lines)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+pub struct NewOrderLine {
+    pub order_id: i32,
+    pub book_id: i32,
+    pub quantity: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum OrderStatus {
+    Cart,
+    Pending,
+    Paid,
+    Shipped,
+    Cancelled,
+    Refunded,
+}
+
+impl OrderStatus {
+    pub const ALL: [Self; 6] = [
+        Self::Cart,
+        Self::Pending,
+        Self::Paid,
+        Self::Shipped,
+        Self::Cancelled,
+        Self::Refunded,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Cart => "cart",
+            Self::Pending => "pending",
+            Self::Paid => "paid",
+            Self::Shipped => "shipped",
+            Self::Cancelled => "cancelled",
+            Self::Refunded => "refunded",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|status| status.as_str() == value)
+    }
+
+    /// A cart only leaves the cart state by being placed, which is not a plain transition.
+    pub fn can_become(self, next: Self) -> bool {
+        matches!(
+            (self, next),
+            (Self::Pending, Self::Paid | Self::Cancelled)
+                | (Self::Paid, Self::Shipped | Self::Refunded)
+                | (Self::Shipped, Self::Refunded)
+        )
+    }
+}
+
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[diesel(table_name = crate::schema::members)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+pub struct Member {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+}
+
+#[derive(Insertable, Debug, PartialEq)]
+#[diesel(table_name = crate::schema::members)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+pub struct NewMember<'a> {
+    pub name: &'a str,
+    pub email: &'a str,
+}
+
+/// One physical copy of a book that can be lent out.
+#[derive(Queryable, Selectable, Identifiable, Associations, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[diesel(table_name = crate::schema::copies)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+#[diesel(belongs_to(Book))]
+pub struct BookCopy {
+    pub id: i32,
+    pub book_id: i32,
+    pub barcode: String,
+}
+
+#[derive(Insertable, Debug, PartialEq)]
+#[diesel(table_name = crate::schema::copies)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+pub struct NewBookCopy<'a> {
+    pub book_id: i32,
+    pub barcode: &'a str,
+}
+
+/// A loan is open until `returned_at` is set.
+#[derive(Queryable, Selectable, Identifiable, Associations, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[diesel(table_name = crate::schema::loans)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+#[diesel(belongs_to(BookCopy, foreign_key = copy_id))]
+#[diesel(belongs_to(Member))]
+pub struct Loan {
+    pub id: i32,
+    pub copy_id: i32,
+    pub member_id: i32,
+    pub checked_out_at: NaiveDateTime,
+    pub due_at: NaiveDateTime,
+    pub returned_at: Option<NaiveDateTime>,
+    pub renewals: i32,
+}
+
+#[derive(Insertable, Debug, PartialEq)]
+#[diesel(table_name = crate::schema::loans)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+pub struct NewLoan {
+    pub copy_id: i32,
+    pub member_id: i32,
+    pub checked_out_at: NaiveDateTime,
+    pub due_at: NaiveDateTime,
+}
+
+/// A member waiting for any copy of a book. `copy_id` is set when a copy is put aside for
+/// the member and the hold becomes ready.
+#[derive(Queryable, Selectable, Identifiable, Associations, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[diesel(table_name = crate::schema::holds)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+#[diesel(belongs_to(Book))]
+#[diesel(belongs_to(Member))]
+pub struct Hold {
+    pub id: i32,
+    pub book_id: i32,
+    pub member_id: i32,
+    pub status: String,
+    pub copy_id: Option<i32>,
+    pub placed_at: NaiveDateTime,
+    pub ready_at: Option<NaiveDateTime>,
+    pub closed_at: Option<NaiveDateTime>,
+}
+
+#[derive(Insertable, Debug, PartialEq)]
+#[diesel(table_name = crate::schema::holds)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+pub struct NewHold<'a> {
+    pub book_id: i32,
+    pub member_id: i32,
+    pub status: &'a str,
+    pub placed_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum HoldStatus {
+    Waiting,
+    Ready,
+    Fulfilled,
+    Cancelled,
+}
+
+impl HoldStatus {
+    pub const ALL: [Self; 4] = [Self::Waiting, Self::Ready, Self::Fulfilled, Self::Cancelled];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Waiting => "waiting",
+            Self::Ready => "ready",
+            Self::Fulfilled => "fulfilled",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|status| status.as_str() == value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OverdueLoan {
+    pub loan_id: i32,
+    pub copy_id: i32,
+    pub barcode: String,
+    pub book_id: i32,
+    pub book_name: String,
+    pub member_id: i32,
+    pub member_name: String,
+    pub due_at: NaiveDateTime,
+    pub days_overdue: i64,
+}
+
+/// One customer's review of a book. A customer reviews each book at most once.
+#[derive(Queryable, Selectable, Identifiable, Associations, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[diesel(table_name = crate::schema::reviews)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+#[diesel(belongs_to(Book))]
+#[diesel(belongs_to(Customer))]
+pub struct Review {
+    pub id: i32,
+    pub book_id: i32,
+    pub customer_id: i32,
+    pub rating: i32,
+    pub body: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Insertable, Debug, PartialEq)]
+#[diesel(table_name = crate::schema::reviews)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+pub struct NewReview<'a> {
+    pub book_id: i32,
+    pub customer_id: i32,
+    pub rating: i32,
+    pub body: &'a str,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// `average_rating` is `None` while a book has no reviews.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RatingSummary {
+    pub review_count: i64,
+    pub average_rating: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TopRatedBook {
+    pub book_id: i32,
+    pub book_name: String,
+    pub review_count: i64,
+    pub average_rating: f64,
+    pub bayesian_average: f64,
+}
+
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[diesel(table_name = crate::schema::series)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+pub struct Series {
+    pub id: i32,
+    pub name: String,
+}
+
+#[derive(Insertable, Debug, PartialEq)]
+#[diesel(table_name = crate::schema::series)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+pub struct NewSeries<'a> {
+    pub name: &'a str,
+}
+
+/// Where a book sits in a series. Positions need not be whole numbers.
+#[derive(
+    Queryable, Selectable, Identifiable, Associations, Insertable, Debug, Clone, PartialEq,
+)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[diesel(table_name = crate::schema::book_series)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+#[diesel(primary_key(book_id, series_id))]
+#[diesel(belongs_to(Book))]
+#[diesel(belongs_to(Series))]
+pub struct BookSeries {
+    pub book_id: i32,
+    pub series_id: i32,
+    pub position: f64,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SeriesEntry {
+    pub position: f64,
+    pub book: BookWithAuthors,
+}
+
+/// A series with its books in reading order.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SeriesWithBooks {
+    pub series: Series,
+    pub entries: Vec<SeriesEntry>,
+}
+
+/// The abstract work that editions (books) belong to. Author credits live here and apply
+/// to every edition.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[diesel(table_name = crate::schema::works)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+pub struct Work {
+    pub id: i32,
+    pub title: String,
+}
+
+#[derive(Insertable, Debug, PartialEq)]
+#[diesel(table_name = crate::schema::works)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+pub struct NewWork<'a> {
+    pub title: &'a str,
+}
+
+#[derive(
+    Queryable, Selectable, Identifiable, Associations, Insertable, Debug, Clone, PartialEq,
+)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[diesel(table_name = crate::schema::work_authors)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+#[diesel(primary_key(work_id, author_id))]
+#[diesel(belongs_to(Work))]
+#[diesel(belongs_to(Author))]
+pub struct WorkAuthor {
+    pub work_id: i32,
+    pub author_id: i32,
+    pub position: i32,
+}
+
+/// A credit on one edition, as an author or in another role such as translator or
+/// illustrator.
+#[derive(Insertable, Debug, PartialEq)]
+#[diesel(table_name = crate::schema::book_authors)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+pub struct NewBookContributor<'a> {
+    pub book_id: i32,
+    pub author_id: i32,
+    pub role: &'a str,
+    pub position: i32,
+}
+
+/// One person credited on a book. `from_work` tells work-level credits, shared by every
+/// edition, from those of this edition alone.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BookCredit {
+    pub author: Author,
+    pub role: String,
+    pub from_work: bool,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WorkWithEditions {
+    pub work: Work,
+    pub authors: Vec<Author>,
+    pub editions: Vec<BookWithAuthors>,
+}
+
+/// The title of a book in one language, keyed by its BCP 47 language tag.
+#[derive(Queryable, Selectable, Identifiable, Associations, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[diesel(table_name = crate::schema::book_titles)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+#[diesel(primary_key(book_id, language))]
+#[diesel(belongs_to(Book))]
+pub struct BookTitle {
+    pub book_id: i32,
+    pub language: String,
+    pub title: String,
+    pub is_original: bool,
+}
+
+#[derive(Insertable, Debug, PartialEq)]
+#[diesel(table_name = crate::schema::book_titles)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+pub struct NewBookTitle<'a> {
+    pub book_id: i32,
+    pub language: &'a str,
+    pub title: &'a str,
+    pub is_original: bool,
+}
+
+/// The title picked for a reader. `language` is `None` when the book has no titles of its
+/// own and its name is used instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LocalizedTitle {
+    pub title: String,
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum AuthorNameKind {
+    /// Another spelling of the author's name, such as one with initials.
+    Variant,
+    /// A pen name.
+    Pseudonym,
+}
+
+impl AuthorNameKind {
+    pub const ALL: [Self; 2] = [Self::Variant, Self::Pseudonym];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Variant => "variant",
+            Self::Pseudonym => "pseudonym",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == value)
+    }
+}
+
+/// Another name of an author. `kind` is one of the `AuthorNameKind` strings.
+#[derive(Queryable, Selectable, Identifiable, Associations, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[diesel(table_name = crate::schema::author_names)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+#[diesel(belongs_to(Author))]
+pub struct AuthorName {
+    pub id: i32,
+    pub author_id: i32,
+    pub name: String,
+    pub kind: String,
+}
+
+#[derive(Insertable, Debug, PartialEq)]
+#[diesel(table_name = crate::schema::author_names)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+pub struct NewAuthorName<'a> {
+    pub author_id: i32,
+    pub name: &'a str,
+    pub kind: &'a str,
+}
+
+/// The name a credited author was published under on a book.
+#[derive(
+    Queryable, Selectable, Identifiable, Associations, Insertable, Debug, Clone, PartialEq,
+)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[diesel(table_name = crate::schema::book_author_names)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+#[diesel(primary_key(book_id, author_id))]
+#[diesel(belongs_to(Book))]
+#[diesel(belongs_to(Author))]
+pub struct BookAuthorName {
+    pub book_id: i32,
+    pub author_id: i32,
+    pub author_name_id: i32,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BookWithAuthors {
+    book: Book,
+    authors: Vec<Author>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    rating: Option<RatingSummary>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    localized_title: Option<LocalizedTitle>,
+}
+
+impl BookWithAuthors {
+    pub fn new(book: Book, authors: Vec<Author>) -> Self {
+        Self {
+            book,
+            authors,
+            rating: None,
+            localized_title: None,
+        }
+    }
+
+    /// Attaches review figures, as loaded by `review_queries::with_ratings`.
+    pub fn with_rating(self, rating: RatingSummary) -> Self {
+        Self {
+            rating: Some(rating),
+            ..self
+        }
+    }
+
+    /// `None` unless the ratings were loaded.
+    pub fn rating(&self) -> Option<&RatingSummary> {
+        self.rating.as_ref()
+    }
+
+    /// Attaches the title picked for a reader, as loaded by
+    /// `book_title_queries::with_localized_titles`.
+    pub fn with_localized_title(self, localized_title: LocalizedTitle) -> Self {
+        Self {
+            localized_title: Some(localized_title),
+            ..self
+        }
+    }
+
+    /// `None` unless the titles were loaded.
+    pub fn localized_title(&self) -> Option<&LocalizedTitle> {
+        self.localized_title.as_ref()
+    }
+
+    pub fn book(&self) -> &Book {
+        &self.book
+    }
+
+    pub fn authors(&self) -> &[Author] {
+        &self.authors
+    }
+
+    /// Replaces the authors, keeping anything else that was loaded.
+    pub fn with_authors(self, authors: Vec<Author>) -> Self {
+        Self { authors, ..self }
+    }
+
+    pub fn into_parts(self) -> (Book, Vec<Author>) {
+        (self.book, self.authors)
+    }
+}
+
+impl From<(Book, Vec<Author>)> for BookWithAuthors {
+    fn from((book, authors): (Book, Vec<Author>)) -> Self {
+        Self::new(book, authors)
+    }
+}
+
+impl From<BookWithAuthors> for (Book, Vec<Author>) {
+    fn from(book_with_authors: BookWithAuthors) -> Self {
+        book_with_authors.into_parts()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AuthorWithBooks {
+    author: Author,
+    books: Vec<Book>,
+}
+
+impl AuthorWithBooks {
+    pub fn new(author: Author, books: Vec<Book>) -> Self {
+        Self { author, books }
+    }
+
+    pub fn author(&self) -> &Author {
+        &self.author
+    }
+
+    pub fn books(&self) -> &[Book] {
+        &self.books
+    }
+
+    pub fn into_parts(self) -> (Author, Vec<Book>) {
+        (self.author, self.books)
+    }
+}
+
+impl From<(Author, Vec<Book>)> for AuthorWithBooks {
+    fn from((author, books): (Author, Vec<Book>)) -> Self {
+        Self::new(author, books)
+    }
+}
+
+impl From<AuthorWithBooks> for (Author, Vec<Book>) {
+    fn from(author_with_books: AuthorWithBooks) -> Self {
+        author_with_books.into_parts()
+    }
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_name<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let name = String::deserialize(deserializer)?;
+
+    crate::validation::validate_name("name", &name).map_err(serde::de::Error::custom)
+}
@@ -0,0 +1,324 @@
+use std::io::Write;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use eyre::{ensure, Context, Result};
+
+use crate::{
+    citation::PersonName,
+    connect::DbConnection,
+    instrumentation::query_span,
+    models::{BookIdentifier, BookWithAuthors, IdentifierScheme},
+    queries::{
+        book_author_queries::get_authors_for_books,
+        book_identifier_queries::get_identifiers_for_books,
+        book_queries::{get_books_changed_since_page, get_books_page},
+        stream_queries::KeysetStream,
+    },
+    validation::validate_isbn,
+    xml::XmlWriter,
+};
+
+const ONIX_NAMESPACE: &str = "http://ns.editeur.org/onix/3.0/reference";
+const PROPRIETARY_ID_TYPE: &str = "01";
+const ISBN13_ID_TYPE: &str = "15";
+const CONFIRMED_NOTIFICATION: &str = "03";
+const AUTHOR_ROLE: &str = "A01";
+
+pub struct OnixOptions {
+    pub sender_name: String,
+    pub record_reference_prefix: String,
+    pub publisher_name: Option<String>,
+    pub require_isbn: bool,
+    /// Only books changed at or after this time are written, for delta feeds.
+    pub since: Option<NaiveDateTime>,
+    pub sent_at: DateTime<Utc>,
+}
+
+impl OnixOptions {
+    pub fn new(sender_name: &str) -> Self {
+        Self {
+            sender_name: sender_name.to_owned(),
+            record_reference_prefix: "bookstore".to_owned(),
+            publisher_name: None,
+            require_isbn: false,
+            since: None,
+            sent_at: Utc::now(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OnixReport {
+    pub products_written: usize,
+    pub rejected: Vec<RejectedProduct>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RejectedProduct {
+    pub book_id: i32,
+    pub problems: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OnixProduct {
+    pub book_id: i32,
+    pub record_reference: String,
+    pub identifiers: Vec<ProductIdentifier>,
+    pub title: String,
+    pub contributors: Vec<OnixContributor>,
+    pub publisher_name: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProductIdentifier {
+    pub id_type: &'static str,
+    pub id_type_name: Option<String>,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OnixContributor {
+    pub sequence_number: usize,
+    pub role: &'static str,
+    pub display_name: String,
+    pub name: PersonName,
+}
+
+impl OnixProduct {
+    pub fn new(
+        book_with_authors: &BookWithAuthors,
+        identifiers: &[BookIdentifier],
+        options: &OnixOptions,
+    ) -> Self {
+        let book = book_with_authors.book();
+        let mut product_identifiers = vec![ProductIdentifier {
+            id_type: PROPRIETARY_ID_TYPE,
+            id_type_name: Some(options.record_reference_prefix.clone()),
+            value: book.id.to_string(),
+        }];
+
+        product_identifiers.extend(
+            identifiers
+                .iter()
+                .filter(|identifier| identifier.scheme == IdentifierScheme::Isbn.as_str())
+                .map(|identifier| ProductIdentifier {
+                    id_type: ISBN13_ID_TYPE,
+                    id_type_name: None,
+                    value: identifier.value.clone(),
+                }),
+        );
+
+        Self {
+            book_id: book.id,
+            record_reference: format!("{}.book.{}", options.record_reference_prefix, book.id),
+            identifiers: product_identifiers,
+            title: book.name.clone(),
+            contributors: book_with_authors
+                .authors()
+                .iter()
+                .enumerate()
+                .map(|(index, author)| OnixContributor {
+                    sequence_number: index + 1,
+                    role: AUTHOR_ROLE,
+                    display_name: author.name.clone(),
+                    name: PersonName::parse(&author.name),
+                })
+                .collect(),
+            publisher_name: options.publisher_name.clone(),
+        }
+    }
+
+    /// Lists every missing or malformed element that would make the product invalid.
+    pub fn validate(&self, require_isbn: bool) -> Vec<String> {
+        let mut problems = Vec::new();
+        let isbns = self
+            .identifiers
+            .iter()
+            .filter(|identifier| identifier.id_type == ISBN13_ID_TYPE)
+            .collect::<Vec<&ProductIdentifier>>();
+
+        if self.record_reference.trim().is_empty() {
+            problems.push("RecordReference is empty".to_owned());
+        }
+
+        if self.identifiers.is_empty() {
+            problems.push("no ProductIdentifier".to_owned());
+        }
+
+        if require_isbn && isbns.is_empty() {
+            problems.push("no ProductIdentifier with an ISBN-13".to_owned());
+        }
+
+        for isbn in isbns {
+            if validate_isbn("isbn", &isbn.value).as_deref() != Ok(isbn.value.as_str()) {
+                problems.push(format!("IDValue `{}` is not an ISBN-13", isbn.value));
+            }
+        }
+
+        if self.title.trim().is_empty() {
+            problems.push("TitleText is empty".to_owned());
+        }
+
+        for contributor in &self.contributors {
+            if contributor.display_name.trim().is_empty() {
+                problems.push(format!(
+                    "Contributor {} has no name",
+                    contributor.sequence_number
+                ));
+            }
+        }
+
+        if self
+            .publisher_name
+            .as_ref()
+            .is_some_and(|publisher_name| publisher_name.trim().is_empty())
+        {
+            problems.push("PublisherName is empty".to_owned());
+        }
+
+        problems
+    }
+}
+
+/// Writes an ONIX 3.0 message. Products that fail validation are left out and listed in
+/// the report; a delta feed (`options.since`) does not include deleted books.
+#[query_span(skip_all)]
+pub fn export_onix<W: Write>(
+    writer: W,
+    options: &OnixOptions,
+    batch_size: i64,
+    connection: &mut DbConnection,
+) -> Result<OnixReport> {
+    ensure!(
+        !options.sender_name.trim().is_empty(),
+        "SenderName must not be empty"
+    );
+
+    let since = options.since;
+    let products = KeysetStream::new(
+        batch_size,
+        move |after_id, limit, connection| {
+            let books = match since {
+                Some(since) => get_books_changed_since_page(since, after_id, limit, connection)?,
+                None => get_books_page(after_id, limit, connection)?,
+            };
+            let identifiers = get_identifiers_for_books(&books, connection)?;
+            let books_with_authors = get_authors_for_books(books, connection)?;
+
+            Ok(books_with_authors.into_iter().zip(identifiers).collect())
+        },
+        |(book_with_authors, _)| book_with_authors.book().id,
+        connection,
+    );
+    let mut report = OnixReport::default();
+    let mut xml = XmlWriter::new(writer)?;
+
+    xml.open_with(
+        "ONIXMessage",
+        &[("release", "3.0"), ("xmlns", ONIX_NAMESPACE)],
+    )?;
+    xml.open("Header")?;
+    xml.open("Sender")?;
+    xml.leaf("SenderName", &options.sender_name)?;
+    xml.close("Sender")?;
+    xml.leaf(
+        "SentDateTime",
+        &options.sent_at.format("%Y%m%dT%H%M%SZ").to_string(),
+    )?;
+    xml.close("Header")?;
+
+    for item in products {
+        let (book_with_authors, identifiers) = item?;
+        let product = OnixProduct::new(&book_with_authors, &identifiers, options);
+        let problems = product.validate(options.require_isbn);
+
+        if problems.is_empty() {
+            write_product(&mut xml, &product)?;
+            report.products_written += 1;
+        } else {
+            report.rejected.push(RejectedProduct {
+                book_id: product.book_id,
+                problems,
+            });
+        }
+    }
+
+    xml.close("ONIXMessage")?;
+    xml.writer.flush().context("flushing onix")?;
+
+    Ok(report)
+}
+
+fn write_product<W: Write>(xml: &mut XmlWriter<W>, product: &OnixProduct) -> Result<()> {
+    xml.open("Product")?;
+    xml.leaf("RecordReference", &product.record_reference)?;
+    xml.leaf("NotificationType", CONFIRMED_NOTIFICATION)?;
+
+    for identifier in &product.identifiers {
+        xml.open("ProductIdentifier")?;
+        xml.leaf("ProductIDType", identifier.id_type)?;
+
+        if let Some(id_type_name) = &identifier.id_type_name {
+            xml.leaf("IDTypeName", id_type_name)?;
+        }
+
+        xml.leaf("IDValue", &identifier.value)?;
+        xml.close("ProductIdentifier")?;
+    }
+
+    xml.open("DescriptiveDetail")?;
+    xml.leaf("ProductComposition", "00")?;
+    xml.leaf("ProductForm", "BA")?;
+    xml.open("TitleDetail")?;
+    xml.leaf("TitleType", "01")?;
+    xml.open("TitleElement")?;
+    xml.leaf("TitleElementLevel", "01")?;
+    xml.leaf("TitleText", &product.title)?;
+    xml.close("TitleElement")?;
+    xml.close("TitleDetail")?;
+
+    for contributor in &product.contributors {
+        let name = &contributor.name;
+
+        xml.open("Contributor")?;
+        xml.leaf("SequenceNumber", &contributor.sequence_number.to_string())?;
+        xml.leaf("ContributorRole", contributor.role)?;
+        xml.leaf("PersonName", &contributor.display_name)?;
+        xml.leaf("PersonNameInverted", &name.inverted())?;
+
+        if let Some(given) = &name.given {
+            xml.leaf("NamesBeforeKey", given)?;
+        }
+
+        if let Some(particle) = &name.particle {
+            xml.leaf("PrefixToKey", particle)?;
+        }
+
+        xml.leaf("KeyNames", &name.family)?;
+
+        if let Some(suffix) = &name.suffix {
+            xml.leaf("SuffixToKey", suffix)?;
+        }
+
+        xml.close("Contributor")?;
+    }
+
+    if product.contributors.is_empty() {
+        xml.empty("NoContributor")?;
+    }
+
+    xml.close("DescriptiveDetail")?;
+
+    if let Some(publisher_name) = &product.publisher_name {
+        xml.open("PublishingDetail")?;
+        xml.open("Publisher")?;
+        xml.leaf("PublishingRole", "01")?;
+        xml.leaf("PublisherName", publisher_name)?;
+        xml.close("Publisher")?;
+        xml.close("PublishingDetail")?;
+    }
+
+    xml.close("Product")
+}
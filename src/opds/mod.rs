@@ -0,0 +1,423 @@
+#[cfg(feature = "server")]
+mod routes;
+
+#[cfg(feature = "server")]
+pub use routes::router;
+
+use chrono::{DateTime, Utc};
+use eyre::Result;
+
+use crate::{
+    connect::DbConnection,
+    models::{Book, BookIdentifier, BookWithAuthors, IdentifierScheme},
+    queries::{
+        book_author_queries::{
+            get_all_authors_and_books, get_author_with_books, get_authors_for_books,
+        },
+        book_identifier_queries::get_identifiers_for_books,
+        book_queries::{get_books_page, search_books_page},
+    },
+    validation::{validate_base_url, ValidationError},
+    xml::XmlWriter,
+};
+
+pub const NAVIGATION_TYPE: &str = "application/atom+xml;profile=opds-catalog;kind=navigation";
+pub const ACQUISITION_TYPE: &str = "application/atom+xml;profile=opds-catalog;kind=acquisition";
+pub const OPENSEARCH_TYPE: &str = "application/opensearchdescription+xml";
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+
+const ATOM_NAMESPACE: &str = "http://www.w3.org/2005/Atom";
+const DUBLIN_CORE_NAMESPACE: &str = "http://purl.org/dc/terms/";
+const OPDS_NAMESPACE: &str = "http://opds-spec.org/2010/catalog";
+const OPENSEARCH_NAMESPACE: &str = "http://a9.com/-/spec/opensearch/1.1/";
+const BUY_RELATION: &str = "http://opds-spec.org/acquisition/buy";
+
+/// Where the catalog is mounted and what it calls itself. Every href in the generated
+/// documents starts with `base_url`, which is absolute so that links still resolve when
+/// a reader fetches the OpenSearch description on its own.
+#[derive(Debug, Clone)]
+pub struct OpdsCatalog {
+    pub base_url: String,
+    pub title: String,
+    pub updated: DateTime<Utc>,
+}
+
+impl OpdsCatalog {
+    pub fn new(base_url: &str) -> Result<Self, ValidationError> {
+        Ok(Self {
+            base_url: validate_base_url("base_url", base_url)?,
+            title: "Bookstore".to_owned(),
+            updated: Utc::now(),
+        })
+    }
+
+    pub fn root_href(&self) -> String {
+        format!("{}/opds", self.base_url)
+    }
+
+    pub fn authors_href(&self) -> String {
+        format!("{}/opds/authors", self.base_url)
+    }
+
+    pub fn author_href(&self, author_id: i32) -> String {
+        format!("{}/opds/authors/{author_id}", self.base_url)
+    }
+
+    pub fn books_href(&self, after_id: Option<i32>) -> String {
+        match after_id {
+            Some(after_id) => format!("{}/opds/books?after={after_id}", self.base_url),
+            None => format!("{}/opds/books", self.base_url),
+        }
+    }
+
+    pub fn search_href(&self, terms: &str, after_id: Option<i32>) -> String {
+        let href = format!(
+            "{}/opds/search?q={}",
+            self.base_url,
+            encode_query_value(terms)
+        );
+
+        match after_id {
+            Some(after_id) => format!("{href}&after={after_id}"),
+            None => href,
+        }
+    }
+
+    pub fn opensearch_href(&self) -> String {
+        format!("{}/opds/opensearch.xml", self.base_url)
+    }
+
+    /// The book's JSON resource, which is what a reader buys from.
+    pub fn book_href(&self, book_id: i32) -> String {
+        format!("{}/books/{book_id}", self.base_url)
+    }
+}
+
+struct Feed {
+    id: String,
+    title: String,
+    kind: &'static str,
+    self_href: String,
+    up_href: Option<String>,
+    next_href: Option<String>,
+}
+
+/// The start document, linking to the author navigation and the full book list.
+pub fn root_feed(catalog: &OpdsCatalog) -> Result<String> {
+    let feed = Feed {
+        id: "urn:bookstore:opds".to_owned(),
+        title: catalog.title.clone(),
+        kind: NAVIGATION_TYPE,
+        self_href: catalog.root_href(),
+        up_href: None,
+        next_href: None,
+    };
+
+    write_feed(catalog, &feed, |xml| {
+        write_navigation_entry(
+            xml,
+            catalog,
+            "urn:bookstore:opds:authors",
+            "By author",
+            "Browse the catalog by author",
+            &catalog.authors_href(),
+            NAVIGATION_TYPE,
+        )?;
+        write_navigation_entry(
+            xml,
+            catalog,
+            "urn:bookstore:opds:books",
+            "All books",
+            "Every book in the catalog",
+            &catalog.books_href(None),
+            ACQUISITION_TYPE,
+        )
+    })
+}
+
+/// One navigation entry per author with at least one book, ordered by name.
+pub fn authors_feed(catalog: &OpdsCatalog, connection: &mut DbConnection) -> Result<String> {
+    let mut authors_with_books = get_all_authors_and_books(connection)?;
+    let feed = Feed {
+        id: "urn:bookstore:opds:authors".to_owned(),
+        title: format!("{} by author", catalog.title),
+        kind: NAVIGATION_TYPE,
+        self_href: catalog.authors_href(),
+        up_href: Some(catalog.root_href()),
+        next_href: None,
+    };
+
+    authors_with_books.retain(|author_with_books| !author_with_books.books().is_empty());
+    authors_with_books.sort_by_key(|author_with_books| {
+        let author = author_with_books.author();
+
+        (author.name.to_lowercase(), author.id)
+    });
+
+    write_feed(catalog, &feed, |xml| {
+        for author_with_books in &authors_with_books {
+            let author = author_with_books.author();
+            let count = author_with_books.books().len();
+
+            write_navigation_entry(
+                xml,
+                catalog,
+                &format!("urn:bookstore:author:{}", author.id),
+                &author.name,
+                &format!("{count} {}", if count == 1 { "book" } else { "books" }),
+                &catalog.author_href(author.id),
+                ACQUISITION_TYPE,
+            )?;
+        }
+
+        Ok(())
+    })
+}
+
+/// `None` when the author does not exist.
+pub fn author_books_feed(
+    catalog: &OpdsCatalog,
+    author_id: i32,
+    connection: &mut DbConnection,
+) -> Result<Option<String>> {
+    let Some(author_with_books) = get_author_with_books(author_id, connection)? else {
+        return Ok(None);
+    };
+    let (author, mut books) = author_with_books.into_parts();
+
+    books.sort_by_key(|book| book.id);
+
+    let identifiers = get_identifiers_for_books(&books, connection)?;
+    let books_with_authors = get_authors_for_books(books, connection)?;
+    let feed = Feed {
+        id: format!("urn:bookstore:author:{}:books", author.id),
+        title: author.name,
+        kind: ACQUISITION_TYPE,
+        self_href: catalog.author_href(author.id),
+        up_href: Some(catalog.authors_href()),
+        next_href: None,
+    };
+
+    write_book_feed(catalog, &feed, &books_with_authors, &identifiers).map(Some)
+}
+
+/// A page of books in id order; the feed links to the next page when there is one.
+pub fn books_feed(
+    catalog: &OpdsCatalog,
+    after_id: Option<i32>,
+    page_size: i64,
+    connection: &mut DbConnection,
+) -> Result<String> {
+    let (books_with_authors, identifiers, next_after_id) =
+        load_page(page_size, connection, |limit, connection| {
+            get_books_page(after_id, limit, connection)
+        })?;
+    let feed = Feed {
+        id: "urn:bookstore:opds:books".to_owned(),
+        title: format!("All {} books", catalog.title),
+        kind: ACQUISITION_TYPE,
+        self_href: catalog.books_href(after_id),
+        up_href: Some(catalog.root_href()),
+        next_href: next_after_id.map(|next_after_id| catalog.books_href(Some(next_after_id))),
+    };
+
+    write_book_feed(catalog, &feed, &books_with_authors, &identifiers)
+}
+
+/// A page of books matching `terms` by title or author name, as the OpenSearch template
+/// returns them.
+pub fn search_feed(
+    catalog: &OpdsCatalog,
+    terms: &str,
+    after_id: Option<i32>,
+    page_size: i64,
+    connection: &mut DbConnection,
+) -> Result<String> {
+    let (books_with_authors, identifiers, next_after_id) =
+        load_page(page_size, connection, |limit, connection| {
+            search_books_page(terms, after_id, limit, connection)
+        })?;
+    let feed = Feed {
+        id: format!("urn:bookstore:opds:search:{}", encode_query_value(terms)),
+        title: format!("Search results for \"{terms}\""),
+        kind: ACQUISITION_TYPE,
+        self_href: catalog.search_href(terms, after_id),
+        up_href: Some(catalog.root_href()),
+        next_href: next_after_id
+            .map(|next_after_id| catalog.search_href(terms, Some(next_after_id))),
+    };
+
+    write_book_feed(catalog, &feed, &books_with_authors, &identifiers)
+}
+
+pub fn opensearch_description(catalog: &OpdsCatalog) -> Result<String> {
+    let mut output = Vec::new();
+    let mut xml = XmlWriter::new(&mut output)?;
+    let template = format!("{}/opds/search?q={{searchTerms}}", catalog.base_url);
+
+    xml.open_with("OpenSearchDescription", &[("xmlns", OPENSEARCH_NAMESPACE)])?;
+    xml.leaf("ShortName", &catalog.title)?;
+    xml.leaf(
+        "Description",
+        &format!("Search {} by title or author", catalog.title),
+    )?;
+    xml.leaf("InputEncoding", "UTF-8")?;
+    xml.leaf("OutputEncoding", "UTF-8")?;
+    xml.empty_with(
+        "Url",
+        &[("type", ACQUISITION_TYPE), ("template", &template)],
+    )?;
+    xml.close("OpenSearchDescription")?;
+
+    Ok(String::from_utf8(output)?)
+}
+
+type Page = (Vec<BookWithAuthors>, Vec<Vec<BookIdentifier>>, Option<i32>);
+
+/// Fetches one row more than the page holds to find out whether a next page exists.
+fn load_page(
+    page_size: i64,
+    connection: &mut DbConnection,
+    fetch: impl FnOnce(i64, &mut DbConnection) -> Result<Vec<Book>>,
+) -> Result<Page> {
+    let page_size = page_size.max(1);
+    let mut books = fetch(page_size + 1, connection)?;
+    let next_after_id = if books.len() as i64 > page_size {
+        books.truncate(page_size as usize);
+        books.last().map(|book| book.id)
+    } else {
+        None
+    };
+    let identifiers = get_identifiers_for_books(&books, connection)?;
+    let books_with_authors = get_authors_for_books(books, connection)?;
+
+    Ok((books_with_authors, identifiers, next_after_id))
+}
+
+fn write_feed(
+    catalog: &OpdsCatalog,
+    feed: &Feed,
+    write_entries: impl FnOnce(&mut XmlWriter<&mut Vec<u8>>) -> Result<()>,
+) -> Result<String> {
+    let mut output = Vec::new();
+    let mut xml = XmlWriter::new(&mut output)?;
+
+    xml.open_with(
+        "feed",
+        &[
+            ("xmlns", ATOM_NAMESPACE),
+            ("xmlns:dc", DUBLIN_CORE_NAMESPACE),
+            ("xmlns:opds", OPDS_NAMESPACE),
+        ],
+    )?;
+    xml.leaf("id", &feed.id)?;
+    xml.leaf("title", &feed.title)?;
+    xml.leaf("updated", &atom_date(catalog.updated))?;
+    xml.open("author")?;
+    xml.leaf("name", &catalog.title)?;
+    xml.leaf("uri", &catalog.root_href())?;
+    xml.close("author")?;
+    link(&mut xml, "self", &feed.self_href, feed.kind)?;
+    link(&mut xml, "start", &catalog.root_href(), NAVIGATION_TYPE)?;
+
+    if let Some(up_href) = &feed.up_href {
+        link(&mut xml, "up", up_href, NAVIGATION_TYPE)?;
+    }
+
+    link(
+        &mut xml,
+        "search",
+        &catalog.opensearch_href(),
+        OPENSEARCH_TYPE,
+    )?;
+
+    if let Some(next_href) = &feed.next_href {
+        link(&mut xml, "next", next_href, feed.kind)?;
+    }
+
+    write_entries(&mut xml)?;
+    xml.close("feed")?;
+
+    Ok(String::from_utf8(output)?)
+}
+
+fn write_navigation_entry(
+    xml: &mut XmlWriter<&mut Vec<u8>>,
+    catalog: &OpdsCatalog,
+    id: &str,
+    title: &str,
+    content: &str,
+    href: &str,
+    kind: &str,
+) -> Result<()> {
+    xml.open("entry")?;
+    xml.leaf("title", title)?;
+    xml.leaf("id", id)?;
+    xml.leaf("updated", &atom_date(catalog.updated))?;
+    xml.leaf_with("content", &[("type", "text")], content)?;
+    link(xml, "subsection", href, kind)?;
+    xml.close("entry")
+}
+
+/// The catalog holds no files, so each book's acquisition link is a buy link to its JSON
+/// resource.
+fn write_book_feed(
+    catalog: &OpdsCatalog,
+    feed: &Feed,
+    books_with_authors: &[BookWithAuthors],
+    identifiers: &[Vec<BookIdentifier>],
+) -> Result<String> {
+    write_feed(catalog, feed, |xml| {
+        for (book_with_authors, identifiers) in books_with_authors.iter().zip(identifiers) {
+            let book = book_with_authors.book();
+
+            xml.open("entry")?;
+            xml.leaf("title", &book.name)?;
+            xml.leaf("id", &format!("urn:bookstore:book:{}", book.id))?;
+            xml.leaf("updated", &atom_date(catalog.updated))?;
+
+            for author in book_with_authors.authors() {
+                xml.open("author")?;
+                xml.leaf("name", &author.name)?;
+                xml.leaf("uri", &catalog.author_href(author.id))?;
+                xml.close("author")?;
+            }
+
+            for identifier in identifiers {
+                if identifier.scheme == IdentifierScheme::Isbn.as_str() {
+                    xml.leaf("dc:identifier", &format!("urn:isbn:{}", identifier.value))?;
+                }
+            }
+
+            link(
+                xml,
+                BUY_RELATION,
+                &catalog.book_href(book.id),
+                "application/json",
+            )?;
+            xml.close("entry")?;
+        }
+
+        Ok(())
+    })
+}
+
+fn link(xml: &mut XmlWriter<&mut Vec<u8>>, rel: &str, href: &str, kind: &str) -> Result<()> {
+    xml.empty_with("link", &[("rel", rel), ("href", href), ("type", kind)])
+}
+
+fn atom_date(date: DateTime<Utc>) -> String {
+    date.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+fn encode_query_value(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
@@ -0,0 +1,129 @@
+use super::{
+    author_books_feed, authors_feed, books_feed, opensearch_description, root_feed, search_feed,
+    OpdsCatalog, ACQUISITION_TYPE, DEFAULT_PAGE_SIZE, NAVIGATION_TYPE, OPENSEARCH_TYPE,
+};
+use crate::{
+    api::{run_query, ApiError},
+    connect::DbPool,
+};
+use axum::{
+    extract::{Path, Query, State},
+    http::header,
+    routing::get,
+    Router,
+};
+use chrono::Utc;
+use serde::Deserialize;
+
+type Document = ([(header::HeaderName, &'static str); 1], String);
+
+#[derive(Clone)]
+struct OpdsState {
+    pool: DbPool,
+    catalog: OpdsCatalog,
+}
+
+impl OpdsState {
+    /// The catalog as of this request.
+    fn catalog(&self) -> OpdsCatalog {
+        OpdsCatalog {
+            updated: Utc::now(),
+            ..self.catalog.clone()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct PageQuery {
+    after: Option<i32>,
+}
+
+#[derive(Deserialize)]
+struct SearchQuery {
+    q: String,
+    after: Option<i32>,
+}
+
+/// Serves `catalog` under `/opds`. Its links start with the catalog's `base_url`, which
+/// should be where this router is reachable.
+pub fn router(pool: DbPool, catalog: OpdsCatalog) -> Router {
+    Router::new()
+        .route("/opds", get(root))
+        .route("/opds/authors", get(authors))
+        .route("/opds/authors/{id}", get(author_books))
+        .route("/opds/books", get(books))
+        .route("/opds/search", get(search))
+        .route("/opds/opensearch.xml", get(opensearch))
+        .with_state(OpdsState { pool, catalog })
+}
+
+async fn root(State(state): State<OpdsState>) -> Result<Document, ApiError> {
+    Ok(document(NAVIGATION_TYPE, root_feed(&state.catalog())?))
+}
+
+async fn authors(State(state): State<OpdsState>) -> Result<Document, ApiError> {
+    let catalog = state.catalog();
+    let feed = run_query(&state.pool, move |connection| {
+        authors_feed(&catalog, connection)
+    })
+    .await?;
+
+    Ok(document(NAVIGATION_TYPE, feed))
+}
+
+async fn author_books(
+    State(state): State<OpdsState>,
+    Path(id): Path<i32>,
+) -> Result<Document, ApiError> {
+    let catalog = state.catalog();
+    let feed = run_query(&state.pool, move |connection| {
+        author_books_feed(&catalog, id, connection)
+    })
+    .await?
+    .ok_or_else(|| ApiError::NotFound(format!("author {id} not found")))?;
+
+    Ok(document(ACQUISITION_TYPE, feed))
+}
+
+async fn books(
+    State(state): State<OpdsState>,
+    Query(query): Query<PageQuery>,
+) -> Result<Document, ApiError> {
+    let catalog = state.catalog();
+    let feed = run_query(&state.pool, move |connection| {
+        books_feed(&catalog, query.after, DEFAULT_PAGE_SIZE, connection)
+    })
+    .await?;
+
+    Ok(document(ACQUISITION_TYPE, feed))
+}
+
+async fn search(
+    State(state): State<OpdsState>,
+    Query(query): Query<SearchQuery>,
+) -> Result<Document, ApiError> {
+    let catalog = state.catalog();
+    let feed = run_query(&state.pool, move |connection| {
+        search_feed(
+            &catalog,
+            &query.q,
+            query.after,
+            DEFAULT_PAGE_SIZE,
+            connection,
+        )
+    })
+    .await?;
+
+    Ok(document(ACQUISITION_TYPE, feed))
+}
+
+async fn opensearch(State(state): State<OpdsState>) -> Result<Document, ApiError> {
+    Ok(document(
+        OPENSEARCH_TYPE,
+        opensearch_description(&state.catalog())?,
+    ))
+}
+
+fn document(content_type: &'static str, body: String) -> Document {
+    ([(header::CONTENT_TYPE, content_type)], body)
+}
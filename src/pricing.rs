@@ -0,0 +1,254 @@
+use std::{collections::HashMap, io::Read};
+
+use chrono::NaiveDateTime;
+use csv::ReaderBuilder;
+use eyre::{bail, Result};
+
+use crate::{
+    connect::DbConnection,
+    import::{ImportError, RowError},
+    instrumentation::query_span,
+    queries::{
+        book_queries::get_books_by_ids,
+        price_queries::{current_price, set_price},
+    },
+    validation::validate_currency,
+};
+
+const PRICES: &str = "prices";
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PriceUpdateReport {
+    pub changes: Vec<PriceChange>,
+    pub unchanged: usize,
+    pub dry_run: bool,
+}
+
+/// One line of the preview diff. `old_amount` is `None` when the book had no price in
+/// that currency.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PriceChange {
+    pub book_id: i32,
+    pub book_name: String,
+    pub currency: String,
+    pub old_amount: Option<i64>,
+    pub new_amount: i64,
+}
+
+struct PriceRow {
+    line: u64,
+    book_id: i32,
+    currency: String,
+    amount_minor: i64,
+}
+
+/// Digits after the decimal point in the currency's minor unit.
+pub fn minor_unit_digits(currency: &str) -> u32 {
+    match currency {
+        "BIF" | "CLP" | "DJF" | "GNF" | "ISK" | "JPY" | "KMF" | "KRW" | "PYG" | "RWF" | "UGX"
+        | "UYI" | "VND" | "VUV" | "XAF" | "XOF" | "XPF" => 0,
+        "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 3,
+        _ => 2,
+    }
+}
+
+/// Turns "12.5" into 1250 for a two-digit currency. Amounts with more decimals than the
+/// currency has are refused rather than rounded.
+pub fn parse_amount(value: &str, currency: &str) -> Result<i64, String> {
+    let digits = minor_unit_digits(currency);
+    let invalid = || format!("price `{value}` is not a valid {currency} amount");
+    let (whole, fraction) = value.trim().split_once('.').unwrap_or((value.trim(), ""));
+
+    if whole.is_empty()
+        || !whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    if fraction.len() as u32 > digits {
+        return Err(format!(
+            "price `{value}` has more than {digits} decimal places for {currency}"
+        ));
+    }
+
+    let padded = format!("{whole}{fraction:0<width$}", width = digits as usize);
+
+    padded.parse().map_err(|_| invalid())
+}
+
+pub fn format_amount(amount_minor: i64, currency: &str) -> String {
+    let digits = minor_unit_digits(currency);
+
+    if digits == 0 {
+        return format!("{amount_minor} {currency}");
+    }
+
+    let scale = 10_i64.pow(digits);
+
+    format!(
+        "{}.{:0width$} {currency}",
+        amount_minor / scale,
+        amount_minor % scale,
+        width = digits as usize
+    )
+}
+
+/// Reads `book_id,currency,price` rows, with prices in major units such as `12.99`, and
+/// compares each one with the price in effect at `effective_from`. Unless `dry_run` is set,
+/// the changed prices are saved in one transaction starting at `effective_from`.
+#[query_span(skip(reader, connection))]
+pub fn update_prices_csv<R: Read>(
+    reader: R,
+    effective_from: NaiveDateTime,
+    dry_run: bool,
+    connection: &mut DbConnection,
+) -> Result<PriceUpdateReport> {
+    use diesel::Connection;
+
+    let mut errors = Vec::new();
+    let rows = read_price_rows(reader, &mut errors)?;
+
+    if !errors.is_empty() {
+        bail!(ImportError { errors });
+    }
+
+    connection.transaction(|connection| {
+        let book_ids = rows.iter().map(|row| row.book_id).collect::<Vec<i32>>();
+        let book_names = get_books_by_ids(&book_ids, connection)?
+            .into_iter()
+            .map(|book| (book.id, book.name))
+            .collect::<HashMap<i32, String>>();
+        let mut report = PriceUpdateReport {
+            dry_run,
+            ..PriceUpdateReport::default()
+        };
+
+        for row in rows {
+            let Some(book_name) = book_names.get(&row.book_id) else {
+                errors.push(row_error(
+                    row.line,
+                    format!("book {} does not exist", row.book_id),
+                ));
+                continue;
+            };
+            let old_amount = current_price(row.book_id, &row.currency, effective_from, connection)?
+                .map(|price| price.amount_minor);
+
+            if old_amount == Some(row.amount_minor) {
+                report.unchanged += 1;
+                continue;
+            }
+
+            report.changes.push(PriceChange {
+                book_id: row.book_id,
+                book_name: book_name.clone(),
+                currency: row.currency,
+                old_amount,
+                new_amount: row.amount_minor,
+            });
+        }
+
+        if !errors.is_empty() {
+            bail!(ImportError {
+                errors: std::mem::take(&mut errors),
+            });
+        }
+
+        if !dry_run {
+            for change in &report.changes {
+                set_price(
+                    change.book_id,
+                    &change.currency,
+                    change.new_amount,
+                    effective_from,
+                    connection,
+                )?;
+            }
+        }
+
+        Ok(report)
+    })
+}
+
+fn read_price_rows(reader: impl Read, errors: &mut Vec<RowError>) -> Result<Vec<PriceRow>> {
+    let mut reader = ReaderBuilder::new().from_reader(reader);
+    let headers = reader.headers()?.clone();
+    let column = |name: &str| headers.iter().position(|header| header.trim() == name);
+    let (Some(book_index), Some(currency_index), Some(price_index)) =
+        (column("book_id"), column("currency"), column("price"))
+    else {
+        errors.push(row_error(
+            1,
+            "expected `book_id`, `currency` and `price` columns",
+        ));
+        return Ok(Vec::new());
+    };
+    let mut rows = Vec::new();
+    let mut seen = HashMap::new();
+
+    for record in reader.records() {
+        let record = match record {
+            Ok(record) => record,
+            Err(error) => {
+                let line = error.position().map_or(0, |position| position.line());
+                errors.push(row_error(line, error.to_string()));
+                continue;
+            }
+        };
+        let line = record.position().map_or(0, |position| position.line());
+        let mut messages = Vec::new();
+        let book_id = record[book_index]
+            .trim()
+            .parse::<i32>()
+            .map_err(|_| {
+                messages.push(format!(
+                    "book_id `{}` is not a valid id",
+                    &record[book_index]
+                ))
+            })
+            .ok();
+        let currency = validate_currency("currency", &record[currency_index])
+            .map_err(|error| messages.push(error.to_string()))
+            .ok();
+        let amount_minor = currency.as_deref().and_then(|currency| {
+            parse_amount(&record[price_index], currency)
+                .map_err(|message| messages.push(message))
+                .ok()
+        });
+        let (Some(book_id), Some(currency), Some(amount_minor)) = (book_id, currency, amount_minor)
+        else {
+            errors.extend(messages.into_iter().map(|message| row_error(line, message)));
+            continue;
+        };
+
+        if let Some(first_line) = seen.insert((book_id, currency.clone()), line) {
+            errors.push(row_error(
+                line,
+                format!("book {book_id} is already priced in {currency} on line {first_line}"),
+            ));
+            continue;
+        }
+
+        rows.push(PriceRow {
+            line,
+            book_id,
+            currency,
+            amount_minor,
+        });
+    }
+
+    Ok(rows)
+}
+
+fn row_error(line: u64, message: impl Into<String>) -> RowError {
+    RowError {
+        file: PRICES,
+        line,
+        message: message.into(),
+    }
+}
@@ -0,0 +1,124 @@
+use diesel::{associations::HasTable, prelude::*, BelongingToDsl};
+use eyre::{Context, Result};
+
+use crate::{
+    connect::DbConnection,
+    instrumentation::{query_span, record_rows},
+    models::{Author, AuthorIdentifier, AuthorIdentifierScheme, NewAuthorIdentifier},
+    schema,
+    validation::{
+        validate_isni, validate_orcid, validate_viaf, validate_wikidata_qid, ValidationError,
+    },
+};
+
+fn normalize_identifier(
+    scheme: AuthorIdentifierScheme,
+    value: &str,
+) -> Result<String, ValidationError> {
+    match scheme {
+        AuthorIdentifierScheme::Orcid => validate_orcid("orcid", value),
+        AuthorIdentifierScheme::Isni => validate_isni("isni", value),
+        AuthorIdentifierScheme::Viaf => validate_viaf("viaf", value),
+        AuthorIdentifierScheme::Wikidata => validate_wikidata_qid("wikidata", value),
+    }
+}
+
+#[query_span]
+pub fn add_author_identifier(
+    author_id: i32,
+    scheme: AuthorIdentifierScheme,
+    value: &str,
+    connection: &mut DbConnection,
+) -> Result<String> {
+    let value = normalize_identifier(scheme, value)?;
+    let new_identifier = NewAuthorIdentifier {
+        author_id,
+        scheme: scheme.as_str(),
+        value: &value,
+    };
+
+    let inserted_rows = new_identifier
+        .insert_into(AuthorIdentifier::table())
+        .execute(connection)
+        .context("adding author identifier")?;
+
+    record_rows(inserted_rows);
+
+    Ok(value)
+}
+
+#[query_span]
+pub fn remove_author_identifier(
+    scheme: AuthorIdentifierScheme,
+    value: &str,
+    connection: &mut DbConnection,
+) -> Result<bool> {
+    let value = normalize_identifier(scheme, value)?;
+    let deleted_rows = diesel::delete(AuthorIdentifier::table().find((scheme.as_str(), value)))
+        .execute(connection)
+        .context("removing author identifier")?;
+
+    record_rows(deleted_rows);
+
+    Ok(deleted_rows > 0)
+}
+
+#[query_span]
+pub fn get_author_by_identifier(
+    scheme: AuthorIdentifierScheme,
+    value: &str,
+    connection: &mut DbConnection,
+) -> Result<Option<Author>> {
+    use schema::author_identifiers::dsl;
+
+    let value = normalize_identifier(scheme, value)?;
+    let author = AuthorIdentifier::table()
+        .inner_join(Author::table())
+        .filter(dsl::scheme.eq(scheme.as_str()))
+        .filter(dsl::value.eq(value))
+        .select(Author::as_select())
+        .first(connection)
+        .optional()
+        .context("getting author by identifier")?;
+
+    record_rows(author.iter().count());
+
+    Ok(author)
+}
+
+#[query_span]
+pub fn get_identifiers_for_author(
+    author_id: i32,
+    connection: &mut DbConnection,
+) -> Result<Vec<AuthorIdentifier>> {
+    use schema::author_identifiers::dsl;
+
+    let identifiers = AuthorIdentifier::table()
+        .filter(dsl::author_id.eq(author_id))
+        .order((dsl::scheme, dsl::value))
+        .select(AuthorIdentifier::as_select())
+        .load(connection)
+        .context("getting identifiers for author")?;
+
+    record_rows(identifiers.len());
+
+    Ok(identifiers)
+}
+
+#[query_span(skip_all)]
+pub fn get_identifiers_for_authors(
+    authors: &[Author],
+    connection: &mut DbConnection,
+) -> Result<Vec<Vec<AuthorIdentifier>>> {
+    use schema::author_identifiers::dsl;
+
+    let identifiers = AuthorIdentifier::belonging_to(authors)
+        .order((dsl::scheme, dsl::value))
+        .select(AuthorIdentifier::as_select())
+        .load(connection)
+        .context("getting identifiers for authors")?;
+
+    record_rows(identifiers.len());
+
+    Ok(identifiers.grouped_by(authors))
+}
@@ -0,0 +1,296 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+};
+
+use diesel::{associations::HasTable, prelude::*};
+use eyre::{Context, Result};
+
+use crate::{
+    connect::{unicode_lower, DbConnection},
+    instrumentation::{query_span, record_rows},
+    models::{Author, AuthorName, AuthorNameKind, BookAuthorName, BookWithAuthors, NewAuthorName},
+    queries::{
+        author_queries::get_author_by_id,
+        book_author_queries::{book_author_exists, get_book_with_authors},
+    },
+    schema,
+    validation::{normalize, validate_name},
+};
+
+/// Returned (inside the `eyre::Report`) when an author name cannot be added or used. Nothing
+/// is changed when this happens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthorNameError {
+    AuthorNotFound { author_id: i32 },
+    NameNotFound { author_name_id: i32 },
+    DuplicateName { author_id: i32, author_name_id: i32 },
+    NotCredited { book_id: i32, author_id: i32 },
+}
+
+impl Display for AuthorNameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AuthorNotFound { author_id } => write!(f, "author {author_id} not found"),
+            Self::NameNotFound { author_name_id } => {
+                write!(f, "author name {author_name_id} not found")
+            }
+            Self::DuplicateName {
+                author_id,
+                author_name_id,
+            } => write!(
+                f,
+                "author {author_id} already has this name as author name {author_name_id}"
+            ),
+            Self::NotCredited { book_id, author_id } => {
+                write!(f, "author {author_id} is not credited on book {book_id}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AuthorNameError {}
+
+/// Adds a variant spelling or pseudonym to an author. Names are compared ignoring case, so an
+/// author has each name at most once.
+#[query_span]
+pub fn add_author_name(
+    author_id: i32,
+    name: &str,
+    kind: AuthorNameKind,
+    connection: &mut DbConnection,
+) -> Result<i32> {
+    use schema::author_names::dsl::{
+        author_id as name_author_id, author_names, id, name as name_text,
+    };
+
+    let name = validate_name("name", name)?;
+
+    connection.transaction(|connection| {
+        if get_author_by_id(author_id, connection)?.is_none() {
+            return Err(AuthorNameError::AuthorNotFound { author_id }.into());
+        }
+
+        let existing = author_names
+            .filter(name_author_id.eq(author_id))
+            .filter(unicode_lower(name_text).eq(unicode_lower(&name)))
+            .select(id)
+            .first::<i32>(connection)
+            .optional()
+            .context("checking existing author names")?;
+
+        if let Some(author_name_id) = existing {
+            return Err(AuthorNameError::DuplicateName {
+                author_id,
+                author_name_id,
+            }
+            .into());
+        }
+
+        let author_name_id = NewAuthorName {
+            author_id,
+            name: &name,
+            kind: kind.as_str(),
+        }
+        .insert_into(AuthorName::table())
+        .returning(id)
+        .get_result(connection)
+        .context("adding author name")?;
+
+        record_rows(1);
+
+        Ok(author_name_id)
+    })
+}
+
+#[query_span]
+pub fn get_author_name_by_id(id: i32, connection: &mut DbConnection) -> Result<Option<AuthorName>> {
+    let author_name = AuthorName::table()
+        .find(id)
+        .select(AuthorName::as_select())
+        .first(connection)
+        .optional()
+        .context("getting author name by id")?;
+
+    record_rows(author_name.iter().count());
+
+    Ok(author_name)
+}
+
+#[query_span]
+pub fn get_author_names(author_id: i32, connection: &mut DbConnection) -> Result<Vec<AuthorName>> {
+    use schema::author_names::dsl::{author_id as name_author_id, author_names, id};
+
+    let names = author_names
+        .filter(name_author_id.eq(author_id))
+        .order(id)
+        .select(AuthorName::as_select())
+        .load(connection)
+        .context("getting author names")?;
+
+    record_rows(names.len());
+
+    Ok(names)
+}
+
+/// Removes a name. Books published under it go back to showing the author's own name.
+#[query_span]
+pub fn remove_author_name(id: i32, connection: &mut DbConnection) -> Result<bool> {
+    let deleted_rows = diesel::delete(schema::author_names::table.find(id))
+        .execute(connection)
+        .context("removing author name")?;
+
+    record_rows(deleted_rows);
+
+    Ok(deleted_rows > 0)
+}
+
+/// The authors known by `name`, whether it is their own name, a variant or a pseudonym,
+/// ignoring case. More than one author can share a name.
+#[query_span]
+pub fn resolve_author_name(name: &str, connection: &mut DbConnection) -> Result<Vec<Author>> {
+    use schema::{author_names, authors};
+
+    let name = normalize(name);
+    let named_authors = author_names::table
+        .filter(unicode_lower(author_names::name).eq(unicode_lower(name.clone())))
+        .select(author_names::author_id);
+    let found_authors = authors::table
+        .filter(
+            unicode_lower(authors::name)
+                .eq(unicode_lower(name))
+                .or(authors::id.eq_any(named_authors)),
+        )
+        .order(authors::id)
+        .select(Author::as_select())
+        .load(connection)
+        .context("resolving author name")?;
+
+    record_rows(found_authors.len());
+
+    Ok(found_authors)
+}
+
+/// Records that the book was published under one of its author's other names. The author
+/// has to be credited on the book.
+#[query_span]
+pub fn set_published_name(
+    book_id: i32,
+    author_name_id: i32,
+    connection: &mut DbConnection,
+) -> Result<()> {
+    use schema::book_author_names::dsl::{author_name_id as published_name_id, book_author_names};
+
+    connection.transaction(|connection| {
+        let Some(author_name) = get_author_name_by_id(author_name_id, connection)? else {
+            return Err(AuthorNameError::NameNotFound { author_name_id }.into());
+        };
+        let author_id = author_name.author_id;
+
+        if !book_author_exists(book_id, author_id, connection)? {
+            return Err(AuthorNameError::NotCredited { book_id, author_id }.into());
+        }
+
+        let updated_rows = diesel::update(book_author_names.find((book_id, author_id)))
+            .set(published_name_id.eq(author_name_id))
+            .execute(connection)
+            .context("changing published name")?;
+
+        if updated_rows == 0 {
+            BookAuthorName {
+                book_id,
+                author_id,
+                author_name_id,
+            }
+            .insert_into(BookAuthorName::table())
+            .execute(connection)
+            .context("setting published name")?;
+        }
+
+        record_rows(1);
+
+        Ok(())
+    })
+}
+
+/// Goes back to showing the author's own name on the book.
+#[query_span]
+pub fn clear_published_name(
+    book_id: i32,
+    author_id: i32,
+    connection: &mut DbConnection,
+) -> Result<bool> {
+    let deleted_rows = diesel::delete(BookAuthorName::table().find((book_id, author_id)))
+        .execute(connection)
+        .context("clearing published name")?;
+
+    record_rows(deleted_rows);
+
+    Ok(deleted_rows > 0)
+}
+
+/// Shows each book under the names it was published with: an author with a published name
+/// on the book gets that name instead of their own. Ids are kept, so every author still
+/// leads to the canonical record.
+#[query_span(skip_all)]
+pub fn with_published_names(
+    books_with_authors: Vec<BookWithAuthors>,
+    connection: &mut DbConnection,
+) -> Result<Vec<BookWithAuthors>> {
+    use schema::{author_names, book_author_names};
+
+    let book_ids = books_with_authors
+        .iter()
+        .map(|book_with_authors| book_with_authors.book().id)
+        .collect::<Vec<i32>>();
+    let published: Vec<(i32, i32, String)> = book_author_names::table
+        .inner_join(author_names::table)
+        .filter(book_author_names::book_id.eq_any(&book_ids))
+        .select((
+            book_author_names::book_id,
+            book_author_names::author_id,
+            author_names::name,
+        ))
+        .load(connection)
+        .context("getting published names")?;
+
+    record_rows(published.len());
+
+    let mut published_names = published
+        .into_iter()
+        .map(|(book_id, author_id, name)| ((book_id, author_id), name))
+        .collect::<HashMap<(i32, i32), String>>();
+
+    Ok(books_with_authors
+        .into_iter()
+        .map(|book_with_authors| {
+            let book_id = book_with_authors.book().id;
+            let authors = book_with_authors
+                .authors()
+                .iter()
+                .map(|author| Author {
+                    id: author.id,
+                    name: published_names
+                        .remove(&(book_id, author.id))
+                        .unwrap_or_else(|| author.name.clone()),
+                })
+                .collect();
+
+            book_with_authors.with_authors(authors)
+        })
+        .collect())
+}
+
+/// `get_book_with_authors` with the authors shown under the names the book was published
+/// with.
+#[query_span]
+pub fn get_book_with_published_names(
+    book_id: i32,
+    connection: &mut DbConnection,
+) -> Result<Option<BookWithAuthors>> {
+    let Some(book_with_authors) = get_book_with_authors(book_id, connection)? else {
+        return Ok(None);
+    };
+
+    Ok(with_published_names(vec![book_with_authors], connection)?.pop())
+}
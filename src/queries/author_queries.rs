@@ -1,58 +1,216 @@
-use crate::models::{Author, NewAuthor};
+use crate::connect::DbConnection;
+use crate::instrumentation::{query_span, record_rows};
+use crate::models::{Author, AuthorDetails, AuthorProfile, NewAuthor, PartialDate};
 use crate::schema;
+use crate::validation::{
+    normalize, validate_biography, validate_country_code, validate_name, validate_partial_date,
+    ValidationError, ValidationReason,
+};
 use diesel::{associations::HasTable, prelude::*};
 use eyre::{Context, Result};
 
-pub fn create_author(name: &str, connection: &mut PgConnection) -> Result<i32> {
+#[query_span]
+pub fn create_author(name: &str, connection: &mut DbConnection) -> Result<i32> {
     use schema::authors::dsl::id;
 
     let new_author = NewAuthor {
-        name: name.to_owned(),
+        name: validate_name("name", name)?,
     };
 
-    new_author
+    let created_id = new_author
         .insert_into(Author::table())
         .returning(id)
         .get_result(connection)
-        .context("creating author")
+        .context("creating author")?;
+
+    record_rows(1);
+
+    Ok(created_id)
 }
 
-pub fn get_all_authors(connection: &mut PgConnection) -> Result<Vec<Author>> {
+#[query_span]
+pub fn get_all_authors(connection: &mut DbConnection) -> Result<Vec<Author>> {
     use schema::authors::dsl::authors;
 
-    authors
+    let all_authors = authors
+        .select(Author::as_select())
+        .load(connection)
+        .context("getting all authors")?;
+
+    record_rows(all_authors.len());
+
+    Ok(all_authors)
+}
+
+#[query_span]
+pub fn get_authors_page(
+    after_id: Option<i32>,
+    limit: i64,
+    connection: &mut DbConnection,
+) -> Result<Vec<Author>> {
+    use schema::authors::dsl::{authors, id};
+
+    let page = authors
+        .filter(id.gt(after_id.unwrap_or(i32::MIN)))
+        .order(id)
+        .limit(limit)
         .select(Author::as_select())
         .load(connection)
-        .context("getting all authors")
+        .context("getting page of authors")?;
+
+    record_rows(page.len());
+
+    Ok(page)
 }
 
-pub fn get_author_by_id(id: i32, connection: &mut PgConnection) -> Result<Option<Author>> {
+#[query_span]
+pub fn get_author_by_id(id: i32, connection: &mut DbConnection) -> Result<Option<Author>> {
     use schema::authors::dsl::authors;
 
-    authors
+    let author = authors
         .find(id)
+        .select(Author::as_select())
         .get_result(connection)
         .optional()
-        .context("getting author by id")
+        .context("getting author by id")?;
+
+    record_rows(author.iter().count());
+
+    Ok(author)
+}
+
+#[query_span]
+pub fn get_authors_by_ids(ids: &[i32], connection: &mut DbConnection) -> Result<Vec<Author>> {
+    use schema::authors::dsl::{authors, id};
+
+    let found_authors = authors
+        .filter(id.eq_any(ids))
+        .select(Author::as_select())
+        .load(connection)
+        .context("getting authors by ids")?;
+
+    record_rows(found_authors.len());
+
+    Ok(found_authors)
+}
+
+#[query_span]
+pub fn get_authors_by_name(name: &str, connection: &mut DbConnection) -> Result<Vec<Author>> {
+    use schema::authors::dsl::{authors, name as author_name};
+
+    let found_authors = authors
+        .filter(author_name.eq(normalize(name)))
+        .select(Author::as_select())
+        .load(connection)
+        .context("getting authors by name")?;
+
+    record_rows(found_authors.len());
+
+    Ok(found_authors)
 }
 
-pub fn update_author(id: i32, new_name: &str, connection: &mut PgConnection) -> Result<()> {
+#[query_span]
+pub fn update_author(id: i32, new_name: &str, connection: &mut DbConnection) -> Result<()> {
     use schema::authors::dsl::{authors, name};
 
-    diesel::update(authors.find(id))
+    let new_name = validate_name("name", new_name)?;
+    let updated_rows = diesel::update(authors.find(id))
         .set(name.eq(new_name))
         .execute(connection)
         .context("updating author")?;
 
+    record_rows(updated_rows);
+
+    Ok(())
+}
+
+#[query_span]
+pub fn get_author_profile(id: i32, connection: &mut DbConnection) -> Result<Option<AuthorProfile>> {
+    use schema::authors::dsl::authors;
+
+    let profile = authors
+        .find(id)
+        .select(AuthorProfile::as_select())
+        .first(connection)
+        .optional()
+        .context("getting author profile")?;
+
+    record_rows(profile.iter().count());
+
+    Ok(profile)
+}
+
+/// Replaces the author's profile. A death date before the birth date is refused unless
+/// either of them is only approximate.
+#[query_span(skip(connection, details))]
+pub fn update_author_profile(
+    id: i32,
+    details: &AuthorDetails,
+    connection: &mut DbConnection,
+) -> Result<()> {
+    use schema::authors::dsl::{authors, biography, birth_date, death_date, nationality};
+
+    let new_birth_date = details
+        .birth_date
+        .map(|value| validate_partial_date("birth_date", value))
+        .transpose()?;
+    let new_death_date = details
+        .death_date
+        .map(|value| validate_partial_date("death_date", value))
+        .transpose()?;
+    let new_nationality = details
+        .nationality
+        .map(|value| validate_country_code("nationality", value))
+        .transpose()?;
+    let new_biography = details
+        .biography
+        .map(|value| validate_biography("biography", value))
+        .transpose()?;
+    let dates = new_birth_date
+        .as_deref()
+        .and_then(PartialDate::parse)
+        .zip(new_death_date.as_deref().and_then(PartialDate::parse));
+
+    if let Some((born, died)) = dates {
+        // Compare the latest the death could be with the earliest the birth could be.
+        let latest_death = (died.year, died.month.unwrap_or(12), died.day.unwrap_or(31));
+        let earliest_birth = (born.year, born.month.unwrap_or(1), born.day.unwrap_or(1));
+
+        if !born.circa && !died.circa && latest_death < earliest_birth {
+            return Err(ValidationError {
+                field: "death_date",
+                reason: ValidationReason::Invalid {
+                    expected: "a date after the birth date",
+                },
+            }
+            .into());
+        }
+    }
+
+    let updated_rows = diesel::update(authors.find(id))
+        .set((
+            birth_date.eq(new_birth_date),
+            death_date.eq(new_death_date),
+            nationality.eq(new_nationality),
+            biography.eq(new_biography),
+        ))
+        .execute(connection)
+        .context("updating author profile")?;
+
+    record_rows(updated_rows);
+
     Ok(())
 }
 
-pub fn delete_author(id: i32, connection: &mut PgConnection) -> Result<()> {
+#[query_span]
+pub fn delete_author(id: i32, connection: &mut DbConnection) -> Result<()> {
     use schema::authors::dsl::authors;
 
-    diesel::delete(authors.find(id))
+    let deleted_rows = diesel::delete(authors.find(id))
         .execute(connection)
         .context("deleting author")?;
 
+    record_rows(deleted_rows);
+
     Ok(())
 }
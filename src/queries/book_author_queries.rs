@@ -1,111 +1,336 @@
+use std::{collections::HashSet, slice};
+
 use super::{
     author_queries::{get_all_authors, get_author_by_id},
     book_queries::get_book_by_id,
 };
 use crate::{
-    models::{Author, Book, BookAuthor, NewBookAuthor},
+    connect::DbConnection,
+    instrumentation::{query_span, record_rows},
+    models::{Author, AuthorWithBooks, Book, BookWithAuthors, NewBookContributor},
     queries::book_queries::get_all_books,
+    schema,
+};
+use diesel::{
+    dsl::{exists, max},
+    prelude::*,
+    query_dsl::positional_order_dsl::PositionalOrderDsl,
+    sql_types::Integer,
 };
-use diesel::{associations::HasTable, prelude::*, BelongingToDsl};
 use eyre::{Context, Result};
 
+/// The role of an edition credit that counts the person as one of the book's authors.
+pub const AUTHOR_ROLE: &str = "author";
+
+#[query_span]
 pub fn associate_book_with_author(
     book_id: i32,
     author_id: i32,
-    connection: &mut PgConnection,
+    connection: &mut DbConnection,
 ) -> Result<()> {
-    use crate::schema::book_authors::table as BookAuthorTable;
+    connection.transaction(|connection| {
+        let inserted_rows = NewBookContributor {
+            book_id,
+            author_id,
+            role: AUTHOR_ROLE,
+            position: next_credit_position(book_id, connection)?,
+        }
+        .insert_into(schema::book_authors::table)
+        .execute(connection)
+        .context("associating book with author")?;
+
+        record_rows(inserted_rows);
 
-    let new_book_author = NewBookAuthor { author_id, book_id };
+        Ok(())
+    })
+}
 
-    new_book_author
-        .insert_into(BookAuthorTable)
+/// Removes the book's own author credit. Other roles on the book and the authors of its
+/// work are left alone.
+#[query_span]
+pub fn dissociate_book_from_author(
+    book_id: i32,
+    author_id: i32,
+    connection: &mut DbConnection,
+) -> Result<bool> {
+    use crate::schema::book_authors::table as BookAuthorTable;
+
+    let deleted_rows = diesel::delete(BookAuthorTable.find((author_id, book_id, AUTHOR_ROLE)))
         .execute(connection)
-        .context("associating book with author")?;
+        .context("dissociating book from author")?;
 
-    Ok(())
+    record_rows(deleted_rows);
+
+    Ok(deleted_rows > 0)
 }
 
+/// Whether the author is credited on the book, on the book itself or on its work.
+#[query_span]
+pub fn book_author_exists(
+    book_id: i32,
+    author_id: i32,
+    connection: &mut DbConnection,
+) -> Result<bool> {
+    use schema::{book_authors, books, work_authors};
+
+    let edition_credit = book_authors::table.find((author_id, book_id, AUTHOR_ROLE));
+    let work_credit = work_authors::table
+        .filter(work_authors::author_id.eq(author_id))
+        .filter(
+            work_authors::work_id
+                .nullable()
+                .eq_any(books::table.find(book_id).select(books::work_id)),
+        );
+    let found = diesel::select(exists(edition_credit).or(exists(work_credit)))
+        .get_result::<bool>(connection)
+        .context("checking whether book is associated with author")?;
+
+    record_rows(usize::from(found));
+
+    Ok(found)
+}
+
+#[query_span]
 pub fn get_author_with_books(
     author_id: i32,
-    connection: &mut PgConnection,
-) -> Result<Option<(Author, Vec<Book>)>> {
+    connection: &mut DbConnection,
+) -> Result<Option<AuthorWithBooks>> {
     let Some(author) = get_author_by_id(author_id, connection).context("getting author")? else {
         return Ok(None);
     };
 
-    let books: Vec<Book> = BookAuthor::belonging_to(&author)
-        .inner_join(Book::table())
-        .select(Book::as_select())
-        .get_results(connection)
-        .context("getting all books for the author")?;
+    let books = get_credited_books(slice::from_ref(&author), connection)
+        .context("getting all books for the author")?
+        .pop()
+        .unwrap_or_default();
 
-    Ok(Some((author, books)))
+    record_rows(books.len());
+
+    Ok(Some(AuthorWithBooks::new(author, books)))
 }
 
+#[query_span]
 pub fn get_book_with_authors(
     book_id: i32,
-    connection: &mut PgConnection,
-) -> Result<Option<(Book, Vec<Author>)>> {
+    connection: &mut DbConnection,
+) -> Result<Option<BookWithAuthors>> {
     let Some(book) = get_book_by_id(book_id, connection).context("getting book")? else {
         return Ok(None);
     };
-    let authors: Vec<Author> = BookAuthor::belonging_to(&book)
-        .inner_join(Author::table())
-        .select(Author::as_select())
-        .get_results(connection)
-        .context("getting authors belong to the book")?;
+    let authors = get_credited_authors(slice::from_ref(&book), connection)
+        .context("getting authors belong to the book")?
+        .pop()
+        .unwrap_or_default();
+
+    record_rows(authors.len());
 
-    Ok(Some((book, authors)))
+    Ok(Some(BookWithAuthors::new(book, authors)))
 }
 
-pub fn get_all_books_and_authors(
-    connection: &mut PgConnection,
-) -> Result<Vec<(Book, Vec<Author>)>> {
+#[query_span]
+pub fn get_all_books_and_authors(connection: &mut DbConnection) -> Result<Vec<BookWithAuthors>> {
     let all_books = get_all_books(connection)?;
-    let authors_for_books: Vec<(BookAuthor, Author)> = BookAuthor::belonging_to(&all_books)
-        .inner_join(Author::table())
-        .select((BookAuthor::as_select(), Author::as_select()))
-        .get_results(connection)
-        .context("getting authors for books")?;
+
+    get_authors_for_books(all_books, connection)
+}
+
+#[query_span(skip_all)]
+pub fn get_authors_for_books(
+    books: Vec<Book>,
+    connection: &mut DbConnection,
+) -> Result<Vec<BookWithAuthors>> {
+    let authors_for_books =
+        get_credited_authors(&books, connection).context("getting authors for books")?;
+
+    record_rows(authors_for_books.iter().map(Vec::len).sum());
+
     let books_with_authors = authors_for_books
-        .grouped_by(&all_books)
         .into_iter()
-        .zip(all_books)
-        .map(|(authors, book)| {
-            (
-                book,
-                authors.into_iter().map(|(_, author)| author).collect(),
-            )
-        })
-        .collect::<Vec<(Book, Vec<Author>)>>();
+        .zip(books)
+        .map(|(authors, book)| BookWithAuthors::new(book, authors))
+        .collect::<Vec<BookWithAuthors>>();
 
     Ok(books_with_authors)
 }
 
-pub fn get_all_authors_and_books(
-    connection: &mut PgConnection,
-) -> Result<Vec<(Author, Vec<Book>)>> {
+#[query_span]
+pub fn get_all_authors_and_books(connection: &mut DbConnection) -> Result<Vec<AuthorWithBooks>> {
     let all_authors = get_all_authors(connection)?;
-    let books_with_authors: Vec<(BookAuthor, Book)> = BookAuthor::belonging_to(&all_authors)
-        .inner_join(Book::table())
-        .select((BookAuthor::as_select(), Book::as_select()))
-        .get_results(connection)
-        .context("getting all books with authors")?;
-    let authors_with_books = books_with_authors
-        .grouped_by(&all_authors)
+
+    get_books_for_authors(all_authors, connection)
+}
+
+#[query_span(skip_all)]
+pub fn get_books_for_authors(
+    authors: Vec<Author>,
+    connection: &mut DbConnection,
+) -> Result<Vec<AuthorWithBooks>> {
+    let books_for_authors =
+        get_credited_books(&authors, connection).context("getting all books with authors")?;
+
+    record_rows(books_for_authors.iter().map(Vec::len).sum());
+
+    let authors_with_books = books_for_authors
         .into_iter()
-        .zip(all_authors)
-        .map(|(books, author)| {
-            (
-                author,
-                books
-                    .into_iter()
-                    .map(|(_, book)| book)
-                    .collect::<Vec<Book>>(),
-            )
-        })
-        .collect::<Vec<(Author, Vec<Book>)>>();
+        .zip(authors)
+        .map(|(books, author)| AuthorWithBooks::new(author, books))
+        .collect::<Vec<AuthorWithBooks>>();
 
     Ok(authors_with_books)
 }
+
+/// Credits from a book's work sort before the book's own.
+const WORK_CREDIT: i32 = 0;
+const EDITION_CREDIT: i32 = 1;
+
+/// An author credited on a book, either on the book itself or on its work.
+#[derive(Associations)]
+#[diesel(table_name = schema::book_authors)]
+#[diesel(belongs_to(Book))]
+struct CreditedAuthor {
+    book_id: i32,
+    author: Author,
+}
+
+/// A book credited to an author, either itself or through its work.
+#[derive(Associations)]
+#[diesel(table_name = schema::book_authors)]
+#[diesel(belongs_to(Author))]
+struct CreditedBook {
+    author_id: i32,
+    book: Book,
+}
+
+/// The authors of each book in one query: the authors of the book's work first, then the
+/// book's own author credits, each in credit order. An author credited both ways is listed
+/// once.
+fn get_credited_authors(books: &[Book], connection: &mut DbConnection) -> Result<Vec<Vec<Author>>> {
+    use schema::{authors, book_authors, books, work_authors, works};
+
+    let book_ids = books.iter().map(|book| book.id).collect::<Vec<i32>>();
+    let edition_credits = book_authors::table
+        .inner_join(authors::table)
+        .filter(book_authors::book_id.eq_any(&book_ids))
+        .filter(book_authors::role.eq(AUTHOR_ROLE))
+        .select((
+            book_authors::book_id,
+            EDITION_CREDIT.into_sql::<Integer>(),
+            book_authors::position,
+            (authors::id, authors::name),
+        ));
+    let work_credits = books::table
+        .inner_join(works::table.inner_join(work_authors::table.inner_join(authors::table)))
+        .filter(books::id.eq_any(&book_ids))
+        .select((
+            books::id,
+            WORK_CREDIT.into_sql::<Integer>(),
+            work_authors::position,
+            (authors::id, authors::name),
+        ));
+    // Book, then where the credit comes from, then credit order. Credits that share a
+    // position fall back to author order.
+    let query = edition_credits
+        .union_all(work_credits)
+        .positional_order_by((1, 2, 3, 4));
+    // The multi-backend connection cannot build compound selects, so the query runs on the
+    // concrete connection.
+    let credits: Vec<(i32, i32, i32, Author)> = match connection {
+        DbConnection::Pg(connection) => query.load(connection),
+        DbConnection::Sqlite(connection) => query.load(connection),
+    }
+    .context("getting credited authors")?;
+
+    Ok(credits
+        .into_iter()
+        .map(|(book_id, _, _, author)| CreditedAuthor { book_id, author })
+        .collect::<Vec<CreditedAuthor>>()
+        .grouped_by(books)
+        .into_iter()
+        .map(|credits| {
+            let mut seen = HashSet::new();
+
+            credits
+                .into_iter()
+                .map(|credit| credit.author)
+                .filter(|author| seen.insert(author.id))
+                .collect()
+        })
+        .collect())
+}
+
+/// The books of each author in one query, including every edition of the works they are
+/// credited on, in book order.
+fn get_credited_books(authors: &[Author], connection: &mut DbConnection) -> Result<Vec<Vec<Book>>> {
+    use schema::{book_authors, books, work_authors, works};
+
+    let author_ids = authors.iter().map(|author| author.id).collect::<Vec<i32>>();
+    let edition_credits = book_authors::table
+        .inner_join(books::table)
+        .filter(book_authors::author_id.eq_any(&author_ids))
+        .filter(book_authors::role.eq(AUTHOR_ROLE))
+        .select((
+            book_authors::author_id,
+            EDITION_CREDIT.into_sql::<Integer>(),
+            (books::id, books::name),
+        ));
+    let work_credits = work_authors::table
+        .inner_join(works::table.inner_join(books::table))
+        .filter(work_authors::author_id.eq_any(&author_ids))
+        .select((
+            work_authors::author_id,
+            WORK_CREDIT.into_sql::<Integer>(),
+            (books::id, books::name),
+        ));
+    // Author, then book, then where the credit comes from.
+    let query = edition_credits
+        .union_all(work_credits)
+        .positional_order_by((1, 3, 2));
+    let credits: Vec<(i32, i32, Book)> = match connection {
+        DbConnection::Pg(connection) => query.load(connection),
+        DbConnection::Sqlite(connection) => query.load(connection),
+    }
+    .context("getting credited books")?;
+
+    Ok(credits
+        .into_iter()
+        .map(|(author_id, _, book)| CreditedBook { author_id, book })
+        .collect::<Vec<CreditedBook>>()
+        .grouped_by(authors)
+        .into_iter()
+        .map(|credits| {
+            let mut seen = HashSet::new();
+
+            credits
+                .into_iter()
+                .map(|credit| credit.book)
+                .filter(|book| seen.insert(book.id))
+                .collect()
+        })
+        .collect())
+}
+
+/// The position after the book's last credit.
+pub(crate) fn next_credit_position(book_id: i32, connection: &mut DbConnection) -> Result<i32> {
+    use schema::book_authors::dsl::{book_authors, book_id as credit_book_id, position};
+
+    let last = book_authors
+        .filter(credit_book_id.eq(book_id))
+        .select(max(position))
+        .first::<Option<i32>>(connection)
+        .context("getting last credit position")?;
+
+    Ok(last.map_or(0, |last| last + 1))
+}
+
+pub(crate) fn get_work_id(book_id: i32, connection: &mut DbConnection) -> Result<Option<i32>> {
+    use schema::books::dsl::{books, work_id};
+
+    let found = books
+        .find(book_id)
+        .select(work_id)
+        .first::<Option<i32>>(connection)
+        .optional()
+        .context("getting work of book")?;
+
+    Ok(found.flatten())
+}
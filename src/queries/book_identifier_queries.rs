@@ -0,0 +1,116 @@
+use diesel::{associations::HasTable, prelude::*, BelongingToDsl};
+use eyre::{Context, Result};
+
+use crate::{
+    connect::DbConnection,
+    instrumentation::{query_span, record_rows},
+    models::{Book, BookIdentifier, IdentifierScheme, NewBookIdentifier},
+    schema,
+    validation::{validate_isbn, ValidationError},
+};
+
+fn normalize_identifier(scheme: IdentifierScheme, value: &str) -> Result<String, ValidationError> {
+    match scheme {
+        IdentifierScheme::Isbn => validate_isbn("isbn", value),
+    }
+}
+
+#[query_span]
+pub fn add_book_identifier(
+    book_id: i32,
+    scheme: IdentifierScheme,
+    value: &str,
+    connection: &mut DbConnection,
+) -> Result<String> {
+    let value = normalize_identifier(scheme, value)?;
+    let new_identifier = NewBookIdentifier {
+        book_id,
+        scheme: scheme.as_str(),
+        value: &value,
+    };
+
+    let inserted_rows = new_identifier
+        .insert_into(BookIdentifier::table())
+        .execute(connection)
+        .context("adding book identifier")?;
+
+    record_rows(inserted_rows);
+
+    Ok(value)
+}
+
+#[query_span]
+pub fn remove_book_identifier(
+    scheme: IdentifierScheme,
+    value: &str,
+    connection: &mut DbConnection,
+) -> Result<bool> {
+    let value = normalize_identifier(scheme, value)?;
+    let deleted_rows = diesel::delete(BookIdentifier::table().find((scheme.as_str(), value)))
+        .execute(connection)
+        .context("removing book identifier")?;
+
+    record_rows(deleted_rows);
+
+    Ok(deleted_rows > 0)
+}
+
+#[query_span]
+pub fn get_book_by_identifier(
+    scheme: IdentifierScheme,
+    value: &str,
+    connection: &mut DbConnection,
+) -> Result<Option<Book>> {
+    use schema::book_identifiers::dsl;
+
+    let value = normalize_identifier(scheme, value)?;
+    let book = BookIdentifier::table()
+        .inner_join(Book::table())
+        .filter(dsl::scheme.eq(scheme.as_str()))
+        .filter(dsl::value.eq(value))
+        .select(Book::as_select())
+        .first(connection)
+        .optional()
+        .context("getting book by identifier")?;
+
+    record_rows(book.iter().count());
+
+    Ok(book)
+}
+
+#[query_span]
+pub fn get_identifiers_for_book(
+    book_id: i32,
+    connection: &mut DbConnection,
+) -> Result<Vec<BookIdentifier>> {
+    use schema::book_identifiers::dsl;
+
+    let identifiers = BookIdentifier::table()
+        .filter(dsl::book_id.eq(book_id))
+        .order((dsl::scheme, dsl::value))
+        .select(BookIdentifier::as_select())
+        .load(connection)
+        .context("getting identifiers for book")?;
+
+    record_rows(identifiers.len());
+
+    Ok(identifiers)
+}
+
+#[query_span(skip_all)]
+pub fn get_identifiers_for_books(
+    books: &[Book],
+    connection: &mut DbConnection,
+) -> Result<Vec<Vec<BookIdentifier>>> {
+    use schema::book_identifiers::dsl;
+
+    let identifiers = BookIdentifier::belonging_to(books)
+        .order((dsl::scheme, dsl::value))
+        .select(BookIdentifier::as_select())
+        .load(connection)
+        .context("getting identifiers for books")?;
+
+    record_rows(identifiers.len());
+
+    Ok(identifiers.grouped_by(books))
+}
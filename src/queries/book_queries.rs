@@ -1,16 +1,21 @@
+use chrono::{NaiveDateTime, SubsecRound};
 use diesel::{associations::HasTable, prelude::*};
 use eyre::{Context, Result};
 
 use crate::{
+    connect::{unicode_lower, DbConnection},
+    instrumentation::{query_span, record_rows},
     models::{Book, NewBook},
     schema,
+    validation::{normalize, validate_name},
 };
 
-pub fn create_book(name: &str, connection: &mut PgConnection) -> Result<i32> {
+#[query_span]
+pub fn create_book(name: &str, connection: &mut DbConnection) -> Result<i32> {
     use schema::books::dsl::id;
 
     let new_book = NewBook {
-        name: name.to_owned(),
+        name: validate_name("name", name)?,
     };
 
     let created_id = new_book
@@ -19,46 +24,214 @@ pub fn create_book(name: &str, connection: &mut PgConnection) -> Result<i32> {
         .get_result(connection)
         .context("Getting id back after inserting book")?;
 
+    record_rows(1);
+
     Ok(created_id)
 }
 
-pub fn get_all_books(connection: &mut PgConnection) -> Result<Vec<Book>> {
+#[query_span]
+pub fn get_all_books(connection: &mut DbConnection) -> Result<Vec<Book>> {
     use schema::books::dsl::books;
 
-    books
+    let all_books = books
         .select(Book::as_select())
         .load(connection)
-        .context("getting all books")
+        .context("getting all books")?;
+
+    record_rows(all_books.len());
+
+    Ok(all_books)
 }
 
-pub fn get_book_by_id(id: i32, connection: &mut PgConnection) -> Result<Option<Book>> {
+#[query_span]
+pub fn get_books_page(
+    after_id: Option<i32>,
+    limit: i64,
+    connection: &mut DbConnection,
+) -> Result<Vec<Book>> {
+    use schema::books::dsl::{books, id};
+
+    let page = books
+        .filter(id.gt(after_id.unwrap_or(i32::MIN)))
+        .order(id)
+        .limit(limit)
+        .select(Book::as_select())
+        .load(connection)
+        .context("getting page of books")?;
+
+    record_rows(page.len());
+
+    Ok(page)
+}
+
+#[query_span]
+pub fn get_book_by_id(id: i32, connection: &mut DbConnection) -> Result<Option<Book>> {
     use schema::books::dsl::books;
 
-    books
+    let book = books
         .find(id)
         .select(Book::as_select())
         .first(connection)
         .optional()
-        .context("getting book by id")
+        .context("getting book by id")?;
+
+    record_rows(book.iter().count());
+
+    Ok(book)
+}
+
+#[query_span]
+pub fn get_books_by_ids(ids: &[i32], connection: &mut DbConnection) -> Result<Vec<Book>> {
+    use schema::books::dsl::{books, id};
+
+    let found_books = books
+        .filter(id.eq_any(ids))
+        .select(Book::as_select())
+        .load(connection)
+        .context("getting books by ids")?;
+
+    record_rows(found_books.len());
+
+    Ok(found_books)
+}
+
+#[query_span]
+pub fn get_books_by_name(name: &str, connection: &mut DbConnection) -> Result<Vec<Book>> {
+    use schema::books::dsl::{books, name as book_name};
+
+    let found_books = books
+        .filter(book_name.eq(normalize(name)))
+        .select(Book::as_select())
+        .load(connection)
+        .context("getting books by name")?;
+
+    record_rows(found_books.len());
+
+    Ok(found_books)
 }
 
-pub fn update_book(id: i32, new_name: &str, connection: &mut PgConnection) -> Result<()> {
+#[query_span]
+pub fn update_book(id: i32, new_name: &str, connection: &mut DbConnection) -> Result<()> {
     use schema::books::dsl::{books, name};
 
-    diesel::update(books.find(id))
+    let new_name = validate_name("name", new_name)?;
+    let updated_rows = diesel::update(books.find(id))
         .set(name.eq(new_name))
         .execute(connection)
         .context("updating book")?;
 
+    record_rows(updated_rows);
+
     Ok(())
 }
 
-pub fn delete_book(id: i32, connection: &mut PgConnection) -> Result<()> {
+/// The books changed at or after `since`. The database sets `updated_at`, and SQLite only
+/// keeps milliseconds, so `since` is rounded down to the millisecond; a book changed just
+/// before it may be included, but no change after it is missed.
+#[query_span]
+pub fn get_books_changed_since_page(
+    since: NaiveDateTime,
+    after_id: Option<i32>,
+    limit: i64,
+    connection: &mut DbConnection,
+) -> Result<Vec<Book>> {
+    use schema::books::dsl::{books, id, updated_at};
+
+    let page = books
+        .filter(updated_at.ge(since.trunc_subsecs(3)))
+        .filter(id.gt(after_id.unwrap_or(i32::MIN)))
+        .order(id)
+        .limit(limit)
+        .select(Book::as_select())
+        .load(connection)
+        .context("getting page of changed books")?;
+
+    record_rows(page.len());
+
+    Ok(page)
+}
+
+/// Books whose name, one of whose titles in any language, or one of whose authors' names,
+/// variants and pseudonyms included, contains `terms`, ignoring case.
+#[query_span]
+pub fn search_books_page(
+    terms: &str,
+    after_id: Option<i32>,
+    limit: i64,
+    connection: &mut DbConnection,
+) -> Result<Vec<Book>> {
+    use schema::{author_names, authors, book_authors, book_titles, books, work_authors};
+
+    // Both sides are lowered by the same function, so they agree on what case means.
+    let pattern = unicode_lower(format!(
+        "%{}%",
+        normalize(terms)
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    ));
+    let named_authors = author_names::table
+        .filter(
+            unicode_lower(author_names::name)
+                .like(pattern.clone())
+                .escape('\\'),
+        )
+        .select(author_names::author_id);
+    let credited_books = book_authors::table
+        .inner_join(authors::table)
+        .filter(
+            unicode_lower(authors::name)
+                .like(pattern.clone())
+                .escape('\\')
+                .or(authors::id.eq_any(named_authors.clone())),
+        )
+        .select(book_authors::book_id);
+    let credited_works = work_authors::table
+        .inner_join(authors::table)
+        .filter(
+            unicode_lower(authors::name)
+                .like(pattern.clone())
+                .escape('\\')
+                .or(authors::id.eq_any(named_authors)),
+        )
+        .select(work_authors::work_id.nullable());
+    let titled_books = book_titles::table
+        .filter(
+            unicode_lower(book_titles::title)
+                .like(pattern.clone())
+                .escape('\\'),
+        )
+        .select(book_titles::book_id);
+    let page = books::table
+        .filter(
+            unicode_lower(books::name)
+                .like(pattern.clone())
+                .escape('\\')
+                .or(books::id.eq_any(titled_books))
+                .or(books::id.eq_any(credited_books))
+                .or(books::work_id.eq_any(credited_works)),
+        )
+        .filter(books::id.gt(after_id.unwrap_or(i32::MIN)))
+        .order(books::id)
+        .limit(limit)
+        .select(Book::as_select())
+        .load(connection)
+        .context("searching books")?;
+
+    record_rows(page.len());
+
+    Ok(page)
+}
+
+#[query_span]
+pub fn delete_book(id: i32, connection: &mut DbConnection) -> Result<()> {
     use schema::books::dsl::books;
 
-    diesel::delete(books.find(id))
+    let deleted_rows = diesel::delete(books.find(id))
         .execute(connection)
         .context("deleting book")?;
 
+    record_rows(deleted_rows);
+
     Ok(())
 }
@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+
+use diesel::{associations::HasTable, prelude::*};
+use eyre::{Context, Result};
+
+use crate::{
+    connect::DbConnection,
+    instrumentation::{query_span, record_rows},
+    models::{BookTitle, BookWithAuthors, LocalizedTitle, NewBookTitle},
+    queries::book_author_queries::get_book_with_authors,
+    schema,
+    validation::{validate_language_tag, validate_name},
+};
+
+/// Sets the book's title in `language`, replacing any title it already had in it. Marking a
+/// title as the original takes the mark away from the book's other titles.
+#[query_span]
+pub fn set_book_title(
+    book_id: i32,
+    language: &str,
+    title: &str,
+    is_original: bool,
+    connection: &mut DbConnection,
+) -> Result<()> {
+    use schema::book_titles::dsl::{
+        book_id as title_book_id, book_titles, is_original as title_is_original,
+        title as title_text,
+    };
+
+    let language = validate_language_tag("language", language)?;
+    let title = validate_name("title", title)?;
+
+    connection.transaction(|connection| {
+        if is_original {
+            diesel::update(
+                book_titles
+                    .filter(title_book_id.eq(book_id))
+                    .filter(title_is_original.eq(true)),
+            )
+            .set(title_is_original.eq(false))
+            .execute(connection)
+            .context("clearing original title")?;
+        }
+
+        let updated_rows = diesel::update(book_titles.find((book_id, &language)))
+            .set((title_text.eq(&title), title_is_original.eq(is_original)))
+            .execute(connection)
+            .context("updating book title")?;
+
+        if updated_rows == 0 {
+            NewBookTitle {
+                book_id,
+                language: &language,
+                title: &title,
+                is_original,
+            }
+            .insert_into(BookTitle::table())
+            .execute(connection)
+            .context("adding book title")?;
+        }
+
+        record_rows(1);
+
+        Ok(())
+    })
+}
+
+#[query_span]
+pub fn remove_book_title(
+    book_id: i32,
+    language: &str,
+    connection: &mut DbConnection,
+) -> Result<bool> {
+    let language = validate_language_tag("language", language)?;
+    let deleted_rows = diesel::delete(BookTitle::table().find((book_id, language)))
+        .execute(connection)
+        .context("removing book title")?;
+
+    record_rows(deleted_rows);
+
+    Ok(deleted_rows > 0)
+}
+
+/// Every title of the book, the original first.
+#[query_span]
+pub fn get_book_titles(book_id: i32, connection: &mut DbConnection) -> Result<Vec<BookTitle>> {
+    use schema::book_titles::dsl::{book_id as title_book_id, book_titles, is_original, language};
+
+    let titles = book_titles
+        .filter(title_book_id.eq(book_id))
+        .order((is_original.desc(), language))
+        .select(BookTitle::as_select())
+        .load(connection)
+        .context("getting book titles")?;
+
+    record_rows(titles.len());
+
+    Ok(titles)
+}
+
+/// The languages to try, in order, for a reader who prefers `preferences`. Each tag is
+/// followed by its shorter forms, so `["de-AT", "en"]` becomes `de-AT`, `de`, `en`.
+pub fn language_fallbacks(preferences: &[&str]) -> Result<Vec<String>> {
+    let mut fallbacks = Vec::new();
+
+    for preference in preferences {
+        let mut tag = validate_language_tag("preferences", preference)?;
+
+        loop {
+            if !fallbacks.contains(&tag) {
+                fallbacks.push(tag.clone());
+            }
+
+            let Some((shorter, _)) = tag.rsplit_once('-') else {
+                break;
+            };
+
+            // A single-letter extension or private use prefix means nothing on its own.
+            tag = match shorter.rsplit_once('-') {
+                Some((shortest, singleton)) if singleton.len() == 1 => shortest.to_owned(),
+                _ => shorter.to_owned(),
+            };
+        }
+    }
+
+    Ok(fallbacks)
+}
+
+/// The best title of each book for a reader who prefers `preferences`: the first language in
+/// `language_fallbacks` the book has a title in, otherwise its original title. Books with
+/// neither are left out.
+#[query_span(skip(connection, book_ids))]
+pub fn get_localized_titles(
+    book_ids: &[i32],
+    preferences: &[&str],
+    connection: &mut DbConnection,
+) -> Result<HashMap<i32, LocalizedTitle>> {
+    use schema::book_titles::dsl::{book_id, book_titles, is_original, language};
+
+    let fallbacks = language_fallbacks(preferences)?;
+    let candidates = book_titles
+        .filter(book_id.eq_any(book_ids))
+        .filter(language.eq_any(&fallbacks).or(is_original.eq(true)))
+        .select(BookTitle::as_select())
+        .load(connection)
+        .context("getting localized titles")?;
+
+    record_rows(candidates.len());
+
+    let rank = |title: &BookTitle| {
+        fallbacks
+            .iter()
+            .position(|fallback| *fallback == title.language)
+            .unwrap_or(fallbacks.len())
+    };
+    let mut best = HashMap::<i32, BookTitle>::new();
+
+    for candidate in candidates {
+        match best.get(&candidate.book_id) {
+            Some(current) if rank(current) <= rank(&candidate) => {}
+            _ => {
+                best.insert(candidate.book_id, candidate);
+            }
+        }
+    }
+
+    Ok(best
+        .into_iter()
+        .map(|(id, title)| {
+            (
+                id,
+                LocalizedTitle {
+                    title: title.title,
+                    language: Some(title.language),
+                },
+            )
+        })
+        .collect())
+}
+
+/// Fills in the localized title of every book, extending what the book loaders return. Books
+/// without titles of their own fall back to their name.
+#[query_span(skip(connection, books_with_authors))]
+pub fn with_localized_titles(
+    books_with_authors: Vec<BookWithAuthors>,
+    preferences: &[&str],
+    connection: &mut DbConnection,
+) -> Result<Vec<BookWithAuthors>> {
+    let book_ids = books_with_authors
+        .iter()
+        .map(|book_with_authors| book_with_authors.book().id)
+        .collect::<Vec<i32>>();
+    let mut titles = get_localized_titles(&book_ids, preferences, connection)?;
+
+    Ok(books_with_authors
+        .into_iter()
+        .map(|book_with_authors| {
+            let title = titles
+                .remove(&book_with_authors.book().id)
+                .unwrap_or_else(|| LocalizedTitle {
+                    title: book_with_authors.book().name.clone(),
+                    language: None,
+                });
+
+            book_with_authors.with_localized_title(title)
+        })
+        .collect())
+}
+
+/// `get_book_with_authors` with the title picked for `preferences` filled in.
+#[query_span]
+pub fn get_localized_book_with_authors(
+    book_id: i32,
+    preferences: &[&str],
+    connection: &mut DbConnection,
+) -> Result<Option<BookWithAuthors>> {
+    let Some(book_with_authors) = get_book_with_authors(book_id, connection)? else {
+        return Ok(None);
+    };
+
+    Ok(with_localized_titles(vec![book_with_authors], preferences, connection)?.pop())
+}
@@ -0,0 +1,61 @@
+use diesel::{associations::HasTable, prelude::*};
+use eyre::{Context, Result};
+
+use crate::{
+    connect::DbConnection,
+    instrumentation::{query_span, record_rows},
+    models::{Customer, NewCustomer},
+    schema,
+    validation::{validate_email, validate_name},
+};
+
+#[query_span]
+pub fn create_customer(name: &str, email: &str, connection: &mut DbConnection) -> Result<i32> {
+    let name = validate_name("name", name)?;
+    let email = validate_email("email", email)?;
+    let id = NewCustomer {
+        name: &name,
+        email: &email,
+    }
+    .insert_into(Customer::table())
+    .returning(schema::customers::id)
+    .get_result(connection)
+    .context("creating customer")?;
+
+    record_rows(1);
+
+    Ok(id)
+}
+
+#[query_span]
+pub fn get_customer_by_id(id: i32, connection: &mut DbConnection) -> Result<Option<Customer>> {
+    let customer = Customer::table()
+        .find(id)
+        .select(Customer::as_select())
+        .first(connection)
+        .optional()
+        .context("getting customer by id")?;
+
+    record_rows(customer.iter().count());
+
+    Ok(customer)
+}
+
+#[query_span]
+pub fn get_customer_by_email(
+    email: &str,
+    connection: &mut DbConnection,
+) -> Result<Option<Customer>> {
+    use schema::customers::dsl::{customers, email as customer_email};
+
+    let customer = customers
+        .filter(customer_email.eq(email.trim().to_lowercase()))
+        .select(Customer::as_select())
+        .first(connection)
+        .optional()
+        .context("getting customer by email")?;
+
+    record_rows(customer.iter().count());
+
+    Ok(customer)
+}
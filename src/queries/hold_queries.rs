@@ -0,0 +1,219 @@
+use chrono::NaiveDateTime;
+use diesel::{associations::HasTable, dsl::not, prelude::*};
+use eyre::{eyre, Context, Result};
+
+use crate::{
+    connect::DbConnection,
+    instrumentation::{query_span, record_rows},
+    models::{BookCopy, Hold, HoldStatus, NewHold},
+    queries::loan_queries::{lock_book, LendingError},
+    schema,
+};
+
+const OPEN_STATUSES: [&str; 2] = ["waiting", "ready"];
+
+/// Puts a member in the queue for a book. When a copy is on the shelf it is set aside for
+/// them straight away and the returned hold is already ready.
+#[query_span]
+pub fn place_hold(
+    book_id: i32,
+    member_id: i32,
+    at: NaiveDateTime,
+    connection: &mut DbConnection,
+) -> Result<Hold> {
+    use schema::holds::dsl::{book_id as hold_book_id, holds, member_id as hold_member_id, status};
+    use schema::{copies, loans};
+
+    connection.transaction(|connection| {
+        lock_book(book_id, connection)?;
+
+        let open_holds = holds
+            .filter(hold_book_id.eq(book_id))
+            .filter(hold_member_id.eq(member_id))
+            .filter(status.eq_any(OPEN_STATUSES))
+            .count()
+            .get_result::<i64>(connection)
+            .context("counting open holds")?;
+
+        if open_holds > 0 {
+            return Err(LendingError::HoldExists { book_id, member_id }.into());
+        }
+
+        let open_loans = loans::table
+            .inner_join(copies::table)
+            .filter(copies::book_id.eq(book_id))
+            .filter(loans::member_id.eq(member_id))
+            .filter(loans::returned_at.is_null())
+            .count()
+            .get_result::<i64>(connection)
+            .context("counting open loans")?;
+
+        if open_loans > 0 {
+            return Err(LendingError::AlreadyBorrowed { book_id, member_id }.into());
+        }
+
+        let hold: Hold = NewHold {
+            book_id,
+            member_id,
+            status: HoldStatus::Waiting.as_str(),
+            placed_at: at,
+        }
+        .insert_into(Hold::table())
+        .returning(schema::holds::all_columns)
+        .get_result(connection)
+        .context("placing hold")?;
+        // A copy is only ever free while nobody is waiting, so the new hold is the one
+        // that gets it.
+        let hold = match get_free_copy(book_id, connection)? {
+            Some(copy) => assign_copy(&copy, at, connection)?.unwrap_or(hold),
+            None => hold,
+        };
+
+        record_rows(1);
+
+        Ok(hold)
+    })
+}
+
+/// Closes a hold. A copy that was set aside for it moves on to the next member in the queue.
+#[query_span]
+pub fn cancel_hold(hold_id: i32, at: NaiveDateTime, connection: &mut DbConnection) -> Result<Hold> {
+    use schema::holds::dsl::{closed_at, holds, status};
+
+    connection.transaction(|connection| {
+        let hold =
+            get_hold_by_id(hold_id, connection)?.ok_or(LendingError::HoldNotFound { hold_id })?;
+
+        lock_book(hold.book_id, connection)?;
+
+        let hold = holds
+            .find(hold_id)
+            .select(Hold::as_select())
+            .first(connection)
+            .context("getting hold")?;
+        let current = hold_status(&hold)?;
+
+        if !matches!(current, HoldStatus::Waiting | HoldStatus::Ready) {
+            return Err(LendingError::HoldClosed {
+                hold_id,
+                status: current,
+            }
+            .into());
+        }
+
+        let cancelled: Hold = diesel::update(holds.find(hold_id))
+            .set((status.eq(HoldStatus::Cancelled.as_str()), closed_at.eq(at)))
+            .returning(schema::holds::all_columns)
+            .get_result(connection)
+            .context("cancelling hold")?;
+
+        if let (HoldStatus::Ready, Some(copy_id)) = (current, hold.copy_id) {
+            let copy = BookCopy::table()
+                .find(copy_id)
+                .select(BookCopy::as_select())
+                .first(connection)
+                .context("getting held copy")?;
+
+            assign_copy(&copy, at, connection)?;
+        }
+
+        record_rows(1);
+
+        Ok(cancelled)
+    })
+}
+
+#[query_span]
+pub fn get_hold_by_id(hold_id: i32, connection: &mut DbConnection) -> Result<Option<Hold>> {
+    let hold = Hold::table()
+        .find(hold_id)
+        .select(Hold::as_select())
+        .first(connection)
+        .optional()
+        .context("getting hold by id")?;
+
+    record_rows(hold.iter().count());
+
+    Ok(hold)
+}
+
+/// The open holds on a book in the order they were placed, which is the order copies are
+/// handed out in.
+#[query_span]
+pub fn get_hold_queue(book_id: i32, connection: &mut DbConnection) -> Result<Vec<Hold>> {
+    use schema::holds::dsl::{book_id as hold_book_id, holds, id, placed_at, status};
+
+    let queue = holds
+        .filter(hold_book_id.eq(book_id))
+        .filter(status.eq_any(OPEN_STATUSES))
+        .order((placed_at, id))
+        .select(Hold::as_select())
+        .load(connection)
+        .context("getting hold queue")?;
+
+    record_rows(queue.len());
+
+    Ok(queue)
+}
+
+/// Sets a free copy aside for the longest-waiting hold on its book, if there is one. The
+/// caller must hold the book's lock.
+pub(crate) fn assign_copy(
+    copy: &BookCopy,
+    at: NaiveDateTime,
+    connection: &mut DbConnection,
+) -> Result<Option<Hold>> {
+    use schema::holds::dsl::{book_id, copy_id, holds, id, placed_at, ready_at, status};
+
+    let next_hold = holds
+        .filter(book_id.eq(copy.book_id))
+        .filter(status.eq(HoldStatus::Waiting.as_str()))
+        .order((placed_at, id))
+        .select(id)
+        .first::<i32>(connection)
+        .optional()
+        .context("getting next hold")?;
+    let Some(next_hold) = next_hold else {
+        return Ok(None);
+    };
+    let hold = diesel::update(holds.find(next_hold))
+        .set((
+            status.eq(HoldStatus::Ready.as_str()),
+            copy_id.eq(copy.id),
+            ready_at.eq(at),
+        ))
+        .returning(schema::holds::all_columns)
+        .get_result(connection)
+        .context("setting copy aside for hold")?;
+
+    Ok(Some(hold))
+}
+
+/// A copy of the book that is neither on loan nor set aside for a hold.
+fn get_free_copy(book_id: i32, connection: &mut DbConnection) -> Result<Option<BookCopy>> {
+    use schema::{copies, holds, loans};
+
+    copies::table
+        .filter(copies::book_id.eq(book_id))
+        .filter(not(copies::id.eq_any(
+            loans::table
+                .filter(loans::returned_at.is_null())
+                .select(loans::copy_id),
+        )))
+        .filter(not(copies::id.nullable().eq_any(
+            holds::table
+                .filter(holds::status.eq(HoldStatus::Ready.as_str()))
+                .filter(holds::copy_id.is_not_null())
+                .select(holds::copy_id),
+        )))
+        .order(copies::id)
+        .select(BookCopy::as_select())
+        .first(connection)
+        .optional()
+        .context("getting free copy")
+}
+
+fn hold_status(hold: &Hold) -> Result<HoldStatus> {
+    HoldStatus::parse(&hold.status)
+        .ok_or_else(|| eyre!("hold {} has unknown status `{}`", hold.id, hold.status))
+}
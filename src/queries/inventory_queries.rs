@@ -0,0 +1,408 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+};
+
+use diesel::{associations::HasTable, prelude::*, upsert::excluded};
+use eyre::{Context, Result};
+
+use crate::{
+    connect::DbConnection,
+    instrumentation::{query_span, record_rows},
+    models::{Book, Location, LowStockItem, NewLocation, StockLevel},
+    queries::book_author_queries::get_authors_for_books,
+    schema,
+    validation::{validate_count, validate_name, ValidationError, ValidationReason},
+};
+
+/// Returned (inside the `eyre::Report`) when a location holds fewer copies than an
+/// operation needs. Nothing is changed when this happens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InsufficientStock {
+    pub book_id: i32,
+    pub location_id: i32,
+    pub requested: i32,
+    pub available: i32,
+}
+
+impl Display for InsufficientStock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "book {} has {} of the {} copies needed at location {}",
+            self.book_id, self.available, self.requested, self.location_id
+        )
+    }
+}
+
+impl std::error::Error for InsufficientStock {}
+
+#[query_span]
+pub fn create_location(name: &str, connection: &mut DbConnection) -> Result<i32> {
+    let name = validate_name("name", name)?;
+    let id = NewLocation { name: &name }
+        .insert_into(Location::table())
+        .returning(schema::locations::id)
+        .get_result(connection)
+        .context("creating location")?;
+
+    record_rows(1);
+
+    Ok(id)
+}
+
+#[query_span]
+pub fn get_all_locations(connection: &mut DbConnection) -> Result<Vec<Location>> {
+    use schema::locations::dsl::{id, locations};
+
+    let all_locations = locations
+        .order(id)
+        .select(Location::as_select())
+        .load(connection)
+        .context("getting all locations")?;
+
+    record_rows(all_locations.len());
+
+    Ok(all_locations)
+}
+
+#[query_span]
+pub fn get_stock_level(
+    book_id: i32,
+    location_id: i32,
+    connection: &mut DbConnection,
+) -> Result<Option<StockLevel>> {
+    let stock_level = StockLevel::table()
+        .find((book_id, location_id))
+        .select(StockLevel::as_select())
+        .first(connection)
+        .optional()
+        .context("getting stock level")?;
+
+    record_rows(stock_level.iter().count());
+
+    Ok(stock_level)
+}
+
+#[query_span]
+pub fn get_stock_for_book(book_id: i32, connection: &mut DbConnection) -> Result<Vec<StockLevel>> {
+    use schema::inventory::dsl::{book_id as stock_book_id, inventory, location_id};
+
+    let stock_levels = inventory
+        .filter(stock_book_id.eq(book_id))
+        .order(location_id)
+        .select(StockLevel::as_select())
+        .load(connection)
+        .context("getting stock for book")?;
+
+    record_rows(stock_levels.len());
+
+    Ok(stock_levels)
+}
+
+/// Adds delivered copies, creating the stock level the first time a book reaches a location.
+#[query_span]
+pub fn receive_stock(
+    book_id: i32,
+    location_id: i32,
+    count: i32,
+    connection: &mut DbConnection,
+) -> Result<StockLevel> {
+    // Upserts are not part of `QueryDsl`.
+    use diesel::query_dsl::methods::FilterDsl;
+    use schema::inventory::dsl::{
+        book_id as stock_book_id, inventory, location_id as stock_location_id, quantity,
+    };
+
+    let count = validate_count("count", count)?;
+    let new_stock_level = StockLevel {
+        book_id,
+        location_id,
+        quantity: count,
+        reserved: 0,
+        reorder_threshold: 0,
+    };
+    // One statement, so two deliveries of a new book cannot both try to create its row.
+    // A delivery that would take the quantity past what the column holds updates nothing.
+    let query = diesel::insert_into(inventory)
+        .values(&new_stock_level)
+        .on_conflict((stock_book_id, stock_location_id))
+        .do_update()
+        .set(quantity.eq(quantity + excluded(quantity)))
+        .filter(quantity.le(i32::MAX - count))
+        .returning(schema::inventory::all_columns);
+    let stock_level = match connection {
+        DbConnection::Pg(connection) => query.get_result(connection).optional(),
+        DbConnection::Sqlite(connection) => query.get_result(connection).optional(),
+    }
+    .context("receiving stock")?;
+    let Some(stock_level) = stock_level else {
+        return Err(ValidationError {
+            field: "count",
+            reason: ValidationReason::Invalid {
+                expected: "small enough to keep the quantity in range",
+            },
+        }
+        .into());
+    };
+
+    record_rows(1);
+
+    Ok(stock_level)
+}
+
+/// Holds available copies back from sale until they are released or sold.
+#[query_span]
+pub fn reserve_stock(
+    book_id: i32,
+    location_id: i32,
+    count: i32,
+    connection: &mut DbConnection,
+) -> Result<StockLevel> {
+    adjust_stock(
+        book_id,
+        location_id,
+        count,
+        connection,
+        StockLevel::available,
+        |stock_level| StockLevel {
+            reserved: stock_level.reserved + count,
+            ..stock_level
+        },
+    )
+}
+
+#[query_span]
+pub fn release_stock(
+    book_id: i32,
+    location_id: i32,
+    count: i32,
+    connection: &mut DbConnection,
+) -> Result<StockLevel> {
+    adjust_stock(
+        book_id,
+        location_id,
+        count,
+        connection,
+        |stock_level| stock_level.reserved,
+        |stock_level| StockLevel {
+            reserved: stock_level.reserved - count,
+            ..stock_level
+        },
+    )
+}
+
+/// Sells copies that are not reserved.
+#[query_span]
+pub fn sell_stock(
+    book_id: i32,
+    location_id: i32,
+    count: i32,
+    connection: &mut DbConnection,
+) -> Result<StockLevel> {
+    adjust_stock(
+        book_id,
+        location_id,
+        count,
+        connection,
+        StockLevel::available,
+        |stock_level| StockLevel {
+            quantity: stock_level.quantity - count,
+            ..stock_level
+        },
+    )
+}
+
+/// Sells copies that were reserved earlier, removing them from both counts.
+#[query_span]
+pub fn sell_reserved_stock(
+    book_id: i32,
+    location_id: i32,
+    count: i32,
+    connection: &mut DbConnection,
+) -> Result<StockLevel> {
+    adjust_stock(
+        book_id,
+        location_id,
+        count,
+        connection,
+        |stock_level| stock_level.reserved,
+        |stock_level| StockLevel {
+            quantity: stock_level.quantity - count,
+            reserved: stock_level.reserved - count,
+            ..stock_level
+        },
+    )
+}
+
+#[query_span]
+pub fn set_reorder_threshold(
+    book_id: i32,
+    location_id: i32,
+    threshold: i32,
+    connection: &mut DbConnection,
+) -> Result<Option<StockLevel>> {
+    use schema::inventory::dsl::reorder_threshold;
+
+    if threshold < 0 {
+        return Err(ValidationError {
+            field: "reorder_threshold",
+            reason: ValidationReason::Invalid {
+                expected: "zero or a positive number",
+            },
+        }
+        .into());
+    }
+
+    let stock_level = diesel::update(StockLevel::table().find((book_id, location_id)))
+        .set(reorder_threshold.eq(threshold))
+        .returning(schema::inventory::all_columns)
+        .get_result(connection)
+        .optional()
+        .context("setting reorder threshold")?;
+
+    record_rows(stock_level.iter().count());
+
+    Ok(stock_level)
+}
+
+/// Every stock level whose available copies are at or below its reorder threshold, ordered
+/// by location and then by book name.
+#[query_span]
+pub fn get_low_stock_report(connection: &mut DbConnection) -> Result<Vec<LowStockItem>> {
+    use schema::{books, inventory, locations};
+
+    let rows: Vec<(StockLevel, Book, Location)> = inventory::table
+        .inner_join(books::table)
+        .inner_join(locations::table)
+        .filter((inventory::quantity - inventory::reserved).le(inventory::reorder_threshold))
+        .order((locations::name, locations::id, books::name, books::id))
+        .select((
+            StockLevel::as_select(),
+            Book::as_select(),
+            Location::as_select(),
+        ))
+        .load(connection)
+        .context("getting low stock")?;
+
+    record_rows(rows.len());
+
+    let mut books = rows
+        .iter()
+        .map(|(_, book, _)| book.clone())
+        .collect::<Vec<Book>>();
+
+    books.sort_by_key(|book| book.id);
+    books.dedup_by_key(|book| book.id);
+
+    let authors_by_book = get_authors_for_books(books, connection)?
+        .into_iter()
+        .map(|book_with_authors| {
+            let (book, authors) = book_with_authors.into_parts();
+
+            (
+                book.id,
+                authors.into_iter().map(|author| author.name).collect(),
+            )
+        })
+        .collect::<HashMap<i32, Vec<String>>>();
+
+    Ok(rows
+        .into_iter()
+        .map(|(stock_level, book, location)| LowStockItem {
+            authors: authors_by_book.get(&book.id).cloned().unwrap_or_default(),
+            book_id: book.id,
+            book_name: book.name,
+            location_id: location.id,
+            location_name: location.name,
+            quantity: stock_level.quantity,
+            reserved: stock_level.reserved,
+            reorder_threshold: stock_level.reorder_threshold,
+        })
+        .collect())
+}
+
+/// Locks the stock level, checks that `limit` of it covers `count` and only then saves the
+/// row `adjust` builds. Every adjustment stays between zero and the current quantity once
+/// the limit covers it, so none of them can overflow.
+fn adjust_stock(
+    book_id: i32,
+    location_id: i32,
+    count: i32,
+    connection: &mut DbConnection,
+    limit: impl FnOnce(&StockLevel) -> i32,
+    adjust: impl FnOnce(StockLevel) -> StockLevel,
+) -> Result<StockLevel> {
+    let count = validate_count("count", count)?;
+
+    connection.transaction(|connection| {
+        let insufficient = |available| InsufficientStock {
+            book_id,
+            location_id,
+            requested: count,
+            available,
+        };
+        let Some(stock_level) = lock_stock_level(book_id, location_id, connection)? else {
+            return Err(insufficient(0).into());
+        };
+        let limit = limit(&stock_level);
+
+        if limit < count {
+            return Err(insufficient(limit).into());
+        }
+
+        let stock_level = save_stock_level(adjust(stock_level), connection)?;
+
+        record_rows(1);
+
+        Ok(stock_level)
+    })
+}
+
+/// Reads a stock level and keeps other writers away from it until the surrounding
+/// transaction ends, so two sales can never both see the last copy.
+fn lock_stock_level(
+    book_id: i32,
+    location_id: i32,
+    connection: &mut DbConnection,
+) -> Result<Option<StockLevel>> {
+    use schema::inventory::dsl::quantity;
+
+    let query = StockLevel::table().find((book_id, location_id));
+    let stock_level = match connection {
+        DbConnection::Pg(connection) => query
+            .select(StockLevel::as_select())
+            .for_update()
+            .first(connection)
+            .optional(),
+        // SQLite has no row locks, but a write takes the database-wide write lock, which
+        // serializes the read below against every other writer.
+        DbConnection::Sqlite(connection) => {
+            diesel::update(StockLevel::table().find((book_id, location_id)))
+                .set(quantity.eq(quantity))
+                .execute(connection)
+                .context("locking stock level")?;
+
+            query
+                .select(StockLevel::as_select())
+                .first(connection)
+                .optional()
+        }
+    }
+    .context("locking stock level")?;
+
+    Ok(stock_level)
+}
+
+fn save_stock_level(stock_level: StockLevel, connection: &mut DbConnection) -> Result<StockLevel> {
+    use schema::inventory::dsl::{quantity, reserved};
+
+    diesel::update(StockLevel::table().find((stock_level.book_id, stock_level.location_id)))
+        .set((
+            quantity.eq(stock_level.quantity),
+            reserved.eq(stock_level.reserved),
+        ))
+        .returning(schema::inventory::all_columns)
+        .get_result(connection)
+        .context("saving stock level")
+}
@@ -0,0 +1,456 @@
+use std::fmt::{self, Display};
+
+use chrono::{Duration, NaiveDateTime};
+use diesel::{associations::HasTable, prelude::*};
+use eyre::{Context, Result};
+
+use crate::{
+    connect::DbConnection,
+    instrumentation::{query_span, record_rows},
+    models::{BookCopy, Hold, HoldStatus, Loan, NewBookCopy, NewLoan, OverdueLoan},
+    queries::hold_queries::assign_copy,
+    schema,
+    validation::validate_barcode,
+};
+
+/// Returned (inside the `eyre::Report`) when a lending operation is not allowed. Nothing is
+/// changed when this happens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LendingError {
+    BookNotFound { book_id: i32 },
+    CopyNotFound { copy_id: i32 },
+    LoanNotFound { loan_id: i32 },
+    HoldNotFound { hold_id: i32 },
+    CopyOnLoan { copy_id: i32 },
+    CopyOnHold { copy_id: i32, hold_id: i32 },
+    NotOnLoan { copy_id: i32 },
+    LoanReturned { loan_id: i32 },
+    RenewalLimit { loan_id: i32, renewals: i32 },
+    HoldsWaiting { loan_id: i32, book_id: i32 },
+    HoldExists { book_id: i32, member_id: i32 },
+    AlreadyBorrowed { book_id: i32, member_id: i32 },
+    HoldClosed { hold_id: i32, status: HoldStatus },
+}
+
+impl Display for LendingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BookNotFound { book_id } => write!(f, "book {book_id} not found"),
+            Self::CopyNotFound { copy_id } => write!(f, "copy {copy_id} not found"),
+            Self::LoanNotFound { loan_id } => write!(f, "loan {loan_id} not found"),
+            Self::HoldNotFound { hold_id } => write!(f, "hold {hold_id} not found"),
+            Self::CopyOnLoan { copy_id } => write!(f, "copy {copy_id} is already on loan"),
+            Self::CopyOnHold { copy_id, hold_id } => {
+                write!(f, "copy {copy_id} is set aside for hold {hold_id}")
+            }
+            Self::NotOnLoan { copy_id } => write!(f, "copy {copy_id} is not on loan"),
+            Self::LoanReturned { loan_id } => write!(f, "loan {loan_id} was already returned"),
+            Self::RenewalLimit { loan_id, renewals } => {
+                write!(
+                    f,
+                    "loan {loan_id} has already been renewed {renewals} times"
+                )
+            }
+            Self::HoldsWaiting { loan_id, book_id } => write!(
+                f,
+                "loan {loan_id} cannot be renewed while members are waiting for book {book_id}"
+            ),
+            Self::HoldExists { book_id, member_id } => {
+                write!(f, "member {member_id} already has a hold on book {book_id}")
+            }
+            Self::AlreadyBorrowed { book_id, member_id } => {
+                write!(f, "member {member_id} already has book {book_id} on loan")
+            }
+            Self::HoldClosed { hold_id, status } => {
+                write!(f, "hold {hold_id} is already {}", status.as_str())
+            }
+        }
+    }
+}
+
+impl std::error::Error for LendingError {}
+
+/// How long a loan runs and how often it can be renewed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoanPolicy {
+    pub loan_days: i64,
+    pub max_renewals: i32,
+}
+
+impl Default for LoanPolicy {
+    fn default() -> Self {
+        Self {
+            loan_days: 21,
+            max_renewals: 2,
+        }
+    }
+}
+
+impl LoanPolicy {
+    pub fn due_at(&self, from: NaiveDateTime) -> NaiveDateTime {
+        from + Duration::days(self.loan_days)
+    }
+}
+
+/// What checking a copy in did: the closed loan and, when someone was waiting for the book,
+/// the hold the copy is now set aside for.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize, PartialEq)
+)]
+pub struct Checkin {
+    pub loan: Loan,
+    pub hold: Option<Hold>,
+}
+
+/// Adds a copy to the collection. If members are waiting for the book the copy goes
+/// straight to the first of them.
+#[query_span]
+pub fn add_copy(
+    book_id: i32,
+    barcode: &str,
+    at: NaiveDateTime,
+    connection: &mut DbConnection,
+) -> Result<BookCopy> {
+    let barcode = validate_barcode("barcode", barcode)?;
+
+    connection.transaction(|connection| {
+        lock_book(book_id, connection)?;
+
+        let copy: BookCopy = NewBookCopy {
+            book_id,
+            barcode: &barcode,
+        }
+        .insert_into(BookCopy::table())
+        .returning(schema::copies::all_columns)
+        .get_result(connection)
+        .context("adding copy")?;
+
+        assign_copy(&copy, at, connection)?;
+        record_rows(1);
+
+        Ok(copy)
+    })
+}
+
+#[query_span]
+pub fn get_copy_by_barcode(
+    barcode: &str,
+    connection: &mut DbConnection,
+) -> Result<Option<BookCopy>> {
+    use schema::copies::dsl::{barcode as copy_barcode, copies};
+
+    let copy = copies
+        .filter(copy_barcode.eq(barcode.trim()))
+        .select(BookCopy::as_select())
+        .first(connection)
+        .optional()
+        .context("getting copy by barcode")?;
+
+    record_rows(copy.iter().count());
+
+    Ok(copy)
+}
+
+#[query_span]
+pub fn get_copies_for_book(book_id: i32, connection: &mut DbConnection) -> Result<Vec<BookCopy>> {
+    use schema::copies::dsl::{book_id as copy_book_id, copies, id};
+
+    let book_copies = copies
+        .filter(copy_book_id.eq(book_id))
+        .order(id)
+        .select(BookCopy::as_select())
+        .load(connection)
+        .context("getting copies for book")?;
+
+    record_rows(book_copies.len());
+
+    Ok(book_copies)
+}
+
+/// Lends a copy to a member until `policy.due_at(at)`. A copy set aside for a hold can only
+/// be checked out by the member who placed it, which fulfils the hold.
+#[query_span]
+pub fn checkout(
+    copy_id: i32,
+    member_id: i32,
+    at: NaiveDateTime,
+    policy: &LoanPolicy,
+    connection: &mut DbConnection,
+) -> Result<Loan> {
+    use schema::holds::dsl::{closed_at, copy_id as hold_copy_id, holds, status};
+
+    connection.transaction(|connection| {
+        let copy = get_copy(copy_id, connection)?;
+
+        lock_book(copy.book_id, connection)?;
+
+        if get_open_loan(copy_id, connection)?.is_some() {
+            return Err(LendingError::CopyOnLoan { copy_id }.into());
+        }
+
+        let ready_hold = holds
+            .filter(hold_copy_id.eq(copy_id))
+            .filter(status.eq(HoldStatus::Ready.as_str()))
+            .select(Hold::as_select())
+            .first(connection)
+            .optional()
+            .context("getting hold for copy")?;
+
+        if let Some(hold) = ready_hold {
+            if hold.member_id != member_id {
+                return Err(LendingError::CopyOnHold {
+                    copy_id,
+                    hold_id: hold.id,
+                }
+                .into());
+            }
+
+            diesel::update(holds.find(hold.id))
+                .set((status.eq(HoldStatus::Fulfilled.as_str()), closed_at.eq(at)))
+                .execute(connection)
+                .context("fulfilling hold")?;
+        }
+
+        let loan = NewLoan {
+            copy_id,
+            member_id,
+            checked_out_at: at,
+            due_at: policy.due_at(at),
+        }
+        .insert_into(Loan::table())
+        .returning(schema::loans::all_columns)
+        .get_result(connection)
+        .context("checking out copy")?;
+
+        record_rows(1);
+
+        Ok(loan)
+    })
+}
+
+/// Closes the copy's open loan and hands the copy to the longest-waiting hold on the book.
+#[query_span]
+pub fn checkin(copy_id: i32, at: NaiveDateTime, connection: &mut DbConnection) -> Result<Checkin> {
+    use schema::loans::dsl::{loans, returned_at};
+
+    connection.transaction(|connection| {
+        let copy = get_copy(copy_id, connection)?;
+
+        lock_book(copy.book_id, connection)?;
+
+        let loan =
+            get_open_loan(copy_id, connection)?.ok_or(LendingError::NotOnLoan { copy_id })?;
+        let loan = diesel::update(loans.find(loan.id))
+            .set(returned_at.eq(at))
+            .returning(schema::loans::all_columns)
+            .get_result(connection)
+            .context("checking in copy")?;
+        let hold = assign_copy(&copy, at, connection)?;
+
+        record_rows(1);
+
+        Ok(Checkin { loan, hold })
+    })
+}
+
+/// Extends an open loan to `policy.due_at(at)`, never moving the due date earlier. Loans
+/// cannot be renewed past `policy.max_renewals` or while other members wait for the book.
+#[query_span]
+pub fn renew(
+    loan_id: i32,
+    at: NaiveDateTime,
+    policy: &LoanPolicy,
+    connection: &mut DbConnection,
+) -> Result<Loan> {
+    use schema::holds::dsl::{book_id as hold_book_id, holds, status};
+    use schema::loans::dsl::{due_at, loans, renewals};
+
+    connection.transaction(|connection| {
+        let copy_id = loans
+            .find(loan_id)
+            .select(schema::loans::copy_id)
+            .first::<i32>(connection)
+            .optional()
+            .context("getting loan")?
+            .ok_or(LendingError::LoanNotFound { loan_id })?;
+        let copy = get_copy(copy_id, connection)?;
+
+        lock_book(copy.book_id, connection)?;
+
+        let loan = loans
+            .find(loan_id)
+            .select(Loan::as_select())
+            .first(connection)
+            .context("getting loan")?;
+
+        if loan.returned_at.is_some() {
+            return Err(LendingError::LoanReturned { loan_id }.into());
+        }
+
+        if loan.renewals >= policy.max_renewals {
+            return Err(LendingError::RenewalLimit {
+                loan_id,
+                renewals: loan.renewals,
+            }
+            .into());
+        }
+
+        let waiting = holds
+            .filter(hold_book_id.eq(copy.book_id))
+            .filter(status.eq(HoldStatus::Waiting.as_str()))
+            .count()
+            .get_result::<i64>(connection)
+            .context("counting waiting holds")?;
+
+        if waiting > 0 {
+            return Err(LendingError::HoldsWaiting {
+                loan_id,
+                book_id: copy.book_id,
+            }
+            .into());
+        }
+
+        let loan = diesel::update(loans.find(loan_id))
+            .set((
+                due_at.eq(policy.due_at(at).max(loan.due_at)),
+                renewals.eq(renewals + 1),
+            ))
+            .returning(schema::loans::all_columns)
+            .get_result(connection)
+            .context("renewing loan")?;
+
+        record_rows(1);
+
+        Ok(loan)
+    })
+}
+
+#[query_span]
+pub fn get_open_loans_for_member(
+    member_id: i32,
+    connection: &mut DbConnection,
+) -> Result<Vec<Loan>> {
+    use schema::loans::dsl::{due_at, id, loans, member_id as loan_member_id, returned_at};
+
+    let open_loans = loans
+        .filter(loan_member_id.eq(member_id))
+        .filter(returned_at.is_null())
+        .order((due_at, id))
+        .select(Loan::as_select())
+        .load(connection)
+        .context("getting open loans for member")?;
+
+    record_rows(open_loans.len());
+
+    Ok(open_loans)
+}
+
+/// Every open loan that was due before `at`, the longest overdue first.
+#[query_span]
+pub fn get_overdue_report(
+    at: NaiveDateTime,
+    connection: &mut DbConnection,
+) -> Result<Vec<OverdueLoan>> {
+    use schema::{books, copies, loans, members};
+
+    let rows: Vec<(Loan, String, i32, String, String)> = loans::table
+        .inner_join(copies::table.inner_join(books::table))
+        .inner_join(members::table)
+        .filter(loans::returned_at.is_null())
+        .filter(loans::due_at.lt(at))
+        .order((loans::due_at, loans::id))
+        .select((
+            Loan::as_select(),
+            copies::barcode,
+            copies::book_id,
+            books::name,
+            members::name,
+        ))
+        .load(connection)
+        .context("getting overdue loans")?;
+
+    record_rows(rows.len());
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(loan, barcode, book_id, book_name, member_name)| OverdueLoan {
+                loan_id: loan.id,
+                copy_id: loan.copy_id,
+                barcode,
+                book_id,
+                book_name,
+                member_id: loan.member_id,
+                member_name,
+                due_at: loan.due_at,
+                days_overdue: (at - loan.due_at).num_days(),
+            },
+        )
+        .collect())
+}
+
+fn get_copy(copy_id: i32, connection: &mut DbConnection) -> Result<BookCopy> {
+    let copy = BookCopy::table()
+        .find(copy_id)
+        .select(BookCopy::as_select())
+        .first(connection)
+        .optional()
+        .context("getting copy")?
+        .ok_or(LendingError::CopyNotFound { copy_id })?;
+
+    Ok(copy)
+}
+
+fn get_open_loan(copy_id: i32, connection: &mut DbConnection) -> Result<Option<Loan>> {
+    use schema::loans::dsl::{copy_id as loan_copy_id, loans, returned_at};
+
+    loans
+        .filter(loan_copy_id.eq(copy_id))
+        .filter(returned_at.is_null())
+        .select(Loan::as_select())
+        .first(connection)
+        .optional()
+        .context("getting open loan")
+}
+
+/// Every lending change to a book's copies, loans and holds takes this lock first, so the
+/// holds queue and the copies it hands out are always updated in the same order.
+pub(crate) fn lock_book(book_id: i32, connection: &mut DbConnection) -> Result<()> {
+    use schema::books::dsl::{books, id};
+
+    let found = match connection {
+        DbConnection::Pg(connection) => books
+            .find(book_id)
+            .select(id)
+            .for_no_key_update()
+            .first::<i32>(connection)
+            .optional(),
+        // SQLite has no row locks, but a write takes the database-wide write lock, even one
+        // that changes nothing. It goes through copies because an update on books, even to
+        // the same values, would mark the book as changed.
+        DbConnection::Sqlite(connection) => {
+            use schema::copies::dsl::{book_id as copy_book_id, copies};
+
+            diesel::update(copies.filter(copy_book_id.eq(book_id)))
+                .set(copy_book_id.eq(copy_book_id))
+                .execute(connection)
+                .context("locking book")?;
+
+            books
+                .find(book_id)
+                .select(id)
+                .first::<i32>(connection)
+                .optional()
+        }
+    }
+    .context("locking book")?
+    .is_some();
+
+    if !found {
+        return Err(LendingError::BookNotFound { book_id }.into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use diesel::{associations::HasTable, prelude::*};
+use eyre::{Context, Result};
+
+use crate::{
+    connect::DbConnection,
+    instrumentation::{query_span, record_rows},
+    models::{Member, NewMember},
+    schema,
+    validation::{validate_email, validate_name},
+};
+
+#[query_span]
+pub fn create_member(name: &str, email: &str, connection: &mut DbConnection) -> Result<i32> {
+    let name = validate_name("name", name)?;
+    let email = validate_email("email", email)?;
+    let id = NewMember {
+        name: &name,
+        email: &email,
+    }
+    .insert_into(Member::table())
+    .returning(schema::members::id)
+    .get_result(connection)
+    .context("creating member")?;
+
+    record_rows(1);
+
+    Ok(id)
+}
+
+#[query_span]
+pub fn get_member_by_id(id: i32, connection: &mut DbConnection) -> Result<Option<Member>> {
+    let member = Member::table()
+        .find(id)
+        .select(Member::as_select())
+        .first(connection)
+        .optional()
+        .context("getting member by id")?;
+
+    record_rows(member.iter().count());
+
+    Ok(member)
+}
+
+#[query_span]
+pub fn get_member_by_email(email: &str, connection: &mut DbConnection) -> Result<Option<Member>> {
+    use schema::members::dsl::{email as member_email, members};
+
+    let member = members
+        .filter(member_email.eq(email.trim().to_lowercase()))
+        .select(Member::as_select())
+        .first(connection)
+        .optional()
+        .context("getting member by email")?;
+
+    record_rows(member.iter().count());
+
+    Ok(member)
+}
@@ -1,3 +1,18 @@
+pub mod author_identifier_queries;
+pub mod author_name_queries;
 pub mod author_queries;
 pub mod book_author_queries;
+pub mod book_identifier_queries;
 pub mod book_queries;
+pub mod book_title_queries;
+pub mod customer_queries;
+pub mod hold_queries;
+pub mod inventory_queries;
+pub mod loan_queries;
+pub mod member_queries;
+pub mod order_queries;
+pub mod price_queries;
+pub mod review_queries;
+pub mod series_queries;
+pub mod stream_queries;
+pub mod work_queries;
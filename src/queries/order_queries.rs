@@ -0,0 +1,425 @@
+use std::fmt::{self, Display};
+
+use chrono::{NaiveDateTime, Utc};
+use diesel::{associations::HasTable, prelude::*};
+use eyre::{eyre, Context, Result};
+
+use crate::{
+    connect::DbConnection,
+    instrumentation::{query_span, record_rows},
+    models::{NewOrder, NewOrderLine, Order, OrderLine, OrderStatus},
+    queries::{
+        inventory_queries::{receive_stock, sell_stock},
+        price_queries::current_price,
+    },
+    schema,
+    validation::{validate_count, validate_currency},
+};
+
+/// Returned (inside the `eyre::Report`) when an order cannot be changed the way it was
+/// asked to. Nothing is changed when this happens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderError {
+    NotFound {
+        order_id: i32,
+    },
+    NotACart {
+        order_id: i32,
+        status: OrderStatus,
+    },
+    EmptyCart {
+        order_id: i32,
+    },
+    MissingPrice {
+        book_id: i32,
+        currency: String,
+    },
+    InvalidTransition {
+        order_id: i32,
+        from: OrderStatus,
+        to: OrderStatus,
+    },
+}
+
+impl Display for OrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound { order_id } => write!(f, "order {order_id} not found"),
+            Self::NotACart { order_id, status } => write!(
+                f,
+                "order {order_id} is {} and can no longer be changed",
+                status.as_str()
+            ),
+            Self::EmptyCart { order_id } => write!(f, "order {order_id} has no lines"),
+            Self::MissingPrice { book_id, currency } => {
+                write!(f, "book {book_id} has no {currency} price")
+            }
+            Self::InvalidTransition { order_id, from, to } => write!(
+                f,
+                "order {order_id} cannot go from {} to {}",
+                from.as_str(),
+                to.as_str()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for OrderError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PricedLine {
+    pub book_id: i32,
+    pub quantity: i32,
+    pub unit_price_minor: i64,
+    pub line_total_minor: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrderTotal {
+    pub currency: String,
+    pub lines: Vec<PricedLine>,
+    pub total_minor: i64,
+}
+
+#[query_span]
+pub fn create_cart(customer_id: i32, currency: &str, connection: &mut DbConnection) -> Result<i32> {
+    let currency = validate_currency("currency", currency)?;
+    let id = NewOrder {
+        customer_id,
+        status: OrderStatus::Cart.as_str(),
+        currency: &currency,
+        created_at: Utc::now().naive_utc(),
+    }
+    .insert_into(Order::table())
+    .returning(schema::orders::id)
+    .get_result(connection)
+    .context("creating cart")?;
+
+    record_rows(1);
+
+    Ok(id)
+}
+
+#[query_span]
+pub fn get_order_by_id(order_id: i32, connection: &mut DbConnection) -> Result<Option<Order>> {
+    let order = Order::table()
+        .find(order_id)
+        .select(Order::as_select())
+        .first(connection)
+        .optional()
+        .context("getting order by id")?;
+
+    record_rows(order.iter().count());
+
+    Ok(order)
+}
+
+#[query_span]
+pub fn get_orders_for_customer(
+    customer_id: i32,
+    connection: &mut DbConnection,
+) -> Result<Vec<Order>> {
+    use schema::orders::dsl::{customer_id as order_customer_id, id, orders};
+
+    let customer_orders = orders
+        .filter(order_customer_id.eq(customer_id))
+        .order(id)
+        .select(Order::as_select())
+        .load(connection)
+        .context("getting orders for customer")?;
+
+    record_rows(customer_orders.len());
+
+    Ok(customer_orders)
+}
+
+#[query_span]
+pub fn get_order_lines(order_id: i32, connection: &mut DbConnection) -> Result<Vec<OrderLine>> {
+    use schema::order_lines::dsl::{id, order_id as line_order_id, order_lines};
+
+    let lines = order_lines
+        .filter(line_order_id.eq(order_id))
+        .order(id)
+        .select(OrderLine::as_select())
+        .load(connection)
+        .context("getting order lines")?;
+
+    record_rows(lines.len());
+
+    Ok(lines)
+}
+
+/// Adds copies of a book to a cart, growing the existing line when the book is already in it.
+#[query_span]
+pub fn add_to_cart(
+    order_id: i32,
+    book_id: i32,
+    quantity: i32,
+    connection: &mut DbConnection,
+) -> Result<OrderLine> {
+    use schema::order_lines::dsl::{
+        book_id as line_book_id, order_id as line_order_id, order_lines, quantity as line_quantity,
+    };
+
+    let quantity = validate_count("quantity", quantity)?;
+
+    connection.transaction(|connection| {
+        lock_cart(order_id, connection)?;
+
+        let existing_line = order_lines
+            .filter(line_order_id.eq(order_id))
+            .filter(line_book_id.eq(book_id));
+        let updated = diesel::update(existing_line)
+            .set(line_quantity.eq(line_quantity + quantity))
+            .returning(schema::order_lines::all_columns)
+            .get_result(connection)
+            .optional()
+            .context("adding to order line")?;
+        let line = match updated {
+            Some(line) => line,
+            None => NewOrderLine {
+                order_id,
+                book_id,
+                quantity,
+            }
+            .insert_into(OrderLine::table())
+            .returning(schema::order_lines::all_columns)
+            .get_result(connection)
+            .context("creating order line")?,
+        };
+
+        record_rows(1);
+
+        Ok(line)
+    })
+}
+
+/// Takes a book out of a cart. Returns `false` when the book was not in it.
+#[query_span]
+pub fn remove_from_cart(
+    order_id: i32,
+    book_id: i32,
+    connection: &mut DbConnection,
+) -> Result<bool> {
+    use schema::order_lines::dsl::{
+        book_id as line_book_id, order_id as line_order_id, order_lines,
+    };
+
+    connection.transaction(|connection| {
+        lock_cart(order_id, connection)?;
+
+        let deleted_rows = diesel::delete(
+            order_lines
+                .filter(line_order_id.eq(order_id))
+                .filter(line_book_id.eq(book_id)),
+        )
+        .execute(connection)
+        .context("removing order line")?;
+
+        record_rows(deleted_rows);
+
+        Ok(deleted_rows > 0)
+    })
+}
+
+/// Prices every line of the order at `at` in the order's currency.
+#[query_span]
+pub fn get_order_total(
+    order_id: i32,
+    at: NaiveDateTime,
+    connection: &mut DbConnection,
+) -> Result<OrderTotal> {
+    let order = get_order_by_id(order_id, connection)?.ok_or(OrderError::NotFound { order_id })?;
+
+    price_order(&order, at, connection)
+}
+
+/// Turns a cart into a pending order: prices it at the current time, sells the copies from
+/// `location_id` and records what was charged. Everything happens in one transaction, so
+/// a book that is out of stock or has no price leaves the cart and the inventory untouched.
+#[query_span]
+pub fn place_order(
+    order_id: i32,
+    location_id: i32,
+    connection: &mut DbConnection,
+) -> Result<Order> {
+    use schema::order_lines::dsl::{
+        book_id as line_book_id, order_id as line_order_id, order_lines, unit_price_minor,
+    };
+    use schema::orders::dsl::{
+        location_id as order_location_id, orders, placed_at, status, total_minor,
+    };
+
+    connection.transaction(|connection| {
+        let order = lock_cart(order_id, connection)?;
+        let now = Utc::now().naive_utc();
+        let total = price_order(&order, now, connection)?;
+
+        if total.lines.is_empty() {
+            return Err(OrderError::EmptyCart { order_id }.into());
+        }
+
+        for line in &total.lines {
+            sell_stock(line.book_id, location_id, line.quantity, connection)?;
+            diesel::update(
+                order_lines
+                    .filter(line_order_id.eq(order_id))
+                    .filter(line_book_id.eq(line.book_id)),
+            )
+            .set(unit_price_minor.eq(line.unit_price_minor))
+            .execute(connection)
+            .context("recording price charged")?;
+        }
+
+        let order = diesel::update(orders.find(order_id))
+            .set((
+                status.eq(OrderStatus::Pending.as_str()),
+                order_location_id.eq(location_id),
+                total_minor.eq(total.total_minor),
+                placed_at.eq(now),
+            ))
+            .returning(schema::orders::all_columns)
+            .get_result(connection)
+            .context("placing order")?;
+
+        record_rows(total.lines.len() + 1);
+
+        Ok(order)
+    })
+}
+
+/// Moves a placed order along `OrderStatus::can_become`. Cancelling, or refunding an order
+/// that has not shipped, puts its copies back into stock at the order's location.
+#[query_span]
+pub fn set_order_status(
+    order_id: i32,
+    next: OrderStatus,
+    connection: &mut DbConnection,
+) -> Result<Order> {
+    use schema::orders::dsl::{orders, status};
+
+    connection.transaction(|connection| {
+        let order =
+            get_order_by_id(order_id, connection)?.ok_or(OrderError::NotFound { order_id })?;
+        let current = order_status(&order)?;
+
+        if !current.can_become(next) {
+            return Err(OrderError::InvalidTransition {
+                order_id,
+                from: current,
+                to: next,
+            }
+            .into());
+        }
+
+        let order = diesel::update(orders.find(order_id).filter(status.eq(current.as_str())))
+            .set(status.eq(next.as_str()))
+            .returning(schema::orders::all_columns)
+            .get_result::<Order>(connection)
+            .optional()
+            .context("changing order status")?
+            .ok_or_else(|| eyre!("order {order_id} changed while updating its status"))?;
+        let restock = next == OrderStatus::Cancelled
+            || (current == OrderStatus::Paid && next == OrderStatus::Refunded);
+
+        if let (true, Some(location_id)) = (restock, order.location_id) {
+            for line in get_order_lines(order_id, connection)? {
+                if let Some(book_id) = line.book_id {
+                    receive_stock(book_id, location_id, line.quantity, connection)?;
+                }
+            }
+        }
+
+        record_rows(1);
+
+        Ok(order)
+    })
+}
+
+fn order_status(order: &Order) -> Result<OrderStatus> {
+    OrderStatus::parse(&order.status)
+        .ok_or_else(|| eyre!("order {} has unknown status `{}`", order.id, order.status))
+}
+
+/// Every change to a cart, placing it included, locks its order first. A concurrent change
+/// waits and then sees the cart as the first one left it, so a line cannot be added to a cart
+/// that is being placed and the same cart cannot be sold twice.
+fn lock_cart(order_id: i32, connection: &mut DbConnection) -> Result<Order> {
+    let query = Order::table().find(order_id);
+    let order = match connection {
+        DbConnection::Pg(connection) => query
+            .select(Order::as_select())
+            .for_update()
+            .first(connection)
+            .optional(),
+        // SQLite has no row locks, but a write takes the database-wide write lock, which
+        // serializes the read below against every other writer.
+        DbConnection::Sqlite(connection) => {
+            use schema::orders::dsl::status;
+
+            diesel::update(Order::table().find(order_id))
+                .set(status.eq(status))
+                .execute(connection)
+                .context("locking cart")?;
+
+            query
+                .select(Order::as_select())
+                .first(connection)
+                .optional()
+        }
+    }
+    .context("locking cart")?
+    .ok_or(OrderError::NotFound { order_id })?;
+    let status = order_status(&order)?;
+
+    if status != OrderStatus::Cart {
+        return Err(OrderError::NotACart { order_id, status }.into());
+    }
+
+    Ok(order)
+}
+
+/// Lines are priced in book id order, which is also the order stock rows get locked in
+/// when the order is placed.
+fn price_order(
+    order: &Order,
+    at: NaiveDateTime,
+    connection: &mut DbConnection,
+) -> Result<OrderTotal> {
+    let mut lines = get_order_lines(order.id, connection)?
+        .into_iter()
+        .filter_map(|line| line.book_id.map(|book_id| (book_id, line)))
+        .collect::<Vec<(i32, OrderLine)>>();
+    let mut priced_lines = Vec::new();
+
+    lines.sort_by_key(|(book_id, _)| *book_id);
+
+    for (book_id, line) in lines {
+        let unit_price_minor = match line.unit_price_minor {
+            Some(unit_price_minor) => unit_price_minor,
+            None => {
+                current_price(book_id, &order.currency, at, connection)?
+                    .ok_or_else(|| OrderError::MissingPrice {
+                        book_id,
+                        currency: order.currency.clone(),
+                    })?
+                    .amount_minor
+            }
+        };
+
+        priced_lines.push(PricedLine {
+            book_id,
+            quantity: line.quantity,
+            unit_price_minor,
+            line_total_minor: unit_price_minor * i64::from(line.quantity),
+        });
+    }
+
+    Ok(OrderTotal {
+        currency: order.currency.clone(),
+        total_minor: priced_lines.iter().map(|line| line.line_total_minor).sum(),
+        lines: priced_lines,
+    })
+}
@@ -0,0 +1,142 @@
+use chrono::NaiveDateTime;
+use diesel::{associations::HasTable, dsl::min, prelude::*};
+use eyre::{Context, Result};
+
+use crate::{
+    connect::DbConnection,
+    instrumentation::{query_span, record_rows},
+    models::{BookPrice, NewBookPrice},
+    schema,
+    validation::{validate_currency, ValidationError, ValidationReason},
+};
+
+/// Starts charging `amount_minor` at `effective_from`. The price that applied at that moment
+/// is closed there, and the new one runs until the next scheduled price starts, so the
+/// history keeps every earlier price. Setting a price for a moment that already starts a
+/// price replaces its amount.
+#[query_span]
+pub fn set_price(
+    book_id: i32,
+    currency: &str,
+    amount_minor: i64,
+    effective_from: NaiveDateTime,
+    connection: &mut DbConnection,
+) -> Result<BookPrice> {
+    use schema::book_prices::dsl::{
+        amount_minor as price_amount, book_id as price_book_id, book_prices,
+        currency as price_currency, effective_from as price_from, effective_to as price_to,
+    };
+
+    let currency = validate_currency("currency", currency)?;
+
+    if amount_minor < 0 {
+        return Err(ValidationError {
+            field: "amount_minor",
+            reason: ValidationReason::Invalid {
+                expected: "zero or a positive number",
+            },
+        }
+        .into());
+    }
+
+    connection.transaction(|connection| {
+        let prices_for_book = book_prices
+            .filter(price_book_id.eq(book_id))
+            .filter(price_currency.eq(&currency));
+        let existing = prices_for_book
+            .filter(price_from.eq(effective_from))
+            .select(price_amount)
+            .first::<i64>(connection)
+            .optional()
+            .context("getting price starting at the same time")?;
+
+        if existing.is_some() {
+            let price = diesel::update(prices_for_book.filter(price_from.eq(effective_from)))
+                .set(price_amount.eq(amount_minor))
+                .returning(schema::book_prices::all_columns)
+                .get_result(connection)
+                .context("replacing price")?;
+
+            record_rows(1);
+
+            return Ok(price);
+        }
+
+        let closed_rows = diesel::update(
+            prices_for_book
+                .filter(price_from.lt(effective_from))
+                .filter(price_to.is_null().or(price_to.gt(effective_from))),
+        )
+        .set(price_to.eq(effective_from))
+        .execute(connection)
+        .context("closing previous price")?;
+        let next_from = prices_for_book
+            .filter(price_from.gt(effective_from))
+            .select(min(price_from))
+            .first::<Option<NaiveDateTime>>(connection)
+            .context("getting next scheduled price")?;
+        let price = NewBookPrice {
+            book_id,
+            currency: &currency,
+            amount_minor,
+            effective_from,
+            effective_to: next_from,
+        }
+        .insert_into(BookPrice::table())
+        .returning(schema::book_prices::all_columns)
+        .get_result(connection)
+        .context("adding price")?;
+
+        record_rows(closed_rows + 1);
+
+        Ok(price)
+    })
+}
+
+#[query_span]
+pub fn current_price(
+    book_id: i32,
+    currency: &str,
+    at: NaiveDateTime,
+    connection: &mut DbConnection,
+) -> Result<Option<BookPrice>> {
+    use schema::book_prices::dsl::{
+        book_id as price_book_id, book_prices, currency as price_currency,
+        effective_from as price_from, effective_to as price_to,
+    };
+
+    let currency = validate_currency("currency", currency)?;
+    let price = book_prices
+        .filter(price_book_id.eq(book_id))
+        .filter(price_currency.eq(currency))
+        .filter(price_from.le(at))
+        .filter(price_to.is_null().or(price_to.gt(at)))
+        .order(price_from.desc())
+        .select(BookPrice::as_select())
+        .first(connection)
+        .optional()
+        .context("getting current price")?;
+
+    record_rows(price.iter().count());
+
+    Ok(price)
+}
+
+/// Every price the book has had or is scheduled to have, by currency and start.
+#[query_span]
+pub fn get_price_history(book_id: i32, connection: &mut DbConnection) -> Result<Vec<BookPrice>> {
+    use schema::book_prices::dsl::{
+        book_id as price_book_id, book_prices, currency, effective_from,
+    };
+
+    let prices = book_prices
+        .filter(price_book_id.eq(book_id))
+        .order((currency, effective_from))
+        .select(BookPrice::as_select())
+        .load(connection)
+        .context("getting price history")?;
+
+    record_rows(prices.len());
+
+    Ok(prices)
+}
@@ -0,0 +1,299 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+};
+
+use chrono::Utc;
+use diesel::{
+    associations::HasTable,
+    dsl::{count_star, sql, sum},
+    prelude::*,
+    sql_types::Double,
+};
+use eyre::{Context, Result};
+
+use crate::{
+    connect::DbConnection,
+    instrumentation::{query_span, record_rows},
+    models::{BookWithAuthors, NewReview, RatingSummary, Review, TopRatedBook},
+    queries::{book_author_queries::get_book_with_authors, book_queries::get_books_by_ids},
+    schema,
+    validation::{validate_count, validate_rating, validate_review_body},
+};
+
+/// Returned (inside the `eyre::Report`) when a customer reviews a book a second time. The
+/// existing review should be updated instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateReview {
+    pub book_id: i32,
+    pub customer_id: i32,
+    pub review_id: i32,
+}
+
+impl Display for DuplicateReview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "customer {} already reviewed book {} in review {}",
+            self.customer_id, self.book_id, self.review_id
+        )
+    }
+}
+
+impl std::error::Error for DuplicateReview {}
+
+#[query_span(skip(connection, body))]
+pub fn create_review(
+    book_id: i32,
+    customer_id: i32,
+    rating: i32,
+    body: &str,
+    connection: &mut DbConnection,
+) -> Result<i32> {
+    use schema::reviews::dsl::{
+        book_id as review_book_id, customer_id as review_customer_id, id, reviews,
+    };
+
+    let rating = validate_rating("rating", rating)?;
+    let body = validate_review_body("body", body)?;
+
+    connection.transaction(|connection| {
+        let existing = reviews
+            .filter(review_book_id.eq(book_id))
+            .filter(review_customer_id.eq(customer_id))
+            .select(id)
+            .first::<i32>(connection)
+            .optional()
+            .context("getting existing review")?;
+
+        if let Some(review_id) = existing {
+            return Err(DuplicateReview {
+                book_id,
+                customer_id,
+                review_id,
+            }
+            .into());
+        }
+
+        let now = Utc::now().naive_utc();
+        let review_id = NewReview {
+            book_id,
+            customer_id,
+            rating,
+            body: &body,
+            created_at: now,
+            updated_at: now,
+        }
+        .insert_into(Review::table())
+        .returning(id)
+        .get_result(connection)
+        .context("creating review")?;
+
+        record_rows(1);
+
+        Ok(review_id)
+    })
+}
+
+#[query_span]
+pub fn get_review_by_id(id: i32, connection: &mut DbConnection) -> Result<Option<Review>> {
+    let review = Review::table()
+        .find(id)
+        .select(Review::as_select())
+        .first(connection)
+        .optional()
+        .context("getting review by id")?;
+
+    record_rows(review.iter().count());
+
+    Ok(review)
+}
+
+/// Newest reviews first.
+#[query_span]
+pub fn get_reviews_for_book(book_id: i32, connection: &mut DbConnection) -> Result<Vec<Review>> {
+    use schema::reviews::dsl::{book_id as review_book_id, created_at, id, reviews};
+
+    let book_reviews = reviews
+        .filter(review_book_id.eq(book_id))
+        .order((created_at.desc(), id.desc()))
+        .select(Review::as_select())
+        .load(connection)
+        .context("getting reviews for book")?;
+
+    record_rows(book_reviews.len());
+
+    Ok(book_reviews)
+}
+
+#[query_span(skip(connection, new_body))]
+pub fn update_review(
+    id: i32,
+    new_rating: i32,
+    new_body: &str,
+    connection: &mut DbConnection,
+) -> Result<()> {
+    use schema::reviews::dsl::{body, rating, reviews, updated_at};
+
+    let new_rating = validate_rating("rating", new_rating)?;
+    let new_body = validate_review_body("body", new_body)?;
+    let updated_rows = diesel::update(reviews.find(id))
+        .set((
+            rating.eq(new_rating),
+            body.eq(new_body),
+            updated_at.eq(Utc::now().naive_utc()),
+        ))
+        .execute(connection)
+        .context("updating review")?;
+
+    record_rows(updated_rows);
+
+    Ok(())
+}
+
+#[query_span]
+pub fn delete_review(id: i32, connection: &mut DbConnection) -> Result<()> {
+    use schema::reviews::dsl::reviews;
+
+    let deleted_rows = diesel::delete(reviews.find(id))
+        .execute(connection)
+        .context("deleting review")?;
+
+    record_rows(deleted_rows);
+
+    Ok(())
+}
+
+/// Review count and average rating for each of the books. Books without reviews get a
+/// summary with a count of zero.
+#[query_span(skip_all)]
+pub fn get_rating_summaries(
+    book_ids: &[i32],
+    connection: &mut DbConnection,
+) -> Result<HashMap<i32, RatingSummary>> {
+    use schema::reviews::dsl::{book_id, rating, reviews};
+
+    let totals: Vec<(i32, i64, Option<i64>)> = reviews
+        .filter(book_id.eq_any(book_ids))
+        .group_by(book_id)
+        .select((book_id, count_star(), sum(rating)))
+        .load(connection)
+        .context("getting rating summaries")?;
+
+    record_rows(totals.len());
+
+    let mut summaries = book_ids
+        .iter()
+        .map(|id| (*id, RatingSummary::default()))
+        .collect::<HashMap<i32, RatingSummary>>();
+
+    for (id, review_count, rating_total) in totals {
+        summaries.insert(id, summary(review_count, rating_total.unwrap_or(0)));
+    }
+
+    Ok(summaries)
+}
+
+/// Fills in the rating of every book, extending what the book loaders return.
+#[query_span(skip_all)]
+pub fn with_ratings(
+    books_with_authors: Vec<BookWithAuthors>,
+    connection: &mut DbConnection,
+) -> Result<Vec<BookWithAuthors>> {
+    let book_ids = books_with_authors
+        .iter()
+        .map(|book_with_authors| book_with_authors.book().id)
+        .collect::<Vec<i32>>();
+    let summaries = get_rating_summaries(&book_ids, connection)?;
+
+    Ok(books_with_authors
+        .into_iter()
+        .map(|book_with_authors| {
+            let rating = summaries
+                .get(&book_with_authors.book().id)
+                .copied()
+                .unwrap_or_default();
+
+            book_with_authors.with_rating(rating)
+        })
+        .collect())
+}
+
+/// `get_book_with_authors` with the book's rating filled in.
+#[query_span]
+pub fn get_book_with_authors_and_rating(
+    book_id: i32,
+    connection: &mut DbConnection,
+) -> Result<Option<BookWithAuthors>> {
+    let Some(book_with_authors) = get_book_with_authors(book_id, connection)? else {
+        return Ok(None);
+    };
+
+    Ok(with_ratings(vec![book_with_authors], connection)?.pop())
+}
+
+/// Books with at least `min_reviews` reviews, best first by Bayesian average: each book's
+/// ratings are blended with `prior_weight` imaginary ratings at the average of every
+/// review, so a handful of five-star reviews does not outrank a long record of fours.
+#[query_span]
+pub fn get_top_rated_books(
+    min_reviews: i32,
+    prior_weight: i32,
+    limit: i64,
+    connection: &mut DbConnection,
+) -> Result<Vec<TopRatedBook>> {
+    use schema::reviews::dsl::{book_id, rating, reviews};
+
+    let min_reviews = validate_count("min_reviews", min_reviews)?;
+    let prior_weight = f64::from(validate_count("prior_weight", prior_weight)?);
+    let (all_count, all_total) = reviews
+        .select((count_star(), sum(rating)))
+        .first::<(i64, Option<i64>)>(connection)
+        .context("getting overall rating")?;
+    let Some(overall_average) = summary(all_count, all_total.unwrap_or(0)).average_rating else {
+        return Ok(Vec::new());
+    };
+    // Written out because SQLite cannot cast to a floating point type; adding the bound
+    // `f64`s makes the division floating point on both backends.
+    let bayesian_average = sql::<Double>("(")
+        .bind::<Double, _>(prior_weight * overall_average)
+        .sql(" + SUM(rating)) / (")
+        .bind::<Double, _>(prior_weight)
+        .sql(" + COUNT(*))");
+    let totals: Vec<(i32, i64, Option<i64>, f64)> = reviews
+        .group_by(book_id)
+        .having(count_star().ge(i64::from(min_reviews)))
+        .select((book_id, count_star(), sum(rating), bayesian_average.clone()))
+        .order((bayesian_average.desc(), count_star().desc(), book_id.asc()))
+        .limit(limit.max(0))
+        .load(connection)
+        .context("getting top rated books")?;
+
+    record_rows(totals.len());
+
+    let book_ids = totals.iter().map(|(id, ..)| *id).collect::<Vec<i32>>();
+    let book_names = get_books_by_ids(&book_ids, connection)?
+        .into_iter()
+        .map(|book| (book.id, book.name))
+        .collect::<HashMap<i32, String>>();
+
+    Ok(totals
+        .into_iter()
+        .filter_map(|(id, review_count, rating_total, bayesian_average)| {
+            Some(TopRatedBook {
+                book_id: id,
+                book_name: book_names.get(&id)?.clone(),
+                review_count,
+                average_rating: rating_total.unwrap_or(0) as f64 / review_count as f64,
+                bayesian_average,
+            })
+        })
+        .collect())
+}
+
+fn summary(review_count: i64, rating_total: i64) -> RatingSummary {
+    RatingSummary {
+        review_count,
+        average_rating: (review_count > 0).then(|| rating_total as f64 / review_count as f64),
+    }
+}
@@ -0,0 +1,264 @@
+use std::fmt::{self, Display};
+
+use diesel::{associations::HasTable, prelude::*};
+use eyre::{Context, Result};
+
+use crate::{
+    connect::DbConnection,
+    instrumentation::{query_span, record_rows},
+    models::{Book, BookSeries, NewSeries, Series, SeriesEntry, SeriesWithBooks},
+    queries::book_author_queries::get_authors_for_books,
+    schema,
+    validation::{validate_name, ValidationError, ValidationReason},
+};
+
+/// Returned (inside the `eyre::Report`) when another book already sits at a position in a
+/// series. Nothing is changed when this happens.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeriesPositionTaken {
+    pub series_id: i32,
+    pub position: f64,
+    pub book_id: i32,
+}
+
+impl Display for SeriesPositionTaken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "position {} in series {} is already taken by book {}",
+            self.position, self.series_id, self.book_id
+        )
+    }
+}
+
+impl std::error::Error for SeriesPositionTaken {}
+
+#[query_span]
+pub fn create_series(name: &str, connection: &mut DbConnection) -> Result<i32> {
+    let name = validate_name("name", name)?;
+    let id = NewSeries { name: &name }
+        .insert_into(Series::table())
+        .returning(schema::series::id)
+        .get_result(connection)
+        .context("creating series")?;
+
+    record_rows(1);
+
+    Ok(id)
+}
+
+#[query_span]
+pub fn get_series_by_id(id: i32, connection: &mut DbConnection) -> Result<Option<Series>> {
+    let series = Series::table()
+        .find(id)
+        .select(Series::as_select())
+        .first(connection)
+        .optional()
+        .context("getting series by id")?;
+
+    record_rows(series.iter().count());
+
+    Ok(series)
+}
+
+/// Places a book in a series, or moves it if it is already part of it.
+#[query_span]
+pub fn add_book_to_series(
+    book_id: i32,
+    series_id: i32,
+    position: f64,
+    connection: &mut DbConnection,
+) -> Result<()> {
+    use schema::book_series::dsl::{
+        book_id as entry_book_id, book_series, position as entry_position,
+        series_id as entry_series_id,
+    };
+
+    if !position.is_finite() || position < 0.0 {
+        return Err(ValidationError {
+            field: "position",
+            reason: ValidationReason::Invalid {
+                expected: "zero or a positive number",
+            },
+        }
+        .into());
+    }
+
+    connection.transaction(|connection| {
+        let occupant = book_series
+            .filter(entry_series_id.eq(series_id))
+            .filter(entry_position.eq(position))
+            .filter(entry_book_id.ne(book_id))
+            .select(entry_book_id)
+            .first::<i32>(connection)
+            .optional()
+            .context("checking series position")?;
+
+        if let Some(occupant) = occupant {
+            return Err(SeriesPositionTaken {
+                series_id,
+                position,
+                book_id: occupant,
+            }
+            .into());
+        }
+
+        let moved_rows = diesel::update(book_series.find((book_id, series_id)))
+            .set(entry_position.eq(position))
+            .execute(connection)
+            .context("moving book in series")?;
+
+        if moved_rows == 0 {
+            BookSeries {
+                book_id,
+                series_id,
+                position,
+            }
+            .insert_into(BookSeries::table())
+            .execute(connection)
+            .context("adding book to series")?;
+        }
+
+        record_rows(1);
+
+        Ok(())
+    })
+}
+
+#[query_span]
+pub fn remove_book_from_series(
+    book_id: i32,
+    series_id: i32,
+    connection: &mut DbConnection,
+) -> Result<()> {
+    let deleted_rows = diesel::delete(BookSeries::table().find((book_id, series_id)))
+        .execute(connection)
+        .context("removing book from series")?;
+
+    record_rows(deleted_rows);
+
+    Ok(())
+}
+
+/// Every series the book belongs to, with its position in each.
+#[query_span]
+pub fn get_series_for_book(
+    book_id: i32,
+    connection: &mut DbConnection,
+) -> Result<Vec<(Series, f64)>> {
+    use schema::{book_series, series};
+
+    let entries = book_series::table
+        .inner_join(series::table)
+        .filter(book_series::book_id.eq(book_id))
+        .order((series::name, series::id))
+        .select((Series::as_select(), book_series::position))
+        .load(connection)
+        .context("getting series for book")?;
+
+    record_rows(entries.len());
+
+    Ok(entries)
+}
+
+/// The series with its books and their authors, lowest position first.
+#[query_span]
+pub fn get_series_reading_order(
+    series_id: i32,
+    connection: &mut DbConnection,
+) -> Result<Option<SeriesWithBooks>> {
+    use schema::{book_series, books};
+
+    let Some(series) = get_series_by_id(series_id, connection)? else {
+        return Ok(None);
+    };
+    let rows: Vec<(f64, Book)> = book_series::table
+        .inner_join(books::table)
+        .filter(book_series::series_id.eq(series_id))
+        .order(book_series::position)
+        .select((book_series::position, Book::as_select()))
+        .load(connection)
+        .context("getting books in series")?;
+
+    record_rows(rows.len());
+
+    let (positions, books): (Vec<f64>, Vec<Book>) = rows.into_iter().unzip();
+    let entries = positions
+        .into_iter()
+        .zip(get_authors_for_books(books, connection)?)
+        .map(|(position, book)| SeriesEntry { position, book })
+        .collect();
+
+    Ok(Some(SeriesWithBooks { series, entries }))
+}
+
+/// The book that follows `book_id` in the series, or `None` at the end of the series or
+/// when the book is not part of it.
+#[query_span]
+pub fn get_next_book_in_series(
+    book_id: i32,
+    series_id: i32,
+    connection: &mut DbConnection,
+) -> Result<Option<Book>> {
+    use schema::{book_series, books};
+
+    let Some(position) = get_position(book_id, series_id, connection)? else {
+        return Ok(None);
+    };
+    let next = book_series::table
+        .inner_join(books::table)
+        .filter(book_series::series_id.eq(series_id))
+        .filter(book_series::position.gt(position))
+        .order(book_series::position)
+        .select(Book::as_select())
+        .first(connection)
+        .optional()
+        .context("getting next book in series")?;
+
+    record_rows(next.iter().count());
+
+    Ok(next)
+}
+
+/// The book that comes before `book_id` in the series, or `None` at the start of the series
+/// or when the book is not part of it.
+#[query_span]
+pub fn get_previous_book_in_series(
+    book_id: i32,
+    series_id: i32,
+    connection: &mut DbConnection,
+) -> Result<Option<Book>> {
+    use schema::{book_series, books};
+
+    let Some(position) = get_position(book_id, series_id, connection)? else {
+        return Ok(None);
+    };
+    let previous = book_series::table
+        .inner_join(books::table)
+        .filter(book_series::series_id.eq(series_id))
+        .filter(book_series::position.lt(position))
+        .order(book_series::position.desc())
+        .select(Book::as_select())
+        .first(connection)
+        .optional()
+        .context("getting previous book in series")?;
+
+    record_rows(previous.iter().count());
+
+    Ok(previous)
+}
+
+fn get_position(
+    book_id: i32,
+    series_id: i32,
+    connection: &mut DbConnection,
+) -> Result<Option<f64>> {
+    use schema::book_series::dsl::{book_series, position};
+
+    book_series
+        .find((book_id, series_id))
+        .select(position)
+        .first(connection)
+        .optional()
+        .context("getting position in series")
+}
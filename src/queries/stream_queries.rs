@@ -0,0 +1,145 @@
+use std::vec::IntoIter;
+
+use chrono::NaiveDateTime;
+use eyre::Result;
+
+use super::{
+    author_queries::get_authors_page,
+    book_author_queries::{get_authors_for_books, get_books_for_authors},
+    book_queries::{get_books_changed_since_page, get_books_page},
+};
+use crate::{
+    connect::DbConnection,
+    models::{Author, AuthorWithBooks, Book, BookWithAuthors},
+    validation::{ValidationError, ValidationReason},
+};
+
+type FetchPage<'a, T> = Box<dyn FnMut(Option<i32>, i64, &mut DbConnection) -> Result<Vec<T>> + 'a>;
+
+/// Yields rows in id order, fetching `batch_size` rows per query after the last id seen. A
+/// `batch_size` below 1 yields a single `ValidationError` instead.
+pub struct KeysetStream<'a, T> {
+    connection: &'a mut DbConnection,
+    batch_size: i64,
+    after_id: Option<i32>,
+    fetch_page: FetchPage<'a, T>,
+    id: fn(&T) -> i32,
+    batch: IntoIter<T>,
+    invalid_batch_size: Option<ValidationError>,
+    finished: bool,
+}
+
+impl<'a, T> KeysetStream<'a, T> {
+    pub fn new(
+        batch_size: i64,
+        fetch_page: impl FnMut(Option<i32>, i64, &mut DbConnection) -> Result<Vec<T>> + 'a,
+        id: fn(&T) -> i32,
+        connection: &'a mut DbConnection,
+    ) -> Self {
+        Self {
+            connection,
+            batch_size,
+            after_id: None,
+            fetch_page: Box::new(fetch_page),
+            id,
+            batch: Vec::new().into_iter(),
+            invalid_batch_size: (batch_size < 1).then_some(ValidationError {
+                field: "batch_size",
+                reason: ValidationReason::Invalid {
+                    expected: "a positive number",
+                },
+            }),
+            finished: false,
+        }
+    }
+}
+
+impl<T> Iterator for KeysetStream<'_, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.invalid_batch_size.take() {
+            self.finished = true;
+            return Some(Err(error.into()));
+        }
+
+        if let Some(item) = self.batch.next() {
+            return Some(Ok(item));
+        }
+
+        if self.finished {
+            return None;
+        }
+
+        let page = match (self.fetch_page)(self.after_id, self.batch_size, self.connection) {
+            Ok(page) => page,
+            Err(error) => {
+                self.finished = true;
+                return Some(Err(error));
+            }
+        };
+
+        self.finished = (page.len() as i64) < self.batch_size;
+        self.after_id = page.last().map(self.id).or(self.after_id);
+        self.batch = page.into_iter();
+        self.batch.next().map(Ok)
+    }
+}
+
+pub fn stream_books(batch_size: i64, connection: &mut DbConnection) -> KeysetStream<'_, Book> {
+    KeysetStream::new(batch_size, get_books_page, |book| book.id, connection)
+}
+
+pub fn stream_authors(batch_size: i64, connection: &mut DbConnection) -> KeysetStream<'_, Author> {
+    KeysetStream::new(batch_size, get_authors_page, |author| author.id, connection)
+}
+
+pub fn stream_books_with_authors(
+    batch_size: i64,
+    connection: &mut DbConnection,
+) -> KeysetStream<'_, BookWithAuthors> {
+    KeysetStream::new(
+        batch_size,
+        |after_id, limit, connection| {
+            let books = get_books_page(after_id, limit, connection)?;
+
+            get_authors_for_books(books, connection)
+        },
+        |book_with_authors| book_with_authors.book().id,
+        connection,
+    )
+}
+
+pub fn stream_authors_with_books(
+    batch_size: i64,
+    connection: &mut DbConnection,
+) -> KeysetStream<'_, AuthorWithBooks> {
+    KeysetStream::new(
+        batch_size,
+        |after_id, limit, connection| {
+            let authors = get_authors_page(after_id, limit, connection)?;
+
+            get_books_for_authors(authors, connection)
+        },
+        |author_with_books| author_with_books.author().id,
+        connection,
+    )
+}
+
+/// Books whose name, credits or identifiers changed at or after `since`, with their authors.
+pub fn stream_books_with_authors_changed_since(
+    since: NaiveDateTime,
+    batch_size: i64,
+    connection: &mut DbConnection,
+) -> KeysetStream<'_, BookWithAuthors> {
+    KeysetStream::new(
+        batch_size,
+        move |after_id, limit, connection| {
+            let books = get_books_changed_since_page(since, after_id, limit, connection)?;
+
+            get_authors_for_books(books, connection)
+        },
+        |book_with_authors| book_with_authors.book().id,
+        connection,
+    )
+}
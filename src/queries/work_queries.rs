@@ -0,0 +1,380 @@
+use std::collections::{HashMap, HashSet};
+
+use diesel::{associations::HasTable, dsl::max, prelude::*};
+use eyre::{Context, Result};
+
+use crate::{
+    connect::DbConnection,
+    instrumentation::{query_span, record_rows},
+    models::{
+        Author, Book, BookCredit, NewBookContributor, NewWork, Work, WorkAuthor, WorkWithEditions,
+    },
+    queries::book_author_queries::{
+        get_authors_for_books, get_work_id, next_credit_position, AUTHOR_ROLE,
+    },
+    schema,
+    validation::{validate_name, validate_role},
+};
+
+#[query_span]
+pub fn create_work(title: &str, connection: &mut DbConnection) -> Result<i32> {
+    let title = validate_name("title", title)?;
+    let id = NewWork { title: &title }
+        .insert_into(Work::table())
+        .returning(schema::works::id)
+        .get_result(connection)
+        .context("creating work")?;
+
+    record_rows(1);
+
+    Ok(id)
+}
+
+#[query_span]
+pub fn get_work_by_id(id: i32, connection: &mut DbConnection) -> Result<Option<Work>> {
+    let work = Work::table()
+        .find(id)
+        .select(Work::as_select())
+        .first(connection)
+        .optional()
+        .context("getting work by id")?;
+
+    record_rows(work.iter().count());
+
+    Ok(work)
+}
+
+#[query_span]
+pub fn get_all_works(connection: &mut DbConnection) -> Result<Vec<Work>> {
+    use schema::works::dsl::{id, works};
+
+    let all_works = works
+        .order(id)
+        .select(Work::as_select())
+        .load(connection)
+        .context("getting all works")?;
+
+    record_rows(all_works.len());
+
+    Ok(all_works)
+}
+
+/// Makes a book an edition of `work_id`. The book's author credits move up to the work,
+/// where every edition shares them; other credits such as translators stay on the book.
+#[query_span]
+pub fn add_edition_to_work(
+    book_id: i32,
+    work_id: i32,
+    connection: &mut DbConnection,
+) -> Result<()> {
+    use schema::book_authors::dsl::{
+        author_id, book_authors, book_id as credit_book_id, position, role,
+    };
+    use schema::books::dsl::{books, work_id as book_work_id};
+
+    connection.transaction(|connection| {
+        let updated_rows = diesel::update(books.find(book_id))
+            .set(book_work_id.eq(work_id))
+            .execute(connection)
+            .context("adding edition to work")?;
+        let book_credits = book_authors
+            .filter(credit_book_id.eq(book_id))
+            .filter(role.eq(AUTHOR_ROLE));
+        let author_ids = book_credits
+            .order((position, author_id))
+            .select(author_id)
+            .load::<i32>(connection)
+            .context("getting edition authors")?;
+        let credited = get_work_author_ids(work_id, connection)?;
+        let first_position = next_work_credit_position(work_id, connection)?;
+        // The edition's authors follow the work's own, in the order the edition had them.
+        let new_credits = author_ids
+            .iter()
+            .filter(|id| !credited.contains(id))
+            .zip(first_position..)
+            .map(|(id, position_on_work)| WorkAuthor {
+                work_id,
+                author_id: *id,
+                position: position_on_work,
+            })
+            .collect::<Vec<WorkAuthor>>();
+
+        for credit in &new_credits {
+            diesel::insert_into(WorkAuthor::table())
+                .values(credit)
+                .execute(connection)
+                .context("moving author credit to work")?;
+        }
+
+        diesel::delete(book_credits)
+            .execute(connection)
+            .context("removing edition author credits")?;
+        record_rows(updated_rows + new_credits.len());
+
+        Ok(())
+    })
+}
+
+/// Makes a book stand on its own again. It keeps the work's authors as its own credits.
+#[query_span]
+pub fn remove_edition_from_work(book_id: i32, connection: &mut DbConnection) -> Result<()> {
+    use schema::book_authors::dsl::{
+        author_id, book_authors, book_id as credit_book_id, position, role,
+    };
+    use schema::books::dsl::{books, work_id as book_work_id};
+
+    connection.transaction(|connection| {
+        let Some(work_id) = get_work_id(book_id, connection)? else {
+            return Ok(());
+        };
+        let already_credited = book_authors
+            .filter(credit_book_id.eq(book_id))
+            .filter(role.eq(AUTHOR_ROLE))
+            .select(author_id)
+            .load::<i32>(connection)
+            .context("getting edition credits")?
+            .into_iter()
+            .collect::<HashSet<i32>>();
+        let new_credits = get_work_author_ids(work_id, connection)?
+            .into_iter()
+            .filter(|id| !already_credited.contains(id))
+            .collect::<Vec<i32>>();
+
+        // The work's authors keep their place ahead of the edition's own credits.
+        diesel::update(book_authors.filter(credit_book_id.eq(book_id)))
+            .set(position.eq(position + new_credits.len() as i32))
+            .execute(connection)
+            .context("making room for work authors")?;
+
+        for (position_on_book, id) in (0..).zip(&new_credits) {
+            NewBookContributor {
+                book_id,
+                author_id: *id,
+                role: AUTHOR_ROLE,
+                position: position_on_book,
+            }
+            .insert_into(book_authors)
+            .execute(connection)
+            .context("copying work author to edition")?;
+        }
+
+        let updated_rows = diesel::update(books.find(book_id))
+            .set(book_work_id.eq(None::<i32>))
+            .execute(connection)
+            .context("removing edition from work")?;
+
+        record_rows(updated_rows + new_credits.len());
+
+        Ok(())
+    })
+}
+
+/// Credits the author on the work, and so on every one of its editions.
+#[query_span]
+pub fn credit_author_on_work(
+    work_id: i32,
+    author_id: i32,
+    connection: &mut DbConnection,
+) -> Result<()> {
+    connection.transaction(|connection| {
+        let inserted_rows = WorkAuthor {
+            work_id,
+            author_id,
+            position: next_work_credit_position(work_id, connection)?,
+        }
+        .insert_into(WorkAuthor::table())
+        .execute(connection)
+        .context("crediting author on work")?;
+
+        record_rows(inserted_rows);
+
+        Ok(())
+    })
+}
+
+/// Removes the author's credit from the work. Credits the author has on single editions
+/// stay.
+#[query_span]
+pub fn remove_author_from_work(
+    work_id: i32,
+    author_id: i32,
+    connection: &mut DbConnection,
+) -> Result<bool> {
+    let deleted_rows = diesel::delete(WorkAuthor::table().find((work_id, author_id)))
+        .execute(connection)
+        .context("removing author credit from work")?;
+
+    record_rows(deleted_rows);
+
+    Ok(deleted_rows > 0)
+}
+
+/// Credits someone on this edition only, for example as `translator`.
+#[query_span]
+pub fn add_edition_contributor(
+    book_id: i32,
+    author_id: i32,
+    role: &str,
+    connection: &mut DbConnection,
+) -> Result<()> {
+    let role = validate_role("role", role)?;
+
+    connection.transaction(|connection| {
+        let inserted_rows = NewBookContributor {
+            book_id,
+            author_id,
+            role: &role,
+            position: next_credit_position(book_id, connection)?,
+        }
+        .insert_into(schema::book_authors::table)
+        .execute(connection)
+        .context("adding edition contributor")?;
+
+        record_rows(inserted_rows);
+
+        Ok(())
+    })
+}
+
+/// Everyone credited on the book: the work's authors, then the edition's authors, then the
+/// edition's other contributors by role.
+#[query_span]
+pub fn get_book_credits(book_id: i32, connection: &mut DbConnection) -> Result<Vec<BookCredit>> {
+    use schema::{authors, book_authors, work_authors};
+
+    let work_authors: Vec<Author> = match get_work_id(book_id, connection)? {
+        Some(work_id) => work_authors::table
+            .inner_join(authors::table)
+            .filter(work_authors::work_id.eq(work_id))
+            .order((work_authors::position, authors::id))
+            .select(Author::as_select())
+            .load(connection)
+            .context("getting work authors")?,
+        None => Vec::new(),
+    };
+    let mut edition_credits: Vec<(String, Author)> = book_authors::table
+        .inner_join(authors::table)
+        .filter(book_authors::book_id.eq(book_id))
+        .order((book_authors::role, book_authors::position, authors::id))
+        .select((book_authors::role, Author::as_select()))
+        .load(connection)
+        .context("getting edition credits")?;
+
+    edition_credits.sort_by_key(|(role, _)| role != AUTHOR_ROLE);
+
+    let credits = work_authors
+        .into_iter()
+        .map(|author| BookCredit {
+            author,
+            role: AUTHOR_ROLE.to_owned(),
+            from_work: true,
+        })
+        .chain(
+            edition_credits
+                .into_iter()
+                .map(|(role, author)| BookCredit {
+                    author,
+                    role,
+                    from_work: false,
+                }),
+        )
+        .collect::<Vec<BookCredit>>();
+
+    record_rows(credits.len());
+
+    Ok(credits)
+}
+
+#[query_span]
+pub fn get_work_with_editions(
+    work_id: i32,
+    connection: &mut DbConnection,
+) -> Result<Option<WorkWithEditions>> {
+    let Some(work) = get_work_by_id(work_id, connection)? else {
+        return Ok(None);
+    };
+
+    Ok(get_editions_for_works(vec![work], connection)?.pop())
+}
+
+#[query_span]
+pub fn get_all_works_with_editions(connection: &mut DbConnection) -> Result<Vec<WorkWithEditions>> {
+    let all_works = get_all_works(connection)?;
+
+    get_editions_for_works(all_works, connection)
+}
+
+/// Each work with its authors and its editions, oldest edition first, with each edition's
+/// authors loaded as `get_book_with_authors` would.
+#[query_span(skip_all)]
+pub fn get_editions_for_works(
+    works: Vec<Work>,
+    connection: &mut DbConnection,
+) -> Result<Vec<WorkWithEditions>> {
+    use schema::{authors, books, work_authors};
+
+    let work_ids = works.iter().map(|work| work.id).collect::<Vec<i32>>();
+    let credits: Vec<(WorkAuthor, Author)> = WorkAuthor::belonging_to(&works)
+        .inner_join(authors::table)
+        .order((work_authors::position, authors::id))
+        .select((WorkAuthor::as_select(), Author::as_select()))
+        .load(connection)
+        .context("getting authors for works")?;
+    let editions: Vec<(Option<i32>, Book)> = books::table
+        .filter(books::work_id.eq_any(&work_ids))
+        .order(books::id)
+        .select((books::work_id, Book::as_select()))
+        .load(connection)
+        .context("getting editions for works")?;
+
+    record_rows(credits.len() + editions.len());
+
+    let (edition_work_ids, edition_books): (Vec<Option<i32>>, Vec<Book>) =
+        editions.into_iter().unzip();
+    let mut editions_by_work = HashMap::<i32, Vec<_>>::new();
+
+    for (work_id, edition) in edition_work_ids
+        .into_iter()
+        .zip(get_authors_for_books(edition_books, connection)?)
+    {
+        if let Some(work_id) = work_id {
+            editions_by_work.entry(work_id).or_default().push(edition);
+        }
+    }
+
+    Ok(credits
+        .grouped_by(&works)
+        .into_iter()
+        .zip(works)
+        .map(|(credits, work)| WorkWithEditions {
+            editions: editions_by_work.remove(&work.id).unwrap_or_default(),
+            authors: credits.into_iter().map(|(_, author)| author).collect(),
+            work,
+        })
+        .collect())
+}
+
+/// The work's authors in credit order.
+fn get_work_author_ids(work_id: i32, connection: &mut DbConnection) -> Result<Vec<i32>> {
+    use schema::work_authors::dsl::{author_id, position, work_authors, work_id as credit_work_id};
+
+    work_authors
+        .filter(credit_work_id.eq(work_id))
+        .order((position, author_id))
+        .select(author_id)
+        .load(connection)
+        .context("getting work authors")
+}
+
+/// The position after the work's last credit.
+fn next_work_credit_position(work_id: i32, connection: &mut DbConnection) -> Result<i32> {
+    use schema::work_authors::dsl::{position, work_authors, work_id as credit_work_id};
+
+    let last = work_authors
+        .filter(credit_work_id.eq(work_id))
+        .select(max(position))
+        .first::<Option<i32>>(connection)
+        .context("getting last work credit position")?;
+
+    Ok(last.map_or(0, |last| last + 1))
+}
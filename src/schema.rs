@@ -1,17 +1,97 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    author_identifiers (scheme, value) {
+        author_id -> Int4,
+        #[max_length = 32]
+        scheme -> Varchar,
+        #[max_length = 64]
+        value -> Varchar,
+    }
+}
+
+diesel::table! {
+    author_names (id) {
+        id -> Int4,
+        author_id -> Int4,
+        #[max_length = 255]
+        name -> Varchar,
+        #[max_length = 16]
+        kind -> Varchar,
+    }
+}
+
 diesel::table! {
     authors (id) {
         id -> Int4,
         #[max_length = 255]
         name -> Varchar,
+        #[max_length = 16]
+        birth_date -> Nullable<Varchar>,
+        #[max_length = 16]
+        death_date -> Nullable<Varchar>,
+        #[max_length = 2]
+        nationality -> Nullable<Varchar>,
+        biography -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    book_author_names (book_id, author_id) {
+        book_id -> Int4,
+        author_id -> Int4,
+        author_name_id -> Int4,
     }
 }
 
 diesel::table! {
-    book_authors (author_id, book_id) {
+    book_authors (author_id, book_id, role) {
         author_id -> Int4,
         book_id -> Int4,
+        #[max_length = 32]
+        role -> Varchar,
+        position -> Int4,
+    }
+}
+
+diesel::table! {
+    book_identifiers (scheme, value) {
+        book_id -> Int4,
+        #[max_length = 32]
+        scheme -> Varchar,
+        #[max_length = 64]
+        value -> Varchar,
+    }
+}
+
+diesel::table! {
+    book_prices (id) {
+        id -> Int4,
+        book_id -> Int4,
+        #[max_length = 3]
+        currency -> Varchar,
+        amount_minor -> Int8,
+        effective_from -> Timestamp,
+        effective_to -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    book_series (book_id, series_id) {
+        book_id -> Int4,
+        series_id -> Int4,
+        position -> Float8,
+    }
+}
+
+diesel::table! {
+    book_titles (book_id, language) {
+        book_id -> Int4,
+        #[max_length = 35]
+        language -> Varchar,
+        #[max_length = 255]
+        title -> Varchar,
+        is_original -> Bool,
     }
 }
 
@@ -20,14 +100,197 @@ diesel::table! {
         id -> Int4,
         #[max_length = 255]
         name -> Varchar,
+        updated_at -> Timestamp,
+        work_id -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    copies (id) {
+        id -> Int4,
+        book_id -> Int4,
+        #[max_length = 64]
+        barcode -> Varchar,
+    }
+}
+
+diesel::table! {
+    customers (id) {
+        id -> Int4,
+        #[max_length = 255]
+        name -> Varchar,
+        #[max_length = 255]
+        email -> Varchar,
+    }
+}
+
+diesel::table! {
+    holds (id) {
+        id -> Int4,
+        book_id -> Int4,
+        member_id -> Int4,
+        #[max_length = 16]
+        status -> Varchar,
+        copy_id -> Nullable<Int4>,
+        placed_at -> Timestamp,
+        ready_at -> Nullable<Timestamp>,
+        closed_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    inventory (book_id, location_id) {
+        book_id -> Int4,
+        location_id -> Int4,
+        quantity -> Int4,
+        reserved -> Int4,
+        reorder_threshold -> Int4,
+    }
+}
+
+diesel::table! {
+    loans (id) {
+        id -> Int4,
+        copy_id -> Int4,
+        member_id -> Int4,
+        checked_out_at -> Timestamp,
+        due_at -> Timestamp,
+        returned_at -> Nullable<Timestamp>,
+        renewals -> Int4,
+    }
+}
+
+diesel::table! {
+    locations (id) {
+        id -> Int4,
+        #[max_length = 255]
+        name -> Varchar,
+    }
+}
+
+diesel::table! {
+    members (id) {
+        id -> Int4,
+        #[max_length = 255]
+        name -> Varchar,
+        #[max_length = 255]
+        email -> Varchar,
+    }
+}
+
+diesel::table! {
+    order_lines (id) {
+        id -> Int4,
+        order_id -> Int4,
+        book_id -> Nullable<Int4>,
+        quantity -> Int4,
+        unit_price_minor -> Nullable<Int8>,
+    }
+}
+
+diesel::table! {
+    orders (id) {
+        id -> Int4,
+        customer_id -> Int4,
+        #[max_length = 16]
+        status -> Varchar,
+        #[max_length = 3]
+        currency -> Varchar,
+        location_id -> Nullable<Int4>,
+        total_minor -> Nullable<Int8>,
+        created_at -> Timestamp,
+        placed_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    reviews (id) {
+        id -> Int4,
+        book_id -> Int4,
+        customer_id -> Int4,
+        rating -> Int4,
+        body -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    series (id) {
+        id -> Int4,
+        #[max_length = 255]
+        name -> Varchar,
+    }
+}
+
+diesel::table! {
+    work_authors (work_id, author_id) {
+        work_id -> Int4,
+        author_id -> Int4,
+        position -> Int4,
+    }
+}
+
+diesel::table! {
+    works (id) {
+        id -> Int4,
+        #[max_length = 255]
+        title -> Varchar,
     }
 }
 
+diesel::joinable!(author_identifiers -> authors (author_id));
+diesel::joinable!(author_names -> authors (author_id));
+diesel::joinable!(book_author_names -> author_names (author_name_id));
+diesel::joinable!(book_author_names -> authors (author_id));
+diesel::joinable!(book_author_names -> books (book_id));
 diesel::joinable!(book_authors -> authors (author_id));
 diesel::joinable!(book_authors -> books (book_id));
+diesel::joinable!(book_identifiers -> books (book_id));
+diesel::joinable!(book_prices -> books (book_id));
+diesel::joinable!(book_series -> books (book_id));
+diesel::joinable!(book_series -> series (series_id));
+diesel::joinable!(book_titles -> books (book_id));
+diesel::joinable!(books -> works (work_id));
+diesel::joinable!(copies -> books (book_id));
+diesel::joinable!(holds -> books (book_id));
+diesel::joinable!(holds -> copies (copy_id));
+diesel::joinable!(holds -> members (member_id));
+diesel::joinable!(inventory -> books (book_id));
+diesel::joinable!(inventory -> locations (location_id));
+diesel::joinable!(loans -> copies (copy_id));
+diesel::joinable!(loans -> members (member_id));
+diesel::joinable!(order_lines -> books (book_id));
+diesel::joinable!(order_lines -> orders (order_id));
+diesel::joinable!(orders -> customers (customer_id));
+diesel::joinable!(orders -> locations (location_id));
+diesel::joinable!(reviews -> books (book_id));
+diesel::joinable!(reviews -> customers (customer_id));
+diesel::joinable!(work_authors -> authors (author_id));
+diesel::joinable!(work_authors -> works (work_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    author_identifiers,
+    author_names,
     authors,
+    book_author_names,
     book_authors,
+    book_identifiers,
+    book_prices,
+    book_series,
+    book_titles,
     books,
+    copies,
+    customers,
+    holds,
+    inventory,
+    loans,
+    locations,
+    members,
+    order_lines,
+    orders,
+    reviews,
+    series,
+    work_authors,
+    works,
 );
@@ -0,0 +1,625 @@
+use crate::models::{PartialDate, MAX_NAME_LENGTH};
+use std::fmt::{self, Display};
+use unicode_normalization::UnicodeNormalization;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationReason {
+    Empty,
+    TooLong { max: usize, actual: usize },
+    Invalid { expected: &'static str },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    pub field: &'static str,
+    pub reason: ValidationReason,
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.reason {
+            ValidationReason::Empty => write!(f, "{} must not be empty", self.field),
+            ValidationReason::TooLong { max, actual } => write!(
+                f,
+                "{} must be at most {max} characters but was {actual}",
+                self.field
+            ),
+            ValidationReason::Invalid { expected } => {
+                write!(f, "{} must be {expected}", self.field)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+pub fn normalize(value: &str) -> String {
+    value
+        .nfc()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+pub fn validate_name(field: &'static str, value: &str) -> Result<String, ValidationError> {
+    let name = normalize(value);
+    let length = name.chars().count();
+
+    if length == 0 {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::Empty,
+        });
+    }
+
+    if length > MAX_NAME_LENGTH {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::TooLong {
+                max: MAX_NAME_LENGTH,
+                actual: length,
+            },
+        });
+    }
+
+    Ok(name)
+}
+
+pub fn validate_count(field: &'static str, value: i32) -> Result<i32, ValidationError> {
+    if value < 1 {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::Invalid {
+                expected: "a positive number",
+            },
+        });
+    }
+
+    Ok(value)
+}
+
+/// Checks the overall `local@domain` shape only and returns the address in lower case.
+pub fn validate_email(field: &'static str, value: &str) -> Result<String, ValidationError> {
+    let email = value.trim().to_lowercase();
+
+    if email.is_empty() {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::Empty,
+        });
+    }
+
+    let valid = match email.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !domain.contains('@')
+                && !email.contains(char::is_whitespace)
+        }
+        None => false,
+    };
+
+    if !valid || email.chars().count() > MAX_NAME_LENGTH {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::Invalid {
+                expected: "an email address",
+            },
+        });
+    }
+
+    Ok(email)
+}
+
+/// Accepts an absolute `http://` or `https://` URL and returns it without a trailing slash,
+/// ready to have paths appended.
+pub fn validate_base_url(field: &'static str, value: &str) -> Result<String, ValidationError> {
+    let url = value.trim().trim_end_matches('/');
+
+    if url.is_empty() {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::Empty,
+        });
+    }
+
+    let host = ["https://", "http://"]
+        .into_iter()
+        .find_map(|scheme| url.strip_prefix(scheme))
+        .unwrap_or_default();
+
+    if host.is_empty()
+        || host.starts_with('/')
+        || host.contains(['?', '#'])
+        || url.contains(char::is_whitespace)
+    {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::Invalid {
+                expected: "an absolute http or https URL",
+            },
+        });
+    }
+
+    Ok(url.to_owned())
+}
+
+/// Accepts a three-letter ISO 4217 code in any case and returns it in upper case.
+pub fn validate_currency(field: &'static str, value: &str) -> Result<String, ValidationError> {
+    let code = value.trim().to_ascii_uppercase();
+
+    if code.is_empty() {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::Empty,
+        });
+    }
+
+    if code.len() != 3 || !code.chars().all(|character| character.is_ascii_uppercase()) {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::Invalid {
+                expected: "a three-letter ISO 4217 currency code",
+            },
+        });
+    }
+
+    Ok(code)
+}
+
+pub const MAX_BARCODE_LENGTH: usize = 64;
+
+/// Barcodes are printed labels, so any whitespace in them is a typo and is refused.
+pub fn validate_barcode(field: &'static str, value: &str) -> Result<String, ValidationError> {
+    let barcode = value.trim();
+    let length = barcode.chars().count();
+
+    if length == 0 {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::Empty,
+        });
+    }
+
+    if length > MAX_BARCODE_LENGTH {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::TooLong {
+                max: MAX_BARCODE_LENGTH,
+                actual: length,
+            },
+        });
+    }
+
+    if barcode.contains(char::is_whitespace) {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::Invalid {
+                expected: "a barcode without spaces",
+            },
+        });
+    }
+
+    Ok(barcode.to_owned())
+}
+
+pub fn validate_rating(field: &'static str, value: i32) -> Result<i32, ValidationError> {
+    if !(1..=5).contains(&value) {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::Invalid {
+                expected: "a rating from 1 to 5",
+            },
+        });
+    }
+
+    Ok(value)
+}
+
+pub const MAX_REVIEW_LENGTH: usize = 10_000;
+
+/// Review text may be empty for a rating on its own. Line breaks are kept.
+pub fn validate_review_body(field: &'static str, value: &str) -> Result<String, ValidationError> {
+    let body = value.nfc().collect::<String>().trim().to_owned();
+    let length = body.chars().count();
+
+    if length > MAX_REVIEW_LENGTH {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::TooLong {
+                max: MAX_REVIEW_LENGTH,
+                actual: length,
+            },
+        });
+    }
+
+    Ok(body)
+}
+
+pub const MAX_ROLE_LENGTH: usize = 32;
+
+/// Credit roles such as `translator` are stored in lower case.
+pub fn validate_role(field: &'static str, value: &str) -> Result<String, ValidationError> {
+    let role = normalize(value).to_lowercase();
+    let length = role.chars().count();
+
+    if length == 0 {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::Empty,
+        });
+    }
+
+    if length > MAX_ROLE_LENGTH {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::TooLong {
+                max: MAX_ROLE_LENGTH,
+                actual: length,
+            },
+        });
+    }
+
+    Ok(role)
+}
+
+pub const MAX_LANGUAGE_TAG_LENGTH: usize = 35;
+
+/// Accepts a BCP 47 language tag such as `de`, `de_at` or `zh-hant-TW` and returns it in
+/// its conventional case: `de-AT`, `zh-Hant-TW`. Only the shape of the tag is checked.
+pub fn validate_language_tag(field: &'static str, value: &str) -> Result<String, ValidationError> {
+    let tag = value.trim().replace('_', "-");
+
+    if tag.is_empty() {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::Empty,
+        });
+    }
+
+    let length = tag.chars().count();
+
+    if length > MAX_LANGUAGE_TAG_LENGTH {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::TooLong {
+                max: MAX_LANGUAGE_TAG_LENGTH,
+                actual: length,
+            },
+        });
+    }
+
+    let alphabetic = |subtag: &str| {
+        subtag
+            .chars()
+            .all(|character| character.is_ascii_alphabetic())
+    };
+    let subtags = tag.split('-').collect::<Vec<&str>>();
+    let language_valid =
+        (2..=8).contains(&subtags[0].len()) && subtags[0].len() != 4 && alphabetic(subtags[0]);
+    let rest_valid = subtags[1..].iter().all(|subtag| {
+        (1..=8).contains(&subtag.len())
+            && subtag
+                .chars()
+                .all(|character| character.is_ascii_alphanumeric())
+    });
+
+    if !language_valid || !rest_valid {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::Invalid {
+                expected: "a BCP 47 language tag",
+            },
+        });
+    }
+
+    // Scripts are title case and regions upper case, up to the first extension or private use
+    // singleton; everything else is lower case.
+    let mut in_extension = false;
+    let canonical = subtags
+        .iter()
+        .enumerate()
+        .map(|(index, subtag)| {
+            let lower = subtag.to_ascii_lowercase();
+
+            in_extension |= index > 0 && subtag.len() == 1;
+
+            if index == 0 || in_extension {
+                lower
+            } else if subtag.len() == 2 && alphabetic(subtag) {
+                lower.to_ascii_uppercase()
+            } else if subtag.len() == 4 && alphabetic(subtag) {
+                lower[..1].to_ascii_uppercase() + &lower[1..]
+            } else {
+                lower
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("-");
+
+    Ok(canonical)
+}
+
+/// Accepts ISBN-10 or ISBN-13 with or without hyphens and returns the ISBN-13 digits.
+pub fn validate_isbn(field: &'static str, value: &str) -> Result<String, ValidationError> {
+    let invalid = || ValidationError {
+        field,
+        reason: ValidationReason::Invalid {
+            expected: "a valid ISBN-10 or ISBN-13",
+        },
+    };
+    let characters = value
+        .chars()
+        .filter(|character| !matches!(character, '-' | ' '))
+        .map(|character| character.to_ascii_uppercase())
+        .collect::<Vec<char>>();
+
+    if characters.is_empty() {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::Empty,
+        });
+    }
+
+    let digits = characters
+        .iter()
+        .enumerate()
+        .map(|(index, character)| match character {
+            'X' if index == 9 && characters.len() == 10 => Some(10),
+            _ => character.to_digit(10),
+        })
+        .collect::<Option<Vec<u32>>>()
+        .ok_or_else(invalid)?;
+
+    match digits.len() {
+        10 => {
+            let sum = digits
+                .iter()
+                .zip((1..=10).rev())
+                .map(|(digit, weight)| digit * weight)
+                .sum::<u32>();
+
+            if sum % 11 != 0 {
+                return Err(invalid());
+            }
+
+            let mut isbn13 = vec![9, 7, 8];
+
+            isbn13.extend(&digits[..9]);
+            isbn13.push(isbn13_check_digit(&isbn13));
+
+            Ok(isbn13.iter().map(u32::to_string).collect())
+        }
+        13 if digits.starts_with(&[9, 7, 8]) || digits.starts_with(&[9, 7, 9]) => {
+            if isbn13_check_digit(&digits[..12]) != digits[12] {
+                return Err(invalid());
+            }
+
+            Ok(digits.iter().map(u32::to_string).collect())
+        }
+        _ => Err(invalid()),
+    }
+}
+
+fn isbn13_check_digit(digits: &[u32]) -> u32 {
+    let sum = digits
+        .iter()
+        .zip([1, 3].into_iter().cycle())
+        .map(|(digit, weight)| digit * weight)
+        .sum::<u32>();
+
+    (10 - sum % 10) % 10
+}
+
+/// Accepts a full, partial or approximate date such as `1835-11-30`, `1835` or `c. 1340` and
+/// returns it in the stored `PartialDate` form.
+pub fn validate_partial_date(field: &'static str, value: &str) -> Result<String, ValidationError> {
+    if value.trim().is_empty() {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::Empty,
+        });
+    }
+
+    PartialDate::parse(value)
+        .map(|date| date.to_string())
+        .ok_or(ValidationError {
+            field,
+            reason: ValidationReason::Invalid {
+                expected: "a date such as 1835-11-30, 1835-11, 1835 or circa 1835",
+            },
+        })
+}
+
+/// Accepts a two-letter ISO 3166-1 country code in any case and returns it in upper case.
+pub fn validate_country_code(field: &'static str, value: &str) -> Result<String, ValidationError> {
+    let code = value.trim().to_ascii_uppercase();
+
+    if code.is_empty() {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::Empty,
+        });
+    }
+
+    if code.len() != 2 || !code.chars().all(|character| character.is_ascii_uppercase()) {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::Invalid {
+                expected: "a two-letter ISO 3166-1 country code",
+            },
+        });
+    }
+
+    Ok(code)
+}
+
+pub const MAX_BIOGRAPHY_LENGTH: usize = 10_000;
+
+/// Biographies keep their line breaks.
+pub fn validate_biography(field: &'static str, value: &str) -> Result<String, ValidationError> {
+    let biography = value.nfc().collect::<String>().trim().to_owned();
+    let length = biography.chars().count();
+
+    if length == 0 {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::Empty,
+        });
+    }
+
+    if length > MAX_BIOGRAPHY_LENGTH {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::TooLong {
+                max: MAX_BIOGRAPHY_LENGTH,
+                actual: length,
+            },
+        });
+    }
+
+    Ok(biography)
+}
+
+/// Accepts an ORCID iD with or without hyphens or the `https://orcid.org/` prefix and returns
+/// it as `0000-0002-1825-0097`.
+pub fn validate_orcid(field: &'static str, value: &str) -> Result<String, ValidationError> {
+    let value = value.trim();
+    let value = ["https://orcid.org/", "http://orcid.org/", "orcid.org/"]
+        .into_iter()
+        .find_map(|prefix| value.strip_prefix(prefix))
+        .unwrap_or(value);
+    let characters = mod11_2_characters(field, value, "a valid ORCID iD")?;
+
+    Ok(characters
+        .chunks(4)
+        .map(|chunk| chunk.iter().collect::<String>())
+        .collect::<Vec<String>>()
+        .join("-"))
+}
+
+/// Accepts an ISNI with or without spaces and returns its sixteen characters.
+pub fn validate_isni(field: &'static str, value: &str) -> Result<String, ValidationError> {
+    let value = value.trim();
+    let value = value
+        .strip_prefix("ISNI")
+        .or_else(|| value.strip_prefix("isni"))
+        .unwrap_or(value);
+
+    Ok(mod11_2_characters(field, value, "a valid ISNI")?
+        .into_iter()
+        .collect())
+}
+
+/// VIAF ids carry no check digit, so only their shape is checked.
+pub fn validate_viaf(field: &'static str, value: &str) -> Result<String, ValidationError> {
+    let value = value.trim().trim_end_matches('/');
+    let value = ["https://viaf.org/viaf/", "http://viaf.org/viaf/"]
+        .into_iter()
+        .find_map(|prefix| value.strip_prefix(prefix))
+        .unwrap_or(value);
+
+    if value.is_empty() {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::Empty,
+        });
+    }
+
+    if value.len() > 22
+        || value.starts_with('0')
+        || !value.chars().all(|character| character.is_ascii_digit())
+    {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::Invalid {
+                expected: "a VIAF id",
+            },
+        });
+    }
+
+    Ok(value.to_owned())
+}
+
+/// Wikidata item ids carry no check digit, so only their `Q123` shape is checked.
+pub fn validate_wikidata_qid(field: &'static str, value: &str) -> Result<String, ValidationError> {
+    let value = value.trim();
+    let value = value
+        .strip_prefix("https://www.wikidata.org/wiki/")
+        .unwrap_or(value);
+
+    if value.is_empty() {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::Empty,
+        });
+    }
+
+    let digits = value
+        .strip_prefix('Q')
+        .or_else(|| value.strip_prefix('q'))
+        .unwrap_or_default();
+
+    if digits.is_empty()
+        || digits.len() > 18
+        || digits.starts_with('0')
+        || !digits.chars().all(|character| character.is_ascii_digit())
+    {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::Invalid {
+                expected: "a Wikidata item id such as Q42",
+            },
+        });
+    }
+
+    Ok(format!("Q{digits}"))
+}
+
+/// The sixteen characters of an ORCID iD or ISNI, whose last character is an ISO 7064
+/// MOD 11-2 check character.
+fn mod11_2_characters(
+    field: &'static str,
+    value: &str,
+    expected: &'static str,
+) -> Result<Vec<char>, ValidationError> {
+    let characters = value
+        .chars()
+        .filter(|character| !matches!(character, '-' | ' '))
+        .map(|character| character.to_ascii_uppercase())
+        .collect::<Vec<char>>();
+
+    if characters.is_empty() {
+        return Err(ValidationError {
+            field,
+            reason: ValidationReason::Empty,
+        });
+    }
+
+    let invalid = ValidationError {
+        field,
+        reason: ValidationReason::Invalid { expected },
+    };
+
+    if characters.len() != 16 {
+        return Err(invalid);
+    }
+
+    let total = characters[..15]
+        .iter()
+        .try_fold(0, |total, character| {
+            Some((total + character.to_digit(10)?) * 2)
+        })
+        .ok_or(invalid.clone())?;
+    // A remainder of ten is written as `X`.
+    let check = char::from_digit((12 - total % 11) % 11, 10).unwrap_or('X');
+
+    if characters[15] != check {
+        return Err(invalid);
+    }
+
+    Ok(characters)
+}
@@ -0,0 +1,82 @@
+use std::io::Write;
+
+use eyre::Result;
+use quick_xml::escape::escape;
+
+/// Writes indented XML one element per line, escaping text and attribute values.
+pub(crate) struct XmlWriter<W: Write> {
+    pub(crate) writer: W,
+    depth: usize,
+}
+
+impl<W: Write> XmlWriter<W> {
+    pub(crate) fn new(mut writer: W) -> Result<Self> {
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+
+        Ok(Self { writer, depth: 0 })
+    }
+
+    pub(crate) fn open(&mut self, name: &str) -> Result<()> {
+        self.open_with(name, &[])
+    }
+
+    pub(crate) fn open_with(&mut self, name: &str, attributes: &[(&str, &str)]) -> Result<()> {
+        self.start_tag(name, attributes)?;
+        writeln!(self.writer, ">")?;
+        self.depth += 1;
+
+        Ok(())
+    }
+
+    pub(crate) fn close(&mut self, name: &str) -> Result<()> {
+        self.depth -= 1;
+        self.indent()?;
+        writeln!(self.writer, "</{name}>")?;
+
+        Ok(())
+    }
+
+    pub(crate) fn leaf(&mut self, name: &str, text: &str) -> Result<()> {
+        self.leaf_with(name, &[], text)
+    }
+
+    pub(crate) fn leaf_with(
+        &mut self,
+        name: &str,
+        attributes: &[(&str, &str)],
+        text: &str,
+    ) -> Result<()> {
+        self.start_tag(name, attributes)?;
+        writeln!(self.writer, ">{}</{name}>", escape(text))?;
+
+        Ok(())
+    }
+
+    pub(crate) fn empty(&mut self, name: &str) -> Result<()> {
+        self.empty_with(name, &[])
+    }
+
+    pub(crate) fn empty_with(&mut self, name: &str, attributes: &[(&str, &str)]) -> Result<()> {
+        self.start_tag(name, attributes)?;
+        writeln!(self.writer, "/>")?;
+
+        Ok(())
+    }
+
+    fn start_tag(&mut self, name: &str, attributes: &[(&str, &str)]) -> Result<()> {
+        self.indent()?;
+        write!(self.writer, "<{name}")?;
+
+        for (attribute, value) in attributes {
+            write!(self.writer, r#" {attribute}="{}""#, escape(*value))?;
+        }
+
+        Ok(())
+    }
+
+    fn indent(&mut self) -> Result<()> {
+        write!(self.writer, "{:width$}", "", width = self.depth * 2)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,267 @@
+#![cfg(feature = "server")]
+
+mod utilities;
+
+use axum::{
+    body::Body,
+    http::{Method, Request, StatusCode},
+    Router,
+};
+use diesel_bookstore_assessment::{api::router, connect::build_pool, opds::OpdsCatalog};
+use eyre::Result;
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use std::env;
+use tower::ServiceExt;
+use utilities::random_name;
+
+fn app() -> Result<Router> {
+    dotenvy::dotenv().ok();
+
+    Ok(router(
+        build_pool(&env::var("DATABASE_URL")?)?,
+        OpdsCatalog::new("http://localhost:3000")?,
+    ))
+}
+
+async fn send(
+    app: &Router,
+    method: Method,
+    uri: &str,
+    body: Option<Value>,
+) -> Result<(StatusCode, Value)> {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json");
+    let request = match body {
+        Some(body) => request.body(Body::from(body.to_string()))?,
+        None => request.body(Body::empty())?,
+    };
+    let response = app.clone().oneshot(request).await?;
+    let status = response.status();
+    let bytes = response.into_body().collect().await?.to_bytes();
+    let body = if bytes.is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_slice(&bytes)?
+    };
+
+    Ok((status, body))
+}
+
+#[tokio::test]
+async fn book_crud_api_test() -> Result<()> {
+    let app = app()?;
+    let book_name = random_name("api book");
+    let new_name = random_name("renamed api book");
+
+    let (status, created) = send(
+        &app,
+        Method::POST,
+        "/books",
+        Some(json!({ "name": book_name })),
+    )
+    .await?;
+
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(created["name"], book_name);
+
+    let uri = format!("/books/{}", created["id"]);
+    let (status, fetched) = send(&app, Method::GET, &uri, None).await?;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(fetched, created);
+
+    let (status, all_books) = send(&app, Method::GET, "/books", None).await?;
+
+    assert_eq!(status, StatusCode::OK);
+    assert!(all_books
+        .as_array()
+        .is_some_and(|books| books.contains(&created)));
+
+    let (status, updated) =
+        send(&app, Method::PUT, &uri, Some(json!({ "name": new_name }))).await?;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["name"], new_name);
+
+    let (status, _) = send(&app, Method::DELETE, &uri, None).await?;
+
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _) = send(&app, Method::GET, &uri, None).await?;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn author_crud_api_test() -> Result<()> {
+    let app = app()?;
+    let author_name = random_name("api author");
+    let new_name = random_name("renamed api author");
+
+    let (status, created) = send(
+        &app,
+        Method::POST,
+        "/authors",
+        Some(json!({ "name": author_name })),
+    )
+    .await?;
+
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(created["name"], author_name);
+
+    let uri = format!("/authors/{}", created["id"]);
+    let (status, updated) =
+        send(&app, Method::PUT, &uri, Some(json!({ "name": new_name }))).await?;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["name"], new_name);
+
+    let (status, _) = send(&app, Method::DELETE, &uri, None).await?;
+
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _) = send(&app, Method::DELETE, &uri, None).await?;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn book_authors_api_test() -> Result<()> {
+    let app = app()?;
+    let (_, book) = send(
+        &app,
+        Method::POST,
+        "/books",
+        Some(json!({ "name": random_name("linked book") })),
+    )
+    .await?;
+    let (_, author) = send(
+        &app,
+        Method::POST,
+        "/authors",
+        Some(json!({ "name": random_name("linked author") })),
+    )
+    .await?;
+    let uri = format!("/books/{}/authors", book["id"]);
+    let link = json!({ "author_id": author["id"] });
+
+    let (status, _) = send(&app, Method::POST, &uri, Some(link.clone())).await?;
+
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, authors) = send(&app, Method::GET, &uri, None).await?;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(authors, json!([author]));
+
+    let (status, _) = send(&app, Method::POST, &uri, Some(link)).await?;
+
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, _) = send(
+        &app,
+        Method::DELETE,
+        &format!("/books/{}", book["id"]),
+        None,
+    )
+    .await?;
+
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, _) = send(
+        &app,
+        Method::DELETE,
+        &format!("{uri}/{}", author["id"]),
+        None,
+    )
+    .await?;
+
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, authors) = send(&app, Method::GET, &uri, None).await?;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(authors, json!([]));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn book_authors_api_errors_test() -> Result<()> {
+    let app = app()?;
+    let (_, book) = send(
+        &app,
+        Method::POST,
+        "/books",
+        Some(json!({ "name": random_name("lonely book") })),
+    )
+    .await?;
+    let uri = format!("/books/{}/authors", book["id"]);
+
+    let (status, _) = send(&app, Method::POST, &uri, Some(json!({ "author_id": -1 }))).await?;
+
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let (status, _) = send(&app, Method::GET, "/books/-1/authors", None).await?;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send(&app, Method::DELETE, &format!("{uri}/-1"), None).await?;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn invalid_names_are_unprocessable_test() -> Result<()> {
+    let app = app()?;
+
+    let (status, body) = send(&app, Method::POST, "/books", Some(json!({ "name": "   " }))).await?;
+
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(body["error"].is_string());
+
+    let (_, book) = send(
+        &app,
+        Method::POST,
+        "/books",
+        Some(json!({ "name": random_name("valid book") })),
+    )
+    .await?;
+    let (status, body) = send(
+        &app,
+        Method::PUT,
+        &format!("/books/{}", book["id"]),
+        Some(json!({ "name": "\u{3000}" })),
+    )
+    .await?;
+
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(body["error"]
+        .as_str()
+        .is_some_and(|error| error.contains("name must not be empty")));
+
+    let (status, _) = send(
+        &app,
+        Method::POST,
+        "/authors",
+        Some(json!({ "name": "a".repeat(256) })),
+    )
+    .await?;
+
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let (status, _) = send(&app, Method::POST, "/authors", Some(json!({}))).await?;
+
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    Ok(())
+}
@@ -0,0 +1,225 @@
+mod utilities;
+
+use diesel_bookstore_assessment::{
+    connect::connect,
+    models::AuthorNameKind,
+    queries::{
+        author_name_queries::{
+            add_author_name, clear_published_name, get_author_names, get_book_with_published_names,
+            remove_author_name, resolve_author_name, set_published_name, AuthorNameError,
+        },
+        author_queries::create_author,
+        book_author_queries::{associate_book_with_author, get_book_with_authors},
+        book_queries::{create_book, search_books_page},
+    },
+};
+use eyre::Result;
+use utilities::random_name;
+
+#[test]
+fn variants_and_pseudonyms_resolve_to_the_author_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let marker = rand::random::<u32>();
+    let canonical = format!("Samuel Langhorne Clemens {marker}");
+    let author_id = create_author(&canonical, connection)?;
+    let namesake_id = create_author(&random_name("Another Clemens"), connection)?;
+    let pseudonym_id = add_author_name(
+        author_id,
+        &format!("Mark Twain {marker}"),
+        AuthorNameKind::Pseudonym,
+        connection,
+    )?;
+
+    add_author_name(
+        author_id,
+        &format!("S. L. Clemens {marker}"),
+        AuthorNameKind::Variant,
+        connection,
+    )?;
+    add_author_name(
+        namesake_id,
+        &format!("S. L. Clemens {marker}"),
+        AuthorNameKind::Variant,
+        connection,
+    )?;
+
+    let resolved = |name: &str, connection: &mut _| -> Result<Vec<i32>> {
+        Ok(resolve_author_name(name, connection)?
+            .into_iter()
+            .map(|author| author.id)
+            .collect())
+    };
+
+    assert_eq!(
+        resolved(&format!("  mark   TWAIN {marker}"), connection)?,
+        vec![author_id]
+    );
+    assert_eq!(resolved(&canonical, connection)?, vec![author_id]);
+    assert_eq!(
+        resolved(&format!("s. l. clemens {marker}"), connection)?,
+        vec![author_id, namesake_id]
+    );
+    assert!(resolved(&format!("Huck Finn {marker}"), connection)?.is_empty());
+
+    let error = add_author_name(
+        author_id,
+        &format!("MARK TWAIN {marker}"),
+        AuthorNameKind::Variant,
+        connection,
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        error.downcast_ref::<AuthorNameError>(),
+        Some(&AuthorNameError::DuplicateName {
+            author_id,
+            author_name_id: pseudonym_id
+        })
+    );
+
+    let error = add_author_name(-1, "Nobody", AuthorNameKind::Variant, connection).unwrap_err();
+
+    assert_eq!(
+        error.downcast_ref::<AuthorNameError>(),
+        Some(&AuthorNameError::AuthorNotFound { author_id: -1 })
+    );
+
+    let names = get_author_names(author_id, connection)?;
+
+    assert_eq!(
+        names
+            .iter()
+            .map(|name| AuthorNameKind::parse(&name.kind))
+            .collect::<Vec<_>>(),
+        vec![
+            Some(AuthorNameKind::Pseudonym),
+            Some(AuthorNameKind::Variant)
+        ]
+    );
+
+    assert!(remove_author_name(names[1].id, connection)?);
+    assert_eq!(
+        resolved(&format!("S. L. Clemens {marker}"), connection)?,
+        vec![namesake_id]
+    );
+    Ok(())
+}
+
+#[test]
+fn names_ignore_case_outside_ascii_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let marker = rand::random::<u32>();
+    let author_id = create_author(&format!("Émile Zola {marker}"), connection)?;
+    let variant_id = add_author_name(
+        author_id,
+        &format!("Émile Édouard Charles Antoine Zola {marker}"),
+        AuthorNameKind::Variant,
+        connection,
+    )?;
+
+    for name in [
+        format!("ÉMILE ZOLA {marker}"),
+        format!("émile édouard charles antoine zola {marker}"),
+    ] {
+        assert_eq!(
+            resolve_author_name(&name, connection)?
+                .into_iter()
+                .map(|author| author.id)
+                .collect::<Vec<i32>>(),
+            vec![author_id]
+        );
+    }
+
+    let error = add_author_name(
+        author_id,
+        &format!("ÉMILE ÉDOUARD CHARLES ANTOINE ZOLA {marker}"),
+        AuthorNameKind::Variant,
+        connection,
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        error.downcast_ref::<AuthorNameError>(),
+        Some(&AuthorNameError::DuplicateName {
+            author_id,
+            author_name_id: variant_id
+        })
+    );
+    Ok(())
+}
+
+#[test]
+fn books_show_the_name_they_were_published_with_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let marker = rand::random::<u32>();
+    let author_id = create_author(&random_name("Mary Ann Evans"), connection)?;
+    let other_author_id = create_author(&random_name("Jane Austen"), connection)?;
+    let pen_name = format!("George Eliot {marker}");
+    let pseudonym_id =
+        add_author_name(author_id, &pen_name, AuthorNameKind::Pseudonym, connection)?;
+    let book_id = create_book(&random_name("Middlemarch"), connection)?;
+    let uncredited_book_id = create_book(&random_name("Emma"), connection)?;
+
+    associate_book_with_author(book_id, author_id, connection)?;
+    associate_book_with_author(book_id, other_author_id, connection)?;
+
+    let error = set_published_name(uncredited_book_id, pseudonym_id, connection).unwrap_err();
+
+    assert_eq!(
+        error.downcast_ref::<AuthorNameError>(),
+        Some(&AuthorNameError::NotCredited {
+            book_id: uncredited_book_id,
+            author_id
+        })
+    );
+
+    set_published_name(book_id, pseudonym_id, connection)?;
+
+    let published = get_book_with_published_names(book_id, connection)?.unwrap();
+    let canonical = get_book_with_authors(book_id, connection)?.unwrap();
+    let mut names = published
+        .authors()
+        .iter()
+        .map(|author| (author.id, author.name.clone()))
+        .collect::<Vec<_>>();
+
+    names.sort();
+
+    let mut expected = vec![
+        (author_id, pen_name.clone()),
+        (
+            other_author_id,
+            canonical
+                .authors()
+                .iter()
+                .find(|author| author.id == other_author_id)
+                .unwrap()
+                .name
+                .clone(),
+        ),
+    ];
+
+    expected.sort();
+
+    assert_eq!(names, expected);
+    assert!(canonical
+        .authors()
+        .iter()
+        .all(|author| author.name != pen_name));
+
+    // Searching for the pen name finds the book.
+    let found = search_books_page(&pen_name, None, 10, connection)?;
+
+    assert_eq!(
+        found.iter().map(|book| book.id).collect::<Vec<i32>>(),
+        vec![book_id]
+    );
+
+    assert!(clear_published_name(book_id, author_id, connection)?);
+    assert!(get_book_with_published_names(book_id, connection)?
+        .unwrap()
+        .authors()
+        .iter()
+        .all(|author| author.name != pen_name));
+    Ok(())
+}
@@ -0,0 +1,154 @@
+mod utilities;
+
+use diesel_bookstore_assessment::{
+    connect::connect,
+    models::{AuthorDetails, AuthorIdentifierScheme, PartialDate},
+    queries::{
+        author_identifier_queries::{
+            add_author_identifier, get_author_by_identifier, get_identifiers_for_author,
+            remove_author_identifier,
+        },
+        author_queries::{create_author, get_author_profile, update_author_profile},
+    },
+    validation::ValidationError,
+};
+use eyre::Result;
+use utilities::random_name;
+
+#[test]
+fn author_profiles_keep_partial_dates_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let author_id = create_author(&random_name("Geoffrey Chaucer"), connection)?;
+    let blank = get_author_profile(author_id, connection)?.unwrap();
+
+    assert_eq!(
+        (blank.birth_date, blank.death_date, blank.biography),
+        (None, None, None)
+    );
+
+    update_author_profile(
+        author_id,
+        &AuthorDetails {
+            birth_date: Some("c. 1343"),
+            death_date: Some("1400-10-25"),
+            nationality: Some("gb"),
+            biography: Some("  Poet and civil servant.\n"),
+        },
+        connection,
+    )?;
+
+    let profile = get_author_profile(author_id, connection)?.unwrap();
+
+    assert_eq!(profile.birth_date.as_deref(), Some("1343~"));
+    assert_eq!(
+        PartialDate::parse(profile.death_date.as_deref().unwrap()),
+        Some(PartialDate {
+            year: 1400,
+            month: Some(10),
+            day: Some(25),
+            circa: false,
+        })
+    );
+    assert_eq!(profile.nationality.as_deref(), Some("GB"));
+    assert_eq!(
+        profile.biography.as_deref(),
+        Some("Poet and civil servant.")
+    );
+
+    // A death in the same year as a birth known only to the year is fine; one before it is not.
+    update_author_profile(
+        author_id,
+        &AuthorDetails {
+            birth_date: Some("1400"),
+            death_date: Some("1400-01"),
+            ..AuthorDetails::default()
+        },
+        connection,
+    )?;
+
+    let error = update_author_profile(
+        author_id,
+        &AuthorDetails {
+            birth_date: Some("1400"),
+            death_date: Some("1399-12-31"),
+            ..AuthorDetails::default()
+        },
+        connection,
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        error.downcast_ref::<ValidationError>().unwrap().field,
+        "death_date"
+    );
+
+    let profile = get_author_profile(author_id, connection)?.unwrap();
+
+    assert_eq!(profile.death_date.as_deref(), Some("1400-01"));
+    assert_eq!(profile.nationality, None);
+    assert!(get_author_profile(-1, connection)?.is_none());
+    Ok(())
+}
+
+#[test]
+fn authors_can_be_found_by_external_identifiers_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let author_id = create_author(&random_name("Identified Author"), connection)?;
+    let qid = format!("q{}", rand::random::<u32>() | 1);
+    let viaf = format!("{}", rand::random::<u32>() | 1);
+
+    let stored_qid = add_author_identifier(
+        author_id,
+        AuthorIdentifierScheme::Wikidata,
+        &qid,
+        connection,
+    )?;
+
+    add_author_identifier(author_id, AuthorIdentifierScheme::Viaf, &viaf, connection)?;
+
+    assert_eq!(stored_qid, qid.to_uppercase());
+
+    let found =
+        get_author_by_identifier(AuthorIdentifierScheme::Wikidata, &stored_qid, connection)?;
+
+    assert_eq!(found.map(|author| author.id), Some(author_id));
+    assert_eq!(
+        get_author_by_identifier(AuthorIdentifierScheme::Viaf, &viaf, connection)?
+            .map(|author| author.id),
+        Some(author_id)
+    );
+
+    let error = add_author_identifier(
+        author_id,
+        AuthorIdentifierScheme::Orcid,
+        "0000-0002-1825-0098",
+        connection,
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        error.downcast_ref::<ValidationError>().unwrap().field,
+        "orcid"
+    );
+
+    let schemes = get_identifiers_for_author(author_id, connection)?
+        .into_iter()
+        .map(|identifier| AuthorIdentifierScheme::parse(&identifier.scheme))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        schemes,
+        vec![
+            Some(AuthorIdentifierScheme::Viaf),
+            Some(AuthorIdentifierScheme::Wikidata)
+        ]
+    );
+
+    assert!(remove_author_identifier(
+        AuthorIdentifierScheme::Viaf,
+        &viaf,
+        connection
+    )?);
+    assert!(get_author_by_identifier(AuthorIdentifierScheme::Viaf, &viaf, connection)?.is_none());
+    Ok(())
+}
@@ -2,7 +2,9 @@ mod utilities;
 
 use diesel::prelude::*;
 use diesel::QueryDsl;
-use diesel_bookstore_assessment::models::BookAuthor;
+use diesel_bookstore_assessment::models::{
+    Author, AuthorWithBooks, Book, BookAuthor, BookWithAuthors,
+};
 use diesel_bookstore_assessment::queries::book_author_queries::get_all_authors_and_books;
 use diesel_bookstore_assessment::queries::book_author_queries::get_all_books_and_authors;
 use diesel_bookstore_assessment::queries::book_author_queries::{
@@ -32,7 +34,7 @@ fn associate_book_with_author_test() -> Result<()> {
     associate_book_with_author(book_id, author_id, connection)?;
 
     let db_book_author: Option<BookAuthor> = book_authors
-        .find((author_id, book_id))
+        .find((author_id, book_id, "author"))
         .select(BookAuthor::as_select())
         .first(connection)
         .optional()?;
@@ -54,7 +56,9 @@ fn get_author_with_its_one_book() -> Result<()> {
 
     let author_with_books = get_author_with_books(author_id, connection)?;
 
-    assert!(author_with_books.is_some_and(|(author, books)| {
+    assert!(author_with_books.is_some_and(|author_with_books| {
+        let (author, books) = author_with_books.into_parts();
+
         author_name == author.name && books.len() == 1 && books[0].name == book_name
     }));
 
@@ -78,7 +82,7 @@ fn get_author_with_its_multiple_books_test() -> Result<()> {
 
     assert!(author_with_books.is_some());
 
-    let (_author, books) = author_with_books.unwrap();
+    let (_author, books) = author_with_books.unwrap().into_parts();
 
     assert_eq!(books.len(), 2);
 
@@ -103,7 +107,7 @@ fn get_book_with_its_one_author() -> Result<()> {
 
     assert!(book_with_authors.is_some());
 
-    let (book, authors) = book_with_authors.unwrap();
+    let (book, authors) = book_with_authors.unwrap().into_parts();
 
     assert_eq!(book.name, book_name);
     assert_eq!(authors.len(), 1);
@@ -130,7 +134,7 @@ fn get_book_with_its_multiple_authors() -> Result<()> {
 
     assert!(book_with_authors.is_some());
 
-    let (book, authors) = book_with_authors.unwrap();
+    let (book, authors) = book_with_authors.unwrap().into_parts();
 
     assert_eq!(book.name, book_name);
     assert_eq!(authors.len(), 2);
@@ -171,7 +175,10 @@ fn get_all_books_with_their_authors_test() -> Result<()> {
 
     let mut found_books = 0;
 
-    for (book, authors) in all_books_with_authors {
+    for (book, authors) in all_books_with_authors
+        .into_iter()
+        .map(BookWithAuthors::into_parts)
+    {
         if book.id == book_1_id {
             found_books += 1;
 
@@ -233,7 +240,10 @@ fn get_all_authors_with_their_books_test() -> Result<()> {
 
     let mut found_authors = 0;
 
-    for (author, books) in all_authors_with_books {
+    for (author, books) in all_authors_with_books
+        .into_iter()
+        .map(AuthorWithBooks::into_parts)
+    {
         if author.id == author_1_id {
             found_authors += 1;
 
@@ -266,3 +276,62 @@ fn get_all_authors_with_their_books_test() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn authors_are_read_back_in_credit_order_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let book_id = create_book(&random_name("Principia Mathematica"), connection)?;
+    let russell = create_author(&random_name("Bertrand Russell"), connection)?;
+    let whitehead = create_author(&random_name("Alfred North Whitehead"), connection)?;
+    let editor = create_author(&random_name("Editor"), connection)?;
+
+    // Credited in the opposite order to the one the authors were created in.
+    associate_book_with_author(book_id, whitehead, connection)?;
+    associate_book_with_author(book_id, russell, connection)?;
+    associate_book_with_author(book_id, editor, connection)?;
+
+    let author_ids =
+        |authors: &[Author]| authors.iter().map(|author| author.id).collect::<Vec<i32>>();
+    let expected = vec![whitehead, russell, editor];
+
+    assert_eq!(
+        author_ids(
+            get_book_with_authors(book_id, connection)?
+                .unwrap()
+                .authors()
+        ),
+        expected
+    );
+
+    let all_books_and_authors = get_all_books_and_authors(connection)?;
+    let book_with_authors = all_books_and_authors
+        .iter()
+        .find(|book_with_authors| book_with_authors.book().id == book_id)
+        .unwrap();
+
+    assert_eq!(author_ids(book_with_authors.authors()), expected);
+
+    Ok(())
+}
+
+#[test]
+fn named_results_convert_to_and_from_tuples_test() {
+    let book = Book {
+        id: 1,
+        name: "Brave New World".to_owned(),
+    };
+    let author = Author {
+        id: 1,
+        name: "Aldous Huxley".to_owned(),
+    };
+    let book_with_authors = BookWithAuthors::from((book, vec![author]));
+
+    assert_eq!(book_with_authors.book().name, "Brave New World");
+    assert_eq!(book_with_authors.authors()[0].name, "Aldous Huxley");
+
+    let (book, mut authors) = book_with_authors.into();
+    let author_with_books = AuthorWithBooks::new(authors.remove(0), vec![book]);
+
+    assert_eq!(author_with_books.author().name, "Aldous Huxley");
+    assert_eq!(author_with_books.books()[0].name, "Brave New World");
+}
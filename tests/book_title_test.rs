@@ -0,0 +1,164 @@
+mod utilities;
+
+use diesel_bookstore_assessment::{
+    connect::connect,
+    models::LocalizedTitle,
+    queries::{
+        book_author_queries::get_book_with_authors,
+        book_queries::{create_book, search_books_page},
+        book_title_queries::{
+            get_book_titles, get_localized_book_with_authors, get_localized_titles,
+            language_fallbacks, remove_book_title, set_book_title, with_localized_titles,
+        },
+    },
+    validation::ValidationError,
+};
+use eyre::Result;
+use utilities::random_name;
+
+#[test]
+fn language_fallbacks_shorten_each_preference_test() -> Result<()> {
+    assert_eq!(
+        language_fallbacks(&["de-AT", "en_gb", "de"])?,
+        vec!["de-AT", "de", "en-GB", "en"]
+    );
+    assert_eq!(
+        language_fallbacks(&["zh-Hant-TW"])?,
+        vec!["zh-Hant-TW", "zh-Hant", "zh"]
+    );
+    assert_eq!(
+        language_fallbacks(&["en-x-pirate"])?,
+        vec!["en-x-pirate", "en"]
+    );
+    assert!(language_fallbacks(&[])?.is_empty());
+    assert_eq!(
+        language_fallbacks(&["de", "?"])
+            .unwrap_err()
+            .downcast_ref::<ValidationError>()
+            .unwrap()
+            .field,
+        "preferences"
+    );
+    Ok(())
+}
+
+#[test]
+fn titles_fall_back_through_preferences_to_the_original_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let book_id = create_book(&random_name("The Little Prince"), connection)?;
+    let untitled_id = create_book(&random_name("Untitled"), connection)?;
+
+    set_book_title(book_id, "fr", "Le Petit Prince", true, connection)?;
+    set_book_title(book_id, "de", "Der kleine Prinz", false, connection)?;
+    set_book_title(
+        book_id,
+        "de_at",
+        "Der kleine Prinz (Österreich)",
+        false,
+        connection,
+    )?;
+
+    let title_for = |preferences: &[&str], connection: &mut _| -> Result<LocalizedTitle> {
+        Ok(get_localized_titles(&[book_id], preferences, connection)?
+            .remove(&book_id)
+            .unwrap())
+    };
+
+    assert_eq!(
+        title_for(&["de-AT", "en"], connection)?,
+        LocalizedTitle {
+            title: "Der kleine Prinz (Österreich)".to_owned(),
+            language: Some("de-AT".to_owned()),
+        }
+    );
+    assert_eq!(
+        title_for(&["de-CH"], connection)?.language.as_deref(),
+        Some("de")
+    );
+    assert_eq!(title_for(&["ja"], connection)?.title, "Le Petit Prince");
+
+    // Replacing a title in a language keeps a single title for it, and the original mark
+    // moves with it.
+    set_book_title(book_id, "de", "Der Kleine Prinz", true, connection)?;
+
+    let titles = get_book_titles(book_id, connection)?;
+
+    assert_eq!(titles.len(), 3);
+    assert_eq!(
+        (titles[0].language.as_str(), titles[0].title.as_str()),
+        ("de", "Der Kleine Prinz")
+    );
+    assert!(titles[1..].iter().all(|title| !title.is_original));
+    assert_eq!(title_for(&["ja"], connection)?.title, "Der Kleine Prinz");
+
+    assert!(remove_book_title(book_id, "DE-at", connection)?);
+    assert!(!remove_book_title(book_id, "de-AT", connection)?);
+    assert_eq!(title_for(&["de-AT"], connection)?.title, "Der Kleine Prinz");
+
+    let books = with_localized_titles(
+        vec![
+            get_book_with_authors(book_id, connection)?.unwrap(),
+            get_book_with_authors(untitled_id, connection)?.unwrap(),
+        ],
+        &["fr"],
+        connection,
+    )?;
+
+    assert_eq!(books[0].localized_title().unwrap().title, "Le Petit Prince");
+    assert_eq!(
+        books[1].localized_title(),
+        Some(&LocalizedTitle {
+            title: books[1].book().name.clone(),
+            language: None,
+        })
+    );
+
+    let book = get_localized_book_with_authors(book_id, &["en"], connection)?.unwrap();
+
+    assert_eq!(
+        book.localized_title().unwrap().language.as_deref(),
+        Some("de")
+    );
+    assert!(get_localized_book_with_authors(-1, &["en"], connection)?.is_none());
+    Ok(())
+}
+
+#[test]
+fn search_covers_titles_in_every_language_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let marker = format!("kobzar-{}", rand::random::<u32>());
+    let book_id = create_book(&random_name("Collected Poems"), connection)?;
+
+    set_book_title(book_id, "uk", &format!("Кобзар {marker}"), true, connection)?;
+
+    let found = search_books_page(&marker.to_uppercase(), None, 10, connection)?;
+
+    assert_eq!(
+        found.iter().map(|book| book.id).collect::<Vec<i32>>(),
+        vec![book_id]
+    );
+    Ok(())
+}
+
+#[test]
+fn search_ignores_case_outside_ascii_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let marker = format!("{}", rand::random::<u32>());
+    let etudes = create_book(&format!("ÉTUDES SUR ŒDIPE {marker}"), connection)?;
+    let kobzar = create_book(&random_name("Collected Poems"), connection)?;
+
+    set_book_title(kobzar, "uk", &format!("КОБЗАР {marker}"), true, connection)?;
+
+    let by_name = search_books_page(&format!("études sur œdipe {marker}"), None, 10, connection)?;
+    let by_title = search_books_page(&format!("кобзар {marker}"), None, 10, connection)?;
+
+    assert_eq!(
+        by_name.iter().map(|book| book.id).collect::<Vec<i32>>(),
+        vec![etudes]
+    );
+    assert_eq!(
+        by_title.iter().map(|book| book.id).collect::<Vec<i32>>(),
+        vec![kobzar]
+    );
+    Ok(())
+}
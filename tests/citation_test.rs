@@ -0,0 +1,235 @@
+use std::{env, fs, path::PathBuf};
+
+use diesel_bookstore_assessment::{
+    citation::{cite_book, to_bibtex, to_ris, CitationFormat, PersonName},
+    connect::establish,
+    models::{Author, Book, BookWithAuthors},
+    queries::{
+        author_queries::create_author, book_author_queries::associate_book_with_author,
+        book_queries::create_book,
+    },
+};
+use eyre::Result;
+use tempfile::TempDir;
+
+fn book(id: i32, name: &str, authors: &[&str]) -> BookWithAuthors {
+    let authors = authors
+        .iter()
+        .enumerate()
+        .map(|(index, name)| Author {
+            id: index as i32 + 1,
+            name: name.to_string(),
+        })
+        .collect();
+
+    BookWithAuthors::new(
+        Book {
+            id,
+            name: name.to_owned(),
+        },
+        authors,
+    )
+}
+
+fn fixtures() -> Vec<(&'static str, BookWithAuthors)> {
+    vec![
+        ("single_author", book(2, "Moby Dick", &["Herman Melville"])),
+        (
+            "multiple_authors",
+            book(7, "Good Omens", &["Terry Pratchett", "Neil Gaiman"]),
+        ),
+        (
+            "special_characters",
+            book(
+                12,
+                r#"Notes & "Quotes": 100% {Annotated} #1_draft ~ ^ \ $5"#,
+                &[
+                    "Ludwig van Beethoven",
+                    "Martin Luther King Jr.",
+                    "García Márquez, Gabriel",
+                    "Marks and Spencer",
+                    "Homer",
+                ],
+            ),
+        ),
+        ("anonymous", book(40, "Beowulf", &[])),
+        (
+            "accented_first_author",
+            book(
+                51,
+                "Crónica de una muerte anunciada",
+                &["García Márquez, Gabriel"],
+            ),
+        ),
+    ]
+}
+
+/// Set `UPDATE_GOLDEN=1` to rewrite the files after an intended change in output.
+fn assert_golden(file_name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden/citations")
+        .join(file_name);
+
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, actual).expect("writing golden file");
+    }
+
+    let expected = fs::read_to_string(&path).expect("reading golden file");
+
+    assert_eq!(actual, expected, "{file_name} does not match");
+}
+
+#[test]
+fn bibtex_matches_golden_files_test() {
+    for (name, book_with_authors) in fixtures() {
+        assert_golden(&format!("{name}.bib"), &to_bibtex(&book_with_authors));
+    }
+}
+
+#[test]
+fn ris_matches_golden_files_test() {
+    for (name, book_with_authors) in fixtures() {
+        assert_golden(&format!("{name}.ris"), &to_ris(&book_with_authors));
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn csl_json_matches_golden_files_test() {
+    use diesel_bookstore_assessment::citation::to_csl_json;
+
+    for (name, book_with_authors) in fixtures() {
+        assert_golden(&format!("{name}.json"), &to_csl_json(&book_with_authors));
+    }
+}
+
+#[test]
+fn person_names_are_split_into_parts_test() {
+    let cases = [
+        ("Herman Melville", Some("Herman"), None, "Melville", None),
+        ("Edgar Allan Poe", Some("Edgar Allan"), None, "Poe", None),
+        ("Homer", None, None, "Homer", None),
+        ("Marks and Spencer", None, None, "Marks and Spencer", None),
+        (
+            "Ludwig van Beethoven",
+            Some("Ludwig"),
+            Some("van"),
+            "Beethoven",
+            None,
+        ),
+        (
+            "Vincent van der Berg",
+            Some("Vincent"),
+            Some("van der"),
+            "Berg",
+            None,
+        ),
+        ("Melville, Herman", Some("Herman"), None, "Melville", None),
+        (
+            "van Gogh, Vincent",
+            Some("Vincent"),
+            Some("van"),
+            "Gogh",
+            None,
+        ),
+        (
+            "Martin Luther King Jr.",
+            Some("Martin Luther"),
+            None,
+            "King",
+            Some("Jr."),
+        ),
+        (
+            "King, Jr., Martin Luther",
+            Some("Martin Luther"),
+            None,
+            "King",
+            Some("Jr."),
+        ),
+    ];
+
+    for (name, given, particle, family, suffix) in cases {
+        assert_eq!(
+            PersonName::parse(name),
+            PersonName {
+                given: given.map(str::to_owned),
+                particle: particle.map(str::to_owned),
+                family: family.to_owned(),
+                suffix: suffix.map(str::to_owned),
+            },
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn citation_keys_spell_out_letters_outside_ascii_test() {
+    let cases = [
+        ("Gabriel García Márquez", "marquez3"),
+        ("Søren Kierkegaard", "kierkegaard3"),
+        ("Kierkegaard, Søren", "kierkegaard3"),
+        ("Bjørnstjerne Bjørnson", "bjornson3"),
+        ("Stanisław Lem", "lem3"),
+        ("Lem, Stanisław", "lem3"),
+        ("Czesław Miłosz", "milosz3"),
+        ("Ödön von Horváth", "horvath3"),
+        ("Ernst Straße", "strasse3"),
+        ("夏目漱石", "book3"),
+    ];
+
+    for (name, key) in cases {
+        let bibtex = to_bibtex(&book(3, "Title", &[name]));
+
+        assert!(
+            bibtex.starts_with(&format!("@book{{{key},\n")),
+            "{name}: {bibtex}"
+        );
+    }
+}
+
+#[test]
+fn cite_book_uses_stored_authors_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let database_path = directory.path().join("bookstore.sqlite3");
+    let connection = &mut establish(&format!("sqlite://{}", database_path.display()))?;
+    let book_id = create_book("Omoo", connection)?;
+    let author_id = create_author("Herman Melville", connection)?;
+
+    associate_book_with_author(book_id, author_id, connection)?;
+
+    let citation = cite_book(book_id, CitationFormat::Ris, connection)?;
+
+    assert_eq!(
+        citation.as_deref(),
+        Some("TY  - BOOK\nID  - book-1\nAU  - Melville, Herman\nTI  - Omoo\nER  - \n")
+    );
+    assert_eq!(
+        cite_book(book_id + 1, CitationFormat::BibTex, connection)?,
+        None
+    );
+
+    Ok(())
+}
+
+#[test]
+fn cite_book_lists_authors_in_credit_order_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let database_path = directory.path().join("bookstore.sqlite3");
+    let connection = &mut establish(&format!("sqlite://{}", database_path.display()))?;
+    let book_id = create_book("Good Omens", connection)?;
+    let gaiman = create_author("Neil Gaiman", connection)?;
+    let pratchett = create_author("Terry Pratchett", connection)?;
+
+    associate_book_with_author(book_id, pratchett, connection)?;
+    associate_book_with_author(book_id, gaiman, connection)?;
+
+    let citation = cite_book(book_id, CitationFormat::BibTex, connection)?.unwrap();
+
+    assert!(
+        citation
+            .starts_with("@book{pratchett1,\n  author = {Pratchett, Terry and Gaiman, Neil},\n"),
+        "{citation}"
+    );
+
+    Ok(())
+}
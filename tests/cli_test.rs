@@ -0,0 +1,189 @@
+#![cfg(feature = "cli")]
+
+mod utilities;
+
+use assert_cmd::Command;
+use eyre::Result;
+use serde_json::Value;
+use utilities::random_name;
+
+fn bookstore(args: &[&str]) -> Result<Command> {
+    let mut command = Command::cargo_bin("bookstore")?;
+
+    command.args(args);
+
+    Ok(command)
+}
+
+fn bookstore_json(args: &[&str]) -> Result<Value> {
+    let output = bookstore(&[&["--format", "json"], args].concat())?
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    Ok(serde_json::from_slice(&output)?)
+}
+
+#[test]
+fn book_commands_test() -> Result<()> {
+    let book_name = random_name("cli book");
+    let new_name = random_name("renamed cli book");
+    let created = bookstore_json(&["book", "add", &book_name])?;
+    let id = created["id"].to_string();
+
+    assert_eq!(created["name"], book_name);
+
+    let listed = bookstore_json(&["book", "list"])?;
+
+    assert!(listed
+        .as_array()
+        .is_some_and(|books| books.contains(&created)));
+
+    let renamed = bookstore_json(&["book", "rename", &id, &new_name])?;
+
+    assert_eq!(renamed["name"], new_name);
+
+    let table = bookstore(&["book", "show", &id])?
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let table = String::from_utf8(table)?;
+
+    assert!(table.starts_with("id"));
+    assert!(table.contains(&new_name));
+
+    bookstore(&["book", "rm", &id])?.assert().success();
+    bookstore(&["book", "show", &id])?.assert().failure();
+
+    Ok(())
+}
+
+#[test]
+fn author_commands_and_links_test() -> Result<()> {
+    let author_name = random_name("cli author");
+    let book_name = random_name("cli linked book");
+    let author = bookstore_json(&["author", "add", &author_name])?;
+    let book = bookstore_json(&["book", "add", &book_name])?;
+    let author_id = author["id"].to_string();
+    let book_id = book["id"].to_string();
+
+    let linked = bookstore_json(&["link", &book_id, &author_id])?;
+
+    assert_eq!(linked["linked"], true);
+    assert_eq!(linked["book_id"].to_string(), book_id);
+
+    let author_with_books = bookstore_json(&["author", "show", &author_id])?;
+
+    assert_eq!(author_with_books["author"]["name"], author_name);
+    assert_eq!(author_with_books["books"][0]["name"], book_name);
+
+    let book_with_authors = bookstore_json(&["book", "show", &book_id])?;
+
+    assert_eq!(book_with_authors["authors"][0]["name"], author_name);
+
+    let unlinked = bookstore_json(&["unlink", &book_id, &author_id])?;
+
+    assert_eq!(unlinked["linked"], false);
+
+    bookstore(&["unlink", &book_id, &author_id])?
+        .assert()
+        .failure();
+
+    let removed = bookstore_json(&["author", "rm", &author_id])?;
+
+    assert_eq!(removed["removed"], true);
+    assert_eq!(removed["id"].to_string(), author_id);
+
+    let listed = bookstore_json(&["author", "list"])?;
+
+    assert!(listed
+        .as_array()
+        .is_some_and(|authors| !authors.contains(&author)));
+
+    Ok(())
+}
+
+#[test]
+fn invalid_input_fails_test() -> Result<()> {
+    bookstore(&["book", "add", "   "])?.assert().failure();
+    bookstore(&["author", "rename", "-1", "nobody"])?
+        .assert()
+        .failure();
+    bookstore(&["link", "-1", "-1"])?.assert().failure();
+    bookstore(&["export", "--batch-size", "0", "jsonl"])?
+        .assert()
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn import_command_test() -> Result<()> {
+    let directory = tempfile::tempdir()?;
+    let books_path = directory.path().join("books.csv");
+    let book_name = random_name("cli imported book");
+
+    std::fs::write(&books_path, format!("book_id,name\n1,{book_name}\n"))?;
+
+    let books_path = books_path.to_string_lossy();
+    let report = bookstore_json(&["import", "--books", &books_path, "--dry-run"])?;
+
+    assert_eq!(report["books_created"], 1);
+    assert_eq!(report["dry_run"], true);
+
+    let output = bookstore_json(&["import", "--books", &books_path])?;
+
+    assert_eq!(output["dry_run"], false);
+
+    let listed = bookstore_json(&["book", "list"])?;
+
+    assert!(listed.as_array().is_some_and(|books| books
+        .iter()
+        .filter(|book| book["name"] == book_name.as_str())
+        .count()
+        == 1));
+
+    Ok(())
+}
+
+#[test]
+fn price_commands_test() -> Result<()> {
+    let directory = tempfile::tempdir()?;
+    let prices_path = directory.path().join("prices.csv");
+    let book = bookstore_json(&["book", "add", &random_name("cli priced book")])?;
+    let id = book["id"].to_string();
+
+    std::fs::write(
+        &prices_path,
+        format!("book_id,currency,price\n{id},USD,12.50\n"),
+    )?;
+
+    let prices_path = prices_path.to_string_lossy();
+    let preview = bookstore(&["import-prices", &prices_path, "--dry-run"])?
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let preview = String::from_utf8(preview)?;
+
+    assert!(preview.contains("12.50 USD"));
+    bookstore(&["book", "price", &id, "USD"])?
+        .assert()
+        .failure();
+
+    let report = bookstore_json(&["import-prices", &prices_path])?;
+
+    assert_eq!(report["changes"][0]["new_amount"], 1250);
+
+    let price = bookstore_json(&["book", "price", &id, "usd"])?;
+
+    assert_eq!(price["amount_minor"], 1250);
+    assert_eq!(price["currency"], "USD");
+
+    Ok(())
+}
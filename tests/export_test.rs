@@ -0,0 +1,208 @@
+use diesel_bookstore_assessment::{
+    connect::{connect, establish, DbConnection},
+    export::{export_csv, ExportReport, ExportTargets},
+    import::{import_csv, ImportSources},
+    queries::{
+        author_queries::create_author,
+        book_author_queries::{associate_book_with_author, get_all_books_and_authors},
+        book_queries::create_book,
+    },
+};
+use eyre::Result;
+use tempfile::TempDir;
+
+fn sqlite_connection(directory: &TempDir, name: &str) -> Result<DbConnection> {
+    let database_path = directory.path().join(name);
+
+    establish(&format!("sqlite://{}", database_path.display()))
+}
+
+fn seed(connection: &mut DbConnection) -> Result<()> {
+    let brave_new_world = create_book("Brave New World", connection)?;
+    let moby_dick = create_book("Moby Dick", connection)?;
+    let omoo = create_book("Omoo", connection)?;
+    let rip_van_winkle = create_book("Rip Van Winkle", connection)?;
+    let huxley = create_author("Aldous Huxley", connection)?;
+    let melville = create_author("Herman Melville", connection)?;
+    let irving = create_author("Washington Irving", connection)?;
+
+    associate_book_with_author(brave_new_world, huxley, connection)?;
+    associate_book_with_author(moby_dick, melville, connection)?;
+    associate_book_with_author(omoo, melville, connection)?;
+    associate_book_with_author(rip_van_winkle, irving, connection)?;
+
+    Ok(())
+}
+
+fn export_to_strings(
+    batch_size: i64,
+    connection: &mut DbConnection,
+) -> Result<(ExportReport, [String; 3])> {
+    let mut targets = ExportTargets {
+        books: Vec::new(),
+        authors: Vec::new(),
+        book_authors: Vec::new(),
+    };
+    let report = export_csv(
+        ExportTargets {
+            books: &mut targets.books,
+            authors: &mut targets.authors,
+            book_authors: &mut targets.book_authors,
+        },
+        batch_size,
+        connection,
+    )?;
+
+    Ok((
+        report,
+        [targets.books, targets.authors, targets.book_authors]
+            .map(|bytes| String::from_utf8(bytes).expect("utf-8 csv")),
+    ))
+}
+
+#[test]
+fn export_csv_uses_seeds_layout_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let connection = &mut sqlite_connection(&directory, "export.sqlite3")?;
+
+    seed(connection)?;
+
+    let (report, [books, authors, book_authors]) = export_to_strings(3, connection)?;
+
+    assert_eq!(
+        report,
+        ExportReport {
+            books: 4,
+            authors: 3,
+            links: 4,
+        }
+    );
+    assert_eq!(
+        books,
+        "book_id,name\n1,Brave New World\n2,Moby Dick\n3,Omoo\n4,Rip Van Winkle\n"
+    );
+    assert_eq!(
+        authors,
+        "author_id,name\n1,Aldous Huxley\n2,Herman Melville\n3,Washington Irving\n"
+    );
+    assert_eq!(book_authors, "author_id,book_id\n1,1\n2,2\n2,3\n3,4\n");
+
+    Ok(())
+}
+
+#[test]
+fn export_csv_batch_size_does_not_change_output_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let connection = &mut sqlite_connection(&directory, "export.sqlite3")?;
+
+    seed(connection)?;
+
+    let (_, expected) = export_to_strings(100, connection)?;
+
+    for batch_size in [1, 2, 4] {
+        assert_eq!(export_to_strings(batch_size, connection)?.1, expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn export_csv_links_only_exported_authors_on_postgres_test() -> Result<()> {
+    let connection = &mut connect()?;
+
+    seed(connection)?;
+
+    let (report, [_, authors, book_authors]) = export_to_strings(2, connection)?;
+    let author_ids: Vec<&str> = authors
+        .lines()
+        .skip(1)
+        .map(|line| line.split(',').next().expect("author id"))
+        .collect();
+
+    assert_eq!(author_ids.len(), report.authors);
+    assert_eq!(book_authors.lines().skip(1).count(), report.links);
+
+    for link in book_authors.lines().skip(1) {
+        let author_id = link.split(',').next().expect("author id");
+
+        assert!(author_ids.contains(&author_id), "{link} has no author row");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn exported_csv_imports_into_empty_catalog_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let source = &mut sqlite_connection(&directory, "source.sqlite3")?;
+    let destination = &mut sqlite_connection(&directory, "destination.sqlite3")?;
+
+    seed(source)?;
+
+    let (_, [books, authors, book_authors]) = export_to_strings(2, source)?;
+    let sources = ImportSources {
+        books: Some(books.as_bytes()),
+        authors: Some(authors.as_bytes()),
+        book_authors: Some(book_authors.as_bytes()),
+    };
+
+    import_csv(sources, false, destination)?;
+
+    let names = |connection: &mut DbConnection| -> Result<Vec<(String, Vec<String>)>> {
+        Ok(get_all_books_and_authors(connection)?
+            .into_iter()
+            .map(|book_with_authors| {
+                let (book, authors) = book_with_authors.into_parts();
+
+                (
+                    book.name,
+                    authors.into_iter().map(|author| author.name).collect(),
+                )
+            })
+            .collect())
+    };
+
+    assert_eq!(names(destination)?, names(source)?);
+
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn export_json_lines_and_document_test() -> Result<()> {
+    use diesel_bookstore_assessment::{
+        export::{export_json, export_json_lines},
+        models::BookWithAuthors,
+    };
+
+    let directory = TempDir::new()?;
+    let connection = &mut sqlite_connection(&directory, "export.sqlite3")?;
+
+    seed(connection)?;
+
+    let mut lines = Vec::new();
+    let mut document = Vec::new();
+
+    assert_eq!(export_json_lines(&mut lines, 3, connection)?, 4);
+    assert_eq!(export_json(&mut document, 3, connection)?, 4);
+
+    let from_lines = String::from_utf8(lines)?
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<Vec<BookWithAuthors>, _>>()?;
+    let from_document: Vec<BookWithAuthors> = serde_json::from_slice(&document)?;
+
+    assert_eq!(from_lines, from_document);
+    assert_eq!(from_document, get_all_books_and_authors(connection)?);
+    assert_eq!(from_document[2].book().name, "Omoo");
+    assert_eq!(from_document[2].authors()[0].name, "Herman Melville");
+
+    let mut empty = Vec::new();
+    let empty_directory = TempDir::new()?;
+    let empty_connection = &mut sqlite_connection(&empty_directory, "empty.sqlite3")?;
+
+    assert_eq!(export_json(&mut empty, 3, empty_connection)?, 0);
+    assert_eq!(String::from_utf8(empty)?, "[]\n");
+
+    Ok(())
+}
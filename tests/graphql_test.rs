@@ -0,0 +1,208 @@
+#![cfg(feature = "graphql")]
+
+mod utilities;
+
+use diesel_bookstore_assessment::{
+    connect::build_pool,
+    graphql::{build_schema, BookstoreSchema},
+};
+use eyre::{eyre, Result};
+use serde_json::{json, Value};
+use std::env;
+use utilities::random_name;
+
+fn schema() -> Result<BookstoreSchema> {
+    dotenvy::dotenv().ok();
+
+    Ok(build_schema(build_pool(&env::var("DATABASE_URL")?)?))
+}
+
+async fn run(schema: &BookstoreSchema, query: &str) -> Result<Value> {
+    let response = schema.execute(query).await;
+
+    if !response.errors.is_empty() {
+        return Err(eyre!("graphql errors: {:?}", response.errors));
+    }
+
+    Ok(response.data.into_json()?)
+}
+
+async fn create(schema: &BookstoreSchema, mutation: &str, name: &str) -> Result<i64> {
+    let data = run(
+        schema,
+        &format!(r#"mutation {{ {mutation}(name: "{name}") {{ id }} }}"#),
+    )
+    .await?;
+
+    data[mutation]["id"]
+        .as_i64()
+        .ok_or_else(|| eyre!("missing id in {data}"))
+}
+
+#[tokio::test]
+async fn nested_books_authors_and_their_other_books_test() -> Result<()> {
+    let schema = schema()?;
+    let first_book = random_name("first graphql book");
+    let second_book = random_name("second graphql book");
+    let author = random_name("graphql author");
+    let first_book_id = create(&schema, "createBook", &first_book).await?;
+    let second_book_id = create(&schema, "createBook", &second_book).await?;
+    let author_id = create(&schema, "createAuthor", &author).await?;
+
+    for book_id in [first_book_id, second_book_id] {
+        run(
+            &schema,
+            &format!(
+                "mutation {{ associateBookWithAuthor(bookId: {book_id}, authorId: {author_id}) {{ bookId }} }}"
+            ),
+        )
+        .await?;
+    }
+
+    let data = run(
+        &schema,
+        &format!("{{ book(id: {first_book_id}) {{ name authors {{ name books {{ name }} }} }} }}"),
+    )
+    .await?;
+    let authors = &data["book"]["authors"];
+
+    assert_eq!(data["book"]["name"], first_book);
+    assert_eq!(authors[0]["name"], author);
+    assert!(authors[0]["books"]
+        .as_array()
+        .is_some_and(|books| books.len() == 2
+            && books.contains(&json!({ "name": first_book }))
+            && books.contains(&json!({ "name": second_book }))));
+
+    let data = run(&schema, "{ books { id authors { id } } }").await?;
+    let listed_book = data["books"]
+        .as_array()
+        .and_then(|books| books.iter().find(|book| book["id"] == second_book_id))
+        .ok_or_else(|| eyre!("second book missing from books"))?;
+
+    assert_eq!(listed_book["authors"], json!([{ "id": author_id }]));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn book_and_author_mutations_test() -> Result<()> {
+    let schema = schema()?;
+    let book_id = create(&schema, "createBook", &random_name("mutated book")).await?;
+    let author_id = create(&schema, "createAuthor", &random_name("mutated author")).await?;
+    let new_book_name = random_name("renamed graphql book");
+
+    let data = run(
+        &schema,
+        &format!(r#"mutation {{ updateBook(id: {book_id}, name: "{new_book_name}") {{ name }} }}"#),
+    )
+    .await?;
+
+    assert_eq!(data["updateBook"]["name"], new_book_name);
+
+    let data = run(
+        &schema,
+        &format!(
+            "mutation {{ link: associateBookWithAuthor(bookId: {book_id}, authorId: {author_id}) {{ book {{ id }} author {{ id }} }} }}"
+        ),
+    )
+    .await?;
+
+    assert_eq!(data["link"]["book"]["id"], book_id);
+    assert_eq!(data["link"]["author"]["id"], author_id);
+
+    let data = run(
+        &schema,
+        &format!(
+            "mutation {{ unlinked: dissociateBookFromAuthor(bookId: {book_id}, authorId: {author_id}) deletedBook: deleteBook(id: {book_id}) deletedAuthor: deleteAuthor(id: {author_id}) }}"
+        ),
+    )
+    .await?;
+
+    assert_eq!(
+        data,
+        json!({ "unlinked": true, "deletedBook": true, "deletedAuthor": true })
+    );
+
+    let data = run(&schema, &format!("{{ book(id: {book_id}) {{ id }} }}")).await?;
+
+    assert_eq!(data["book"], Value::Null);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn invalid_names_report_the_field_test() -> Result<()> {
+    let schema = schema()?;
+    let response = schema
+        .execute(r#"mutation { createAuthor(name: "   ") { id } }"#)
+        .await;
+
+    assert_eq!(response.errors.len(), 1);
+    assert_eq!(
+        response.errors[0]
+            .extensions
+            .as_ref()
+            .and_then(|extensions| extensions.get("field"))
+            .map(|field| field.to_string()),
+        Some("\"name\"".to_owned())
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn database_errors_are_reported_by_code_test() -> Result<()> {
+    let schema = schema()?;
+    let author_id = create(
+        &schema,
+        "createAuthor",
+        &random_name("graphql error author"),
+    )
+    .await?;
+    let response = schema
+        .execute(format!(
+            "mutation {{ associateBookWithAuthor(bookId: -1, authorId: {author_id}) {{ bookId }} }}"
+        ))
+        .await;
+
+    assert_eq!(response.errors.len(), 1);
+    assert_eq!(
+        response.errors[0]
+            .extensions
+            .as_ref()
+            .and_then(|extensions| extensions.get("code"))
+            .map(|code| code.to_string()),
+        Some("\"CONFLICT\"".to_owned())
+    );
+    assert!(!response.errors[0].message.contains("violates"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn graphql_is_mounted_on_the_api_router_test() -> Result<()> {
+    use axum::{body::Body, http::Request};
+    use diesel_bookstore_assessment::{api::router, opds::OpdsCatalog};
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/graphql")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            json!({ "query": "{ authors { id } }" }).to_string(),
+        ))?;
+    let response = router(
+        build_pool(&env::var("DATABASE_URL")?)?,
+        OpdsCatalog::new("http://localhost:3000")?,
+    )
+    .oneshot(request)
+    .await?;
+    let body: Value = serde_json::from_slice(&response.into_body().collect().await?.to_bytes())?;
+
+    assert!(body["data"]["authors"].is_array());
+
+    Ok(())
+}
@@ -0,0 +1,166 @@
+mod utilities;
+
+use diesel_bookstore_assessment::{
+    connect::connect,
+    import::{import_csv, ImportError, ImportReport, ImportSources},
+    queries::{
+        author_queries::{create_author, get_authors_by_name},
+        book_author_queries::get_book_with_authors,
+        book_queries::{create_book, get_books_by_name},
+    },
+};
+use eyre::Result;
+use utilities::random_name;
+
+fn import_error(error: eyre::Report) -> ImportError {
+    error
+        .downcast_ref::<ImportError>()
+        .expect("import error")
+        .clone()
+}
+
+#[test]
+fn import_books_authors_and_links_by_id_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let book_name = random_name("imported book");
+    let author_name = random_name("imported author");
+    let books = format!("book_id,name\n1,{book_name}\n");
+    let authors = format!("author_id,name\n1,{author_name}\n");
+    let book_authors = "author_id,book_id\n1,1\n";
+    let sources = ImportSources {
+        books: Some(books.as_bytes()),
+        authors: Some(authors.as_bytes()),
+        book_authors: Some(book_authors.as_bytes()),
+    };
+
+    let report = import_csv(sources, false, connection)?;
+
+    assert_eq!(
+        report,
+        ImportReport {
+            books_created: 1,
+            authors_created: 1,
+            links_created: 1,
+            dry_run: false,
+        }
+    );
+
+    let book = get_books_by_name(&book_name, connection)?.remove(0);
+    let (_, authors) = get_book_with_authors(book.id, connection)?
+        .expect("imported book")
+        .into_parts();
+
+    assert_eq!(authors.len(), 1);
+    assert_eq!(authors[0].name, author_name);
+
+    Ok(())
+}
+
+#[test]
+fn import_links_by_name_to_existing_rows_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let book_name = random_name("existing book");
+    let author_name = random_name("existing author");
+    let book_id = create_book(&book_name, connection)?;
+    let author_id = create_author(&author_name, connection)?;
+    let book_authors = format!("author_name,book_name\n{author_name},\"  {book_name} \"\n");
+    let sources = ImportSources {
+        book_authors: Some(book_authors.as_bytes()),
+        ..Default::default()
+    };
+
+    let report = import_csv(sources, false, connection)?;
+
+    assert_eq!(report.links_created, 1);
+
+    let (_, authors) = get_book_with_authors(book_id, connection)?
+        .expect("existing book")
+        .into_parts();
+
+    assert_eq!(authors[0].id, author_id);
+
+    Ok(())
+}
+
+#[test]
+fn import_dry_run_writes_nothing_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let book_name = random_name("dry run book");
+    let books = format!("book_id,name\n7,{book_name}\n");
+    let sources = ImportSources {
+        books: Some(books.as_bytes()),
+        ..Default::default()
+    };
+
+    let report = import_csv(sources, true, connection)?;
+
+    assert_eq!(report.books_created, 1);
+    assert!(report.dry_run);
+    assert!(get_books_by_name(&book_name, connection)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn import_reports_header_errors_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let sources = ImportSources {
+        books: Some("book_id,title\n1,Omoo\n".as_bytes()),
+        book_authors: Some("author_id,book_id,book_name\n1,1,Omoo\n".as_bytes()),
+        ..Default::default()
+    };
+
+    let errors = import_error(import_csv(sources, false, connection).unwrap_err()).errors;
+    let messages = errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<String>>();
+
+    assert_eq!(
+        messages,
+        [
+            "books line 1: missing required column `name`",
+            "book_authors line 1: use only one of `book_id` and `book_name`",
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn import_rolls_back_on_row_errors_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let author_name = random_name("rolled back author");
+    let missing_book = random_name("missing book");
+    let authors = format!("author_id,name\n1,{author_name}\n2,\n1,duplicate\nx,bad id\n");
+    let book_authors = format!("author_id,book_name\n1,{missing_book}\n");
+
+    let sources = ImportSources {
+        authors: Some(authors.as_bytes()),
+        ..Default::default()
+    };
+    let errors = import_error(import_csv(sources, false, connection).unwrap_err()).errors;
+    let lines = errors.iter().map(|error| error.line).collect::<Vec<u64>>();
+
+    assert_eq!(lines, [3, 4, 5]);
+    assert_eq!(errors[0].message, "name must not be empty");
+
+    let authors = format!("author_id,name\n1,{author_name}\n");
+    let sources = ImportSources {
+        authors: Some(authors.as_bytes()),
+        book_authors: Some(book_authors.as_bytes()),
+        ..Default::default()
+    };
+    let errors = import_error(import_csv(sources, false, connection).unwrap_err()).errors;
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].file, "book_authors");
+    assert_eq!(errors[0].line, 2);
+    assert_eq!(
+        errors[0].message,
+        format!("book `{missing_book}` not found")
+    );
+    assert!(get_authors_by_name(&author_name, connection)?.is_empty());
+
+    Ok(())
+}
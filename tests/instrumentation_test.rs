@@ -0,0 +1,93 @@
+#![cfg(feature = "tracing")]
+
+mod utilities;
+
+use diesel::Connection;
+use diesel_bookstore_assessment::{
+    connect::connect, instrumentation::QueryTracing, queries::book_queries::create_book,
+};
+use eyre::Result;
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing_subscriber::fmt::{format::FmtSpan, MakeWriter};
+use utilities::random_name;
+
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl CapturedLogs {
+    fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+    }
+}
+
+impl Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for CapturedLogs {
+    type Writer = Self;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+#[test]
+fn logs_query_with_row_count_test() -> Result<()> {
+    let logs = CapturedLogs::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_ansi(false)
+        .with_writer(logs.clone())
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+    let connection = &mut connect()?;
+    let book_name = random_name("traced book");
+
+    create_book(&book_name, connection)?;
+
+    let output = logs.contents();
+
+    assert!(output.contains("create_book"));
+    assert!(output.contains("INSERT INTO"));
+    assert!(!output.contains("binds"));
+    assert!(output.contains("rows=1"));
+    assert!(output.contains("duration_ms="));
+
+    Ok(())
+}
+
+#[test]
+fn logs_slow_queries_at_warn_test() -> Result<()> {
+    let logs = CapturedLogs::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::WARN)
+        .with_ansi(false)
+        .with_writer(logs.clone())
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+    let connection = &mut connect()?;
+
+    connection.set_instrumentation(QueryTracing::new(Duration::ZERO));
+    create_book(&random_name("slow book"), connection)?;
+
+    let output = logs.contents();
+
+    assert!(output.contains("WARN"));
+    assert!(output.contains("slow query"));
+
+    Ok(())
+}
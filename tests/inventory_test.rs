@@ -0,0 +1,301 @@
+mod utilities;
+
+use std::thread;
+
+use diesel_bookstore_assessment::{
+    connect::{connect, establish, DbConnection},
+    queries::{
+        author_queries::create_author,
+        book_author_queries::associate_book_with_author,
+        book_queries::create_book,
+        inventory_queries::{
+            create_location, get_low_stock_report, get_stock_for_book, get_stock_level,
+            receive_stock, release_stock, reserve_stock, sell_reserved_stock, sell_stock,
+            set_reorder_threshold, InsufficientStock,
+        },
+    },
+    validation::ValidationError,
+};
+use eyre::Result;
+use tempfile::TempDir;
+use utilities::random_name;
+
+/// Location names are unique and every run leaves its locations behind, which is more than
+/// `random_name` has room for.
+fn location_name(name: &str) -> String {
+    format!("{name} {}", rand::random::<u64>())
+}
+
+fn quantities(book_id: i32, location_id: i32, connection: &mut DbConnection) -> Result<(i32, i32)> {
+    let stock_level = get_stock_level(book_id, location_id, connection)?.unwrap();
+
+    Ok((stock_level.quantity, stock_level.reserved))
+}
+
+#[test]
+fn stock_moves_through_receive_reserve_release_and_sell_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let book_id = create_book(&random_name("stocked book"), connection)?;
+    let location_id = create_location(&location_name("store"), connection)?;
+
+    assert!(get_stock_level(book_id, location_id, connection)?.is_none());
+
+    receive_stock(book_id, location_id, 5, connection)?;
+    receive_stock(book_id, location_id, 3, connection)?;
+    assert_eq!(quantities(book_id, location_id, connection)?, (8, 0));
+
+    let reserved = reserve_stock(book_id, location_id, 3, connection)?;
+
+    assert_eq!((reserved.quantity, reserved.reserved), (8, 3));
+    assert_eq!(reserved.available(), 5);
+
+    release_stock(book_id, location_id, 1, connection)?;
+    assert_eq!(quantities(book_id, location_id, connection)?, (8, 2));
+
+    sell_stock(book_id, location_id, 6, connection)?;
+    assert_eq!(quantities(book_id, location_id, connection)?, (2, 2));
+
+    sell_reserved_stock(book_id, location_id, 2, connection)?;
+    assert_eq!(quantities(book_id, location_id, connection)?, (0, 0));
+    assert_eq!(get_stock_for_book(book_id, connection)?.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn stock_operations_refuse_more_than_is_there_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let book_id = create_book(&random_name("scarce book"), connection)?;
+    let location_id = create_location(&location_name("store"), connection)?;
+    let insufficient = |error: eyre::Report| error.downcast::<InsufficientStock>().unwrap();
+
+    assert_eq!(
+        insufficient(sell_stock(book_id, location_id, 1, connection).unwrap_err()),
+        InsufficientStock {
+            book_id,
+            location_id,
+            requested: 1,
+            available: 0,
+        }
+    );
+
+    receive_stock(book_id, location_id, 2, connection)?;
+    reserve_stock(book_id, location_id, 1, connection)?;
+
+    assert_eq!(
+        insufficient(sell_stock(book_id, location_id, 2, connection).unwrap_err()).available,
+        1
+    );
+    assert_eq!(
+        insufficient(reserve_stock(book_id, location_id, 2, connection).unwrap_err()).available,
+        1
+    );
+    assert_eq!(
+        insufficient(release_stock(book_id, location_id, 2, connection).unwrap_err()).available,
+        1
+    );
+    assert_eq!(
+        insufficient(sell_reserved_stock(book_id, location_id, 2, connection).unwrap_err())
+            .available,
+        1
+    );
+    assert!(receive_stock(book_id, location_id, 0, connection)
+        .unwrap_err()
+        .downcast_ref::<ValidationError>()
+        .is_some());
+    assert!(set_reorder_threshold(book_id, location_id, -1, connection).is_err());
+    assert_eq!(quantities(book_id, location_id, connection)?, (2, 1));
+
+    Ok(())
+}
+
+/// Every thread tries to sell one copy of the same book at the same time.
+fn sell_concurrently(database_url: &str, copies: i32, buyers: usize) -> Result<(usize, i32)> {
+    let connection = &mut establish(database_url)?;
+    let book_id = create_book(&random_name("popular book"), connection)?;
+    let location_id = create_location(&location_name("busy store"), connection)?;
+
+    receive_stock(book_id, location_id, copies, connection)?;
+
+    let sold = thread::scope(|scope| {
+        let handles = (0..buyers)
+            .map(|_| {
+                scope.spawn(|| -> Result<bool> {
+                    let connection = &mut establish(database_url)?;
+
+                    match sell_stock(book_id, location_id, 1, connection) {
+                        Ok(_) => Ok(true),
+                        Err(error) if error.downcast_ref::<InsufficientStock>().is_some() => {
+                            Ok(false)
+                        }
+                        Err(error) => Err(error),
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Result<Vec<bool>>>()
+    })?;
+
+    Ok((
+        sold.into_iter().filter(|sold| *sold).count(),
+        get_stock_level(book_id, location_id, connection)?
+            .unwrap()
+            .quantity,
+    ))
+}
+
+#[test]
+fn concurrent_sales_never_oversell_test() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    let database_url = std::env::var("DATABASE_URL")?;
+
+    assert_eq!(sell_concurrently(&database_url, 5, 12)?, (5, 0));
+
+    Ok(())
+}
+
+#[test]
+fn concurrent_sales_never_oversell_on_sqlite_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let database_url = format!(
+        "sqlite://{}",
+        directory.path().join("bookstore.sqlite3").display()
+    );
+
+    establish(&database_url)?;
+
+    assert_eq!(sell_concurrently(&database_url, 3, 8)?, (3, 0));
+
+    Ok(())
+}
+
+/// Every thread delivers copies of a book the location has never stocked.
+fn receive_concurrently(database_url: &str, deliveries: usize) -> Result<i32> {
+    let connection = &mut establish(database_url)?;
+    let book_id = create_book(&random_name("new release"), connection)?;
+    let location_id = create_location(&location_name("new store"), connection)?;
+
+    thread::scope(|scope| {
+        let handles = (0..deliveries)
+            .map(|_| {
+                scope.spawn(|| -> Result<()> {
+                    let connection = &mut establish(database_url)?;
+
+                    receive_stock(book_id, location_id, 2, connection)?;
+
+                    Ok(())
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Result<Vec<()>>>()
+    })?;
+
+    Ok(get_stock_level(book_id, location_id, connection)?
+        .unwrap()
+        .quantity)
+}
+
+#[test]
+fn concurrent_first_deliveries_all_count_test() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    let database_url = std::env::var("DATABASE_URL")?;
+
+    assert_eq!(receive_concurrently(&database_url, 8)?, 16);
+
+    Ok(())
+}
+
+#[test]
+fn concurrent_first_deliveries_all_count_on_sqlite_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let database_url = format!(
+        "sqlite://{}",
+        directory.path().join("bookstore.sqlite3").display()
+    );
+
+    establish(&database_url)?;
+
+    assert_eq!(receive_concurrently(&database_url, 6)?, 12);
+
+    Ok(())
+}
+
+#[test]
+fn receiving_past_the_largest_quantity_is_refused_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let sqlite = &mut establish(&format!(
+        "sqlite://{}",
+        directory.path().join("bookstore.sqlite3").display()
+    ))?;
+
+    for connection in [&mut connect()?, sqlite] {
+        let book_id = create_book(&random_name("bestseller"), connection)?;
+        let location_id = create_location(&location_name("warehouse"), connection)?;
+
+        receive_stock(book_id, location_id, i32::MAX - 1, connection)?;
+        receive_stock(book_id, location_id, 1, connection)?;
+
+        assert!(receive_stock(book_id, location_id, 1, connection)
+            .unwrap_err()
+            .downcast_ref::<ValidationError>()
+            .is_some());
+        assert_eq!(quantities(book_id, location_id, connection)?, (i32::MAX, 0));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn low_stock_report_lists_stock_at_or_below_its_threshold_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let location_name = location_name("low stock store");
+    let location_id = create_location(&location_name, connection)?;
+    let running_low = create_book(&random_name("running low"), connection)?;
+    let plenty = create_book(&random_name("plenty"), connection)?;
+    let sold_out = create_book(&random_name("sold out"), connection)?;
+    let author_name = random_name("low stock author");
+    let author_id = create_author(&author_name, connection)?;
+
+    associate_book_with_author(running_low, author_id, connection)?;
+    receive_stock(running_low, location_id, 4, connection)?;
+    reserve_stock(running_low, location_id, 2, connection)?;
+    set_reorder_threshold(running_low, location_id, 2, connection)?;
+    receive_stock(plenty, location_id, 10, connection)?;
+    set_reorder_threshold(plenty, location_id, 2, connection)?;
+    receive_stock(sold_out, location_id, 1, connection)?;
+    sell_stock(sold_out, location_id, 1, connection)?;
+
+    let report = get_low_stock_report(connection)?
+        .into_iter()
+        .filter(|item| item.location_id == location_id)
+        .collect::<Vec<_>>();
+    let running_low_item = report
+        .iter()
+        .find(|item| item.book_id == running_low)
+        .unwrap();
+
+    assert_eq!(report.len(), 2);
+    assert!(report.iter().any(|item| item.book_id == sold_out));
+    assert_eq!(running_low_item.authors, [author_name]);
+    assert_eq!(running_low_item.location_name, location_name);
+    assert_eq!(
+        (
+            running_low_item.quantity,
+            running_low_item.reserved,
+            running_low_item.reorder_threshold
+        ),
+        (4, 2, 2)
+    );
+
+    Ok(())
+}
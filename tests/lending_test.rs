@@ -0,0 +1,283 @@
+mod utilities;
+
+use std::{thread, time::Duration as StdDuration};
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, Utc};
+use diesel_bookstore_assessment::{
+    connect::{connect, establish, DbConnection},
+    models::HoldStatus,
+    queries::{
+        book_queries::{create_book, get_books_changed_since_page},
+        hold_queries::{cancel_hold, get_hold_queue, place_hold},
+        loan_queries::{
+            add_copy, checkin, checkout, get_copy_by_barcode, get_open_loans_for_member,
+            get_overdue_report, renew, LendingError, LoanPolicy,
+        },
+        member_queries::create_member,
+    },
+    validation::ValidationError,
+};
+use eyre::Result;
+use tempfile::TempDir;
+use utilities::random_name;
+
+fn day(day: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 3, day)
+        .unwrap()
+        .and_hms_opt(10, 0, 0)
+        .unwrap()
+}
+
+fn new_member(connection: &mut DbConnection) -> Result<i32> {
+    create_member(
+        &random_name("member"),
+        &format!("member.{}@example.com", rand::random::<u32>()),
+        connection,
+    )
+}
+
+fn barcode() -> String {
+    format!("C{:010}", rand::random::<u32>())
+}
+
+fn lending_error(error: eyre::Report) -> LendingError {
+    error.downcast_ref::<LendingError>().unwrap().clone()
+}
+
+#[test]
+fn checkout_renew_and_checkin_track_due_dates_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let policy = LoanPolicy::default();
+    let book_id = create_book(&random_name("lent book"), connection)?;
+    let member_id = new_member(connection)?;
+    let label = barcode();
+    let copy = add_copy(book_id, &format!("  {label} "), day(1), connection)?;
+
+    assert_eq!(
+        get_copy_by_barcode(&label, connection)?.map(|copy| copy.id),
+        Some(copy.id)
+    );
+
+    let loan = checkout(copy.id, member_id, day(1), &policy, connection)?;
+
+    assert_eq!(loan.due_at, day(22));
+    assert_eq!(
+        lending_error(checkout(copy.id, member_id, day(2), &policy, connection).unwrap_err()),
+        LendingError::CopyOnLoan { copy_id: copy.id }
+    );
+
+    // Renewing early never shortens the loan.
+    let renewed = renew(loan.id, day(1), &policy, connection)?;
+
+    assert_eq!((renewed.due_at, renewed.renewals), (day(22), 1));
+
+    let renewed = renew(loan.id, day(20), &policy, connection)?;
+
+    assert_eq!(renewed.due_at, day(20) + Duration::days(21));
+    assert_eq!(
+        lending_error(renew(loan.id, day(25), &policy, connection).unwrap_err()),
+        LendingError::RenewalLimit {
+            loan_id: loan.id,
+            renewals: 2
+        }
+    );
+    assert_eq!(get_open_loans_for_member(member_id, connection)?.len(), 1);
+
+    let returned = checkin(copy.id, day(28), connection)?;
+
+    assert_eq!(returned.loan.returned_at, Some(day(28)));
+    assert!(returned.hold.is_none());
+    assert!(get_open_loans_for_member(member_id, connection)?.is_empty());
+    assert_eq!(
+        lending_error(checkin(copy.id, day(29), connection).unwrap_err()),
+        LendingError::NotOnLoan { copy_id: copy.id }
+    );
+    assert_eq!(
+        lending_error(renew(loan.id, day(29), &policy, connection).unwrap_err()),
+        LendingError::LoanReturned { loan_id: loan.id }
+    );
+
+    let error = add_copy(book_id, "two words", day(1), connection).unwrap_err();
+
+    assert_eq!(
+        error.downcast_ref::<ValidationError>().unwrap().field,
+        "barcode"
+    );
+    Ok(())
+}
+
+#[test]
+fn returned_copies_go_to_holds_in_the_order_they_were_placed_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let policy = LoanPolicy::default();
+    let book_id = create_book(&random_name("popular book"), connection)?;
+    let borrower = new_member(connection)?;
+    let first = new_member(connection)?;
+    let second = new_member(connection)?;
+    let copy = add_copy(book_id, &barcode(), day(1), connection)?;
+    let loan = checkout(copy.id, borrower, day(1), &policy, connection)?;
+
+    assert_eq!(
+        lending_error(place_hold(book_id, borrower, day(2), connection).unwrap_err()),
+        LendingError::AlreadyBorrowed {
+            book_id,
+            member_id: borrower
+        }
+    );
+
+    let first_hold = place_hold(book_id, first, day(2), connection)?;
+    let second_hold = place_hold(book_id, second, day(3), connection)?;
+
+    assert_eq!(first_hold.status, "waiting");
+    assert_eq!(
+        lending_error(place_hold(book_id, first, day(4), connection).unwrap_err()),
+        LendingError::HoldExists {
+            book_id,
+            member_id: first
+        }
+    );
+    assert_eq!(
+        lending_error(renew(loan.id, day(5), &policy, connection).unwrap_err()),
+        LendingError::HoldsWaiting {
+            loan_id: loan.id,
+            book_id
+        }
+    );
+
+    let returned = checkin(copy.id, day(6), connection)?;
+    let ready = returned.hold.unwrap();
+
+    assert_eq!(ready.id, first_hold.id);
+    assert_eq!(ready.status, HoldStatus::Ready.as_str());
+    assert_eq!(ready.copy_id, Some(copy.id));
+    assert_eq!(
+        lending_error(checkout(copy.id, second, day(6), &policy, connection).unwrap_err()),
+        LendingError::CopyOnHold {
+            copy_id: copy.id,
+            hold_id: first_hold.id
+        }
+    );
+
+    // The first member gives up their place, so the copy moves down the queue.
+    cancel_hold(first_hold.id, day(7), connection)?;
+
+    let queue = get_hold_queue(book_id, connection)?;
+
+    assert_eq!(queue.len(), 1);
+    assert_eq!(queue[0].id, second_hold.id);
+    assert_eq!(queue[0].status, "ready");
+    assert_eq!(
+        lending_error(cancel_hold(first_hold.id, day(8), connection).unwrap_err()),
+        LendingError::HoldClosed {
+            hold_id: first_hold.id,
+            status: HoldStatus::Cancelled
+        }
+    );
+
+    checkout(copy.id, second, day(8), &policy, connection)?;
+    assert!(get_hold_queue(book_id, connection)?.is_empty());
+
+    // A new copy goes straight to whoever is waiting.
+    let third_hold = place_hold(book_id, first, day(9), connection)?;
+
+    assert_eq!(third_hold.status, "waiting");
+
+    let new_copy = add_copy(book_id, &barcode(), day(10), connection)?;
+    let queue = get_hold_queue(book_id, connection)?;
+
+    assert_eq!(queue[0].id, third_hold.id);
+    assert_eq!(queue[0].copy_id, Some(new_copy.id));
+
+    // With a copy on the shelf a hold is ready as soon as it is placed.
+    let shelf_copy = add_copy(book_id, &barcode(), day(11), connection)?;
+    let instant = place_hold(book_id, borrower, day(12), connection)?;
+
+    assert_eq!(instant.status, "ready");
+    assert_eq!(instant.copy_id, Some(shelf_copy.id));
+    Ok(())
+}
+
+#[test]
+fn overdue_report_lists_open_loans_past_due_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let connection = &mut establish(&format!(
+        "sqlite://{}",
+        directory.path().join("lending.db").display()
+    ))?;
+    let policy = LoanPolicy {
+        loan_days: 7,
+        max_renewals: 1,
+    };
+    let book_id = create_book("The Overdue Book", connection)?;
+    let late = create_member("Late Reader", "late@example.com", connection)?;
+    let later = create_member("Later Reader", "later@example.com", connection)?;
+    let punctual = create_member("Punctual Reader", "punctual@example.com", connection)?;
+    let copies = (1..=3)
+        .map(|number| add_copy(book_id, &format!("OD-{number}"), day(1), connection))
+        .collect::<Result<Vec<_>>>()?;
+
+    checkout(copies[0].id, later, day(3), &policy, connection)?;
+    checkout(copies[1].id, late, day(1), &policy, connection)?;
+    checkout(copies[2].id, punctual, day(1), &policy, connection)?;
+    checkin(copies[2].id, day(5), connection)?;
+
+    let report = get_overdue_report(day(12), connection)?;
+
+    assert_eq!(
+        report
+            .iter()
+            .map(|loan| (
+                loan.member_name.as_str(),
+                loan.barcode.as_str(),
+                loan.days_overdue
+            ))
+            .collect::<Vec<_>>(),
+        vec![("Late Reader", "OD-2", 4), ("Later Reader", "OD-1", 2)]
+    );
+    assert_eq!(report[0].book_name, "The Overdue Book");
+    assert!(get_overdue_report(day(8), connection)?.is_empty());
+    Ok(())
+}
+
+/// Lending locks the book, but copies, loans and holds are not part of the book, so none of
+/// it may mark the book as changed.
+fn lend_without_changing_book(connection: &mut DbConnection) -> Result<()> {
+    let policy = LoanPolicy {
+        loan_days: 14,
+        max_renewals: 1,
+    };
+    let book_id = create_book(&random_name("lent book"), connection)?;
+
+    thread::sleep(StdDuration::from_millis(10));
+
+    let since = Utc::now().naive_utc();
+
+    let copy = add_copy(book_id, &barcode(), day(1), connection)?;
+    let member_id = new_member(connection)?;
+
+    checkout(copy.id, member_id, day(2), &policy, connection)?;
+    place_hold(book_id, new_member(connection)?, day(3), connection)?;
+    checkin(copy.id, day(4), connection)?;
+
+    assert!(
+        !get_books_changed_since_page(since, None, 10_000, connection)?
+            .iter()
+            .any(|book| book.id == book_id)
+    );
+    Ok(())
+}
+
+#[test]
+fn lending_leaves_the_book_unchanged_test() -> Result<()> {
+    lend_without_changing_book(&mut connect()?)
+}
+
+#[test]
+fn lending_leaves_the_book_unchanged_on_sqlite_test() -> Result<()> {
+    let directory = TempDir::new()?;
+
+    lend_without_changing_book(&mut establish(&format!(
+        "sqlite://{}",
+        directory.path().join("lending.db").display()
+    ))?)
+}
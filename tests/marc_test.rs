@@ -0,0 +1,363 @@
+use diesel_bookstore_assessment::{
+    connect::{establish, DbConnection},
+    marc::{
+        export_marc, import_marc, read_marc, read_marc21, read_marcxml, write_marc21, MarcField,
+        MarcRecord, MarcSyntax, MarcXmlWriter, SkippedField, SkippedRecord, Subfield,
+    },
+    models::IdentifierScheme,
+    queries::{
+        author_queries::create_author,
+        book_author_queries::{get_all_books_and_authors, get_book_with_authors},
+        book_identifier_queries::{get_book_by_identifier, get_identifiers_for_book},
+    },
+};
+use eyre::Result;
+use tempfile::TempDir;
+
+const MARCXML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<marc:collection xmlns:marc="http://www.loc.gov/MARC21/slim">
+  <marc:record>
+    <marc:leader>00000cam a2200000 i 4500</marc:leader>
+    <marc:controlfield tag="001">ocm123</marc:controlfield>
+    <marc:controlfield tag="008">851113s1851    nyu           000 1 eng  </marc:controlfield>
+    <marc:datafield tag="020" ind1=" " ind2=" ">
+      <marc:subfield code="a">0142437247 (pbk.)</marc:subfield>
+      <marc:subfield code="z">0000000000</marc:subfield>
+    </marc:datafield>
+    <marc:datafield tag="100" ind1="1" ind2=" ">
+      <marc:subfield code="a">Melville, Herman,</marc:subfield>
+      <marc:subfield code="d">1819-1891.</marc:subfield>
+    </marc:datafield>
+    <marc:datafield tag="245" ind1="1" ind2="0">
+      <marc:subfield code="a">Moby-Dick, or, The whale :</marc:subfield>
+      <marc:subfield code="b">a novel &amp; more /</marc:subfield>
+      <marc:subfield code="c">Herman Melville.</marc:subfield>
+    </marc:datafield>
+    <marc:datafield tag="650" ind1=" " ind2="0">
+      <marc:subfield code="a">Whaling</marc:subfield>
+    </marc:datafield>
+    <marc:datafield tag="700" ind1="1" ind2=" ">
+      <marc:subfield code="a">King, Martin Luther,</marc:subfield>
+      <marc:subfield code="c">Jr.</marc:subfield>
+    </marc:datafield>
+    <marc:datafield tag="700" ind1="0" ind2=" ">
+      <marc:subfield code="a">Homer</marc:subfield>
+    </marc:datafield>
+  </marc:record>
+  <marc:record>
+    <marc:leader>00000cam a2200000 i 4500</marc:leader>
+    <marc:datafield tag="100" ind1="1" ind2=" ">
+      <marc:subfield code="a">Irving, Washington</marc:subfield>
+    </marc:datafield>
+  </marc:record>
+</marc:collection>
+"#;
+
+fn sqlite_connection(directory: &TempDir, name: &str) -> Result<DbConnection> {
+    let database_path = directory.path().join(name);
+
+    establish(&format!("sqlite://{}", database_path.display()))
+}
+
+fn data_field(tag: &str, indicators: [char; 2], subfields: &[(char, &str)]) -> MarcField {
+    MarcField::Data {
+        tag: tag.to_owned(),
+        indicators,
+        subfields: subfields
+            .iter()
+            .map(|(code, value)| Subfield {
+                code: *code,
+                value: value.to_string(),
+            })
+            .collect(),
+    }
+}
+
+fn sample_record() -> MarcRecord {
+    MarcRecord::new(vec![
+        MarcField::Control {
+            tag: "001".to_owned(),
+            value: "42".to_owned(),
+        },
+        data_field("100", ['1', ' '], &[('a', "García Márquez, Gabriel")]),
+        data_field(
+            "245",
+            ['1', '0'],
+            &[('a', "Cien años de soledad <&> \"novela\"")],
+        ),
+    ])
+}
+
+#[test]
+fn marc21_round_trips_records_test() -> Result<()> {
+    let records = [sample_record(), MarcRecord::new(Vec::new())];
+    let mut bytes = Vec::new();
+
+    for record in &records {
+        write_marc21(record, &mut bytes)?;
+    }
+
+    let record_length = std::str::from_utf8(&bytes[..5])?.parse::<usize>()?;
+
+    assert_eq!(bytes[record_length - 1], 0x1d);
+    assert_eq!(&bytes[20..24], b"4500");
+
+    let read = read_marc21(bytes.as_slice())?;
+
+    assert_eq!(read.len(), 2);
+    assert_eq!(read[0].fields, records[0].fields);
+    assert_eq!(&read[0].leader[5..12], "nam a22");
+    assert!(read[1].fields.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn marc21_rejects_truncated_records_test() -> Result<()> {
+    let mut bytes = Vec::new();
+
+    write_marc21(&sample_record(), &mut bytes)?;
+    bytes.truncate(bytes.len() - 3);
+
+    let error = read_marc21(bytes.as_slice()).unwrap_err();
+
+    assert!(format!("{error:#}").contains("record 1"));
+
+    Ok(())
+}
+
+#[test]
+fn marcxml_round_trips_records_test() -> Result<()> {
+    let mut writer = MarcXmlWriter::new(Vec::new())?;
+
+    writer.write_record(&sample_record())?;
+
+    let xml = String::from_utf8(writer.finish()?)?;
+
+    assert!(xml.contains("&lt;&amp;&gt; &quot;novela&quot;"));
+
+    let records = read_marcxml(xml.as_bytes())?;
+
+    assert_eq!(records, [sample_record()]);
+
+    Ok(())
+}
+
+#[test]
+fn marcxml_reads_prefixed_records_test() -> Result<()> {
+    let records = read_marcxml(MARCXML.as_bytes())?;
+
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].fields.len(), 8);
+    assert_eq!(
+        records[0].fields[4].subfields('b').collect::<Vec<&str>>(),
+        ["a novel & more /"]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn import_marc_maps_title_authors_and_isbn_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let connection = &mut sqlite_connection(&directory, "bookstore.sqlite3")?;
+    let existing_author_id = create_author("Homer", connection)?;
+    let records = read_marc(MarcSyntax::Xml, MARCXML.as_bytes())?;
+
+    let report = import_marc(&records, connection)?;
+
+    assert_eq!(report.books_created, 1);
+    assert_eq!(report.authors_created, 2);
+    assert_eq!(report.links_created, 3);
+    assert_eq!(
+        report.skipped_records,
+        [SkippedRecord {
+            record: 2,
+            reason: "no usable 245 title".to_owned(),
+        }]
+    );
+    assert_eq!(
+        report
+            .skipped_fields
+            .iter()
+            .map(|skipped| (
+                skipped.record,
+                skipped.tag.as_str(),
+                skipped.reason.as_str()
+            ))
+            .collect::<Vec<(usize, &str, &str)>>(),
+        [
+            (1, "001", "not mapped"),
+            (1, "008", "not mapped"),
+            (1, "020", "cancelled isbn `0000000000`"),
+            (1, "650", "not mapped"),
+        ]
+    );
+
+    let book = get_book_by_identifier(IdentifierScheme::Isbn, "0-14-243724-7", connection)?
+        .expect("imported book");
+    let (book, authors) = get_book_with_authors(book.id, connection)?
+        .expect("imported book")
+        .into_parts();
+    let mut author_names = authors
+        .iter()
+        .map(|author| author.name.as_str())
+        .collect::<Vec<&str>>();
+
+    author_names.sort();
+
+    assert_eq!(book.name, "Moby-Dick, or, The whale a novel & more");
+    assert_eq!(
+        author_names,
+        ["Herman Melville", "Homer", "Martin Luther King Jr."]
+    );
+    assert!(authors.iter().any(|author| author.id == existing_author_id));
+    assert_eq!(
+        get_identifiers_for_book(book.id, connection)?[0].value,
+        "9780142437247"
+    );
+
+    let report = import_marc(&records[..1], connection)?;
+
+    assert_eq!(report.books_created, 0);
+    assert_eq!(
+        report.skipped_records[0].reason,
+        format!("isbn 9780142437247 already belongs to book {}", book.id)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn import_marc_reports_invalid_fields_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let connection = &mut sqlite_connection(&directory, "bookstore.sqlite3")?;
+    let record = MarcRecord::new(vec![
+        data_field("020", [' ', ' '], &[('a', "12345")]),
+        data_field("100", ['1', ' '], &[('a', " , ")]),
+        data_field("245", ['0', '0'], &[('a', "Beowulf.")]),
+    ]);
+
+    let report = import_marc(&[record], connection)?;
+
+    assert_eq!(report.books_created, 1);
+    assert_eq!(
+        report.skipped_fields,
+        [
+            SkippedField {
+                record: 1,
+                tag: "020".to_owned(),
+                reason: "isbn must be a valid ISBN-10 or ISBN-13: `12345`".to_owned(),
+            },
+            SkippedField {
+                record: 1,
+                tag: "100".to_owned(),
+                reason: "author must not be empty".to_owned(),
+            },
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn import_marc_keeps_abbreviations_and_ignores_repeated_titles_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let connection = &mut sqlite_connection(&directory, "bookstore.sqlite3")?;
+    let records = [
+        MarcRecord::new(vec![
+            data_field(
+                "245",
+                ['1', '0'],
+                &[('a', "Made in U.S.A."), ('b', "stories /")],
+            ),
+            data_field("245", ['1', '0'], &[('a', "Made in America.")]),
+        ]),
+        MarcRecord::new(vec![data_field(
+            "245",
+            ['1', '0'],
+            &[('a', "Letters of Sammy Davis, Jr.")],
+        )]),
+        MarcRecord::new(vec![data_field(
+            "245",
+            ['1', '0'],
+            &[('a', "The whale ;"), ('b', "a novel.")],
+        )]),
+    ];
+
+    let report = import_marc(&records, connection)?;
+    let mut titles = get_all_books_and_authors(connection)?
+        .into_iter()
+        .map(|book_with_authors| book_with_authors.book().name.clone())
+        .collect::<Vec<String>>();
+
+    titles.sort();
+
+    assert_eq!(
+        titles,
+        [
+            "Letters of Sammy Davis, Jr.",
+            "Made in U.S.A. stories",
+            "The whale a novel",
+        ]
+    );
+    assert_eq!(
+        report.skipped_fields,
+        [SkippedField {
+            record: 1,
+            tag: "245".to_owned(),
+            reason: "repeated 245 ignored".to_owned(),
+        }]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn exported_marc_imports_into_empty_catalog_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let source = &mut sqlite_connection(&directory, "source.sqlite3")?;
+    let records = read_marcxml(MARCXML.as_bytes())?;
+
+    import_marc(&records, source)?;
+
+    let names = |connection: &mut DbConnection| -> Result<Vec<(String, Vec<String>)>> {
+        Ok(get_all_books_and_authors(connection)?
+            .into_iter()
+            .map(|book_with_authors| {
+                let (book, authors) = book_with_authors.into_parts();
+                let mut authors = authors
+                    .into_iter()
+                    .map(|author| author.name)
+                    .collect::<Vec<String>>();
+
+                authors.sort();
+
+                (book.name, authors)
+            })
+            .collect())
+    };
+
+    for (syntax, name) in [
+        (MarcSyntax::Binary, "binary.sqlite3"),
+        (MarcSyntax::Xml, "xml.sqlite3"),
+    ] {
+        let destination = &mut sqlite_connection(&directory, name)?;
+        let mut exported = Vec::new();
+
+        assert_eq!(export_marc(syntax, &mut exported, 1, source)?, 1);
+
+        let records = read_marc(syntax, exported.as_slice())?;
+        let report = import_marc(&records, destination)?;
+
+        assert_eq!(report.books_created, 1);
+        assert_eq!(report.skipped_fields.len(), 1);
+        assert_eq!(report.skipped_fields[0].tag, "001");
+        assert_eq!(names(destination)?, names(source)?);
+        assert!(
+            get_book_by_identifier(IdentifierScheme::Isbn, "9780142437247", destination)?.is_some()
+        );
+    }
+
+    Ok(())
+}
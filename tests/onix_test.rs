@@ -0,0 +1,202 @@
+mod utilities;
+
+use std::{env, fs, path::PathBuf, thread::sleep, time::Duration};
+
+use chrono::{TimeZone, Utc};
+use diesel_bookstore_assessment::{
+    connect::{connect, establish, DbConnection},
+    models::{AuthorNameKind, IdentifierScheme},
+    onix::{export_onix, OnixOptions, RejectedProduct},
+    queries::{
+        author_name_queries::{add_author_name, remove_author_name, set_published_name},
+        author_queries::{create_author, update_author},
+        book_author_queries::associate_book_with_author,
+        book_identifier_queries::add_book_identifier,
+        book_queries::{create_book, get_books_changed_since_page, update_book},
+        work_queries::{add_edition_to_work, create_work, credit_author_on_work},
+    },
+};
+use eyre::Result;
+use tempfile::TempDir;
+use utilities::random_name;
+
+fn seeded_connection(directory: &TempDir) -> Result<DbConnection> {
+    let database_path = directory.path().join("bookstore.sqlite3");
+    let connection = &mut establish(&format!("sqlite://{}", database_path.display()))?;
+    let moby_dick = create_book("Moby Dick", connection)?;
+    let good_omens = create_book("Good Omens & <Other> Stories", connection)?;
+    let pratchett = create_author("Terry Pratchett", connection)?;
+    let gaiman = create_author("Neil Gaiman", connection)?;
+
+    create_book("Beowulf", connection)?;
+    add_book_identifier(moby_dick, IdentifierScheme::Isbn, "0142437247", connection)?;
+    associate_book_with_author(
+        moby_dick,
+        create_author("Herman Melville", connection)?,
+        connection,
+    )?;
+    associate_book_with_author(good_omens, pratchett, connection)?;
+    associate_book_with_author(good_omens, gaiman, connection)?;
+
+    establish(&format!("sqlite://{}", database_path.display()))
+}
+
+fn options() -> OnixOptions {
+    OnixOptions {
+        publisher_name: Some("Brooks Books".to_owned()),
+        sent_at: Utc.with_ymd_and_hms(2024, 10, 17, 12, 30, 0).unwrap(),
+        ..OnixOptions::new("Brooks Builds")
+    }
+}
+
+#[test]
+fn export_onix_matches_golden_file_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let connection = &mut seeded_connection(&directory)?;
+    let mut output = Vec::new();
+
+    let report = export_onix(&mut output, &options(), 2, connection)?;
+
+    assert_eq!(report.products_written, 3);
+    assert!(report.rejected.is_empty());
+
+    let actual = String::from_utf8(output)?;
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden/onix/full_feed.xml");
+
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, &actual)?;
+    }
+
+    assert_eq!(actual, fs::read_to_string(&path)?);
+
+    Ok(())
+}
+
+#[test]
+fn export_onix_leaves_out_invalid_products_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let connection = &mut seeded_connection(&directory)?;
+    let mut output = Vec::new();
+    let options = OnixOptions {
+        require_isbn: true,
+        ..options()
+    };
+
+    let report = export_onix(&mut output, &options, 10, connection)?;
+    let output = String::from_utf8(output)?;
+
+    assert_eq!(report.products_written, 1);
+    assert_eq!(
+        report.rejected,
+        [2, 3].map(|book_id| RejectedProduct {
+            book_id,
+            problems: vec!["no ProductIdentifier with an ISBN-13".to_owned()],
+        })
+    );
+    assert_eq!(output.matches("<Product>").count(), 1);
+    assert!(output.contains("<IDValue>9780142437247</IDValue>"));
+
+    let error = export_onix(Vec::new(), &OnixOptions::new("  "), 10, connection).unwrap_err();
+
+    assert_eq!(error.to_string(), "SenderName must not be empty");
+
+    Ok(())
+}
+
+#[test]
+fn export_onix_delta_only_includes_changed_books_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let connection = &mut seeded_connection(&directory)?;
+
+    sleep(Duration::from_millis(10));
+
+    let since = Utc::now().naive_utc();
+
+    update_book(3, "Beowulf: A New Translation", connection)?;
+
+    let mut output = Vec::new();
+    let options = OnixOptions {
+        since: Some(since),
+        ..options()
+    };
+    let report = export_onix(&mut output, &options, 10, connection)?;
+    let output = String::from_utf8(output)?;
+
+    assert_eq!(report.products_written, 1);
+    assert!(output.contains("<TitleText>Beowulf: A New Translation</TitleText>"));
+
+    Ok(())
+}
+
+#[test]
+fn changes_to_credits_and_identifiers_mark_books_changed_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let renamed_book = create_book(&random_name("onix renamed book"), connection)?;
+    let credited_book = create_book(&random_name("onix credited book"), connection)?;
+    let identified_book = create_book(&random_name("onix identified book"), connection)?;
+    let untouched_book = create_book(&random_name("onix untouched book"), connection)?;
+    let author_id = create_author(&random_name("onix author"), connection)?;
+    let isbn = format!("979{:09}", rand::random::<u32>() % 1_000_000_000);
+    let isbn = format!("{isbn}{}", isbn13_check_digit(&isbn));
+
+    associate_book_with_author(credited_book, author_id, connection)?;
+    sleep(Duration::from_millis(10));
+
+    let since = Utc::now().naive_utc();
+
+    update_book(renamed_book, &random_name("onix renamed book"), connection)?;
+    update_author(author_id, &random_name("onix renamed author"), connection)?;
+    add_book_identifier(identified_book, IdentifierScheme::Isbn, &isbn, connection)?;
+
+    let changed = get_books_changed_since_page(since, Some(renamed_book - 1), 1000, connection)?
+        .into_iter()
+        .map(|book| book.id)
+        .collect::<Vec<i32>>();
+
+    assert!(changed.contains(&renamed_book));
+    assert!(changed.contains(&credited_book));
+    assert!(changed.contains(&identified_book));
+    assert!(!changed.contains(&untouched_book));
+
+    Ok(())
+}
+
+#[test]
+fn sqlite_triggers_mark_books_changed_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let connection = &mut seeded_connection(&directory)?;
+    let work_id = create_work("Beowulf", connection)?;
+    let melville = 3;
+    let pen_name = add_author_name(melville, "H. M.", AuthorNameKind::Variant, connection)?;
+
+    create_book("Mort", connection)?;
+    add_edition_to_work(3, work_id, connection)?;
+    set_published_name(1, pen_name, connection)?;
+    sleep(Duration::from_millis(10));
+
+    let since = Utc::now().naive_utc();
+
+    update_author(1, "Sir Terry Pratchett", connection)?;
+    credit_author_on_work(work_id, 2, connection)?;
+    remove_author_name(pen_name, connection)?;
+
+    let changed = get_books_changed_since_page(since, None, 1000, connection)?
+        .into_iter()
+        .map(|book| book.id)
+        .collect::<Vec<i32>>();
+
+    assert_eq!(changed, vec![1, 2, 3]);
+
+    Ok(())
+}
+
+fn isbn13_check_digit(digits: &str) -> u32 {
+    let sum = digits
+        .chars()
+        .filter_map(|digit| digit.to_digit(10))
+        .zip([1, 3].into_iter().cycle())
+        .map(|(digit, weight)| digit * weight)
+        .sum::<u32>();
+
+    (10 - sum % 10) % 10
+}
@@ -0,0 +1,264 @@
+#[cfg(feature = "server")]
+mod utilities;
+
+use std::{env, fs, path::PathBuf};
+
+use chrono::{TimeZone, Utc};
+use diesel_bookstore_assessment::{
+    connect::{establish, DbConnection},
+    models::IdentifierScheme,
+    opds::{
+        author_books_feed, authors_feed, books_feed, opensearch_description, root_feed,
+        search_feed, OpdsCatalog,
+    },
+    queries::{
+        author_queries::create_author, book_author_queries::associate_book_with_author,
+        book_identifier_queries::add_book_identifier, book_queries::create_book,
+    },
+};
+use eyre::Result;
+use tempfile::TempDir;
+
+fn seeded_connection(directory: &TempDir) -> Result<DbConnection> {
+    let database_path = directory.path().join("bookstore.sqlite3");
+    let connection = &mut establish(&format!("sqlite://{}", database_path.display()))?;
+    let moby_dick = create_book("Moby Dick", connection)?;
+    let good_omens = create_book("Good Omens", connection)?;
+    let small_gods = create_book("Small Gods", connection)?;
+    let melville = create_author("Herman Melville", connection)?;
+    let pratchett = create_author("Terry Pratchett", connection)?;
+    let gaiman = create_author("Neil Gaiman", connection)?;
+
+    create_book("Beowulf & <Grendel>", connection)?;
+    create_author("Unpublished Author", connection)?;
+    add_book_identifier(moby_dick, IdentifierScheme::Isbn, "0142437247", connection)?;
+    associate_book_with_author(moby_dick, melville, connection)?;
+    associate_book_with_author(good_omens, pratchett, connection)?;
+    associate_book_with_author(good_omens, gaiman, connection)?;
+    associate_book_with_author(small_gods, pratchett, connection)?;
+
+    establish(&format!("sqlite://{}", database_path.display()))
+}
+
+fn catalog() -> OpdsCatalog {
+    OpdsCatalog {
+        updated: Utc.with_ymd_and_hms(2024, 10, 17, 12, 30, 0).unwrap(),
+        ..OpdsCatalog::new("https://books.example.com/").unwrap()
+    }
+}
+
+fn assert_golden(name: &str, actual: &str) -> Result<()> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden/opds")
+        .join(name);
+
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, actual)?;
+    }
+
+    assert_eq!(actual, fs::read_to_string(&path)?, "{name}");
+
+    Ok(())
+}
+
+#[test]
+fn navigation_feeds_match_golden_files_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let connection = &mut seeded_connection(&directory)?;
+
+    assert_golden("root.xml", &root_feed(&catalog())?)?;
+    assert_golden("authors.xml", &authors_feed(&catalog(), connection)?)?;
+    assert_golden("opensearch.xml", &opensearch_description(&catalog())?)?;
+
+    Ok(())
+}
+
+#[test]
+fn acquisition_feeds_match_golden_files_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let connection = &mut seeded_connection(&directory)?;
+
+    assert_golden(
+        "author_books.xml",
+        &author_books_feed(&catalog(), 2, connection)?.unwrap(),
+    )?;
+    assert_golden(
+        "books_page.xml",
+        &books_feed(&catalog(), None, 2, connection)?,
+    )?;
+    assert!(author_books_feed(&catalog(), 99, connection)?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn books_feed_pages_until_the_last_book_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let connection = &mut seeded_connection(&directory)?;
+
+    let first = books_feed(&catalog(), None, 3, connection)?;
+    let last = books_feed(&catalog(), Some(3), 3, connection)?;
+
+    assert!(
+        first.contains(r#"<link rel="next" href="https://books.example.com/opds/books?after=3""#)
+    );
+    assert_eq!(first.matches("<entry>").count(), 3);
+    assert!(!last.contains(r#"rel="next""#));
+    assert_eq!(last.matches("<entry>").count(), 1);
+    assert!(last.contains("<title>Beowulf &amp; &lt;Grendel&gt;</title>"));
+
+    Ok(())
+}
+
+#[test]
+fn search_feed_matches_titles_and_authors_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let connection = &mut seeded_connection(&directory)?;
+
+    let by_author = search_feed(&catalog(), "pratchett", None, 1, connection)?;
+    let by_title = search_feed(&catalog(), "MOBY", None, 10, connection)?;
+    let wildcard = search_feed(&catalog(), "%", None, 10, connection)?;
+
+    assert_golden("search.xml", &by_author)?;
+    assert_eq!(by_title.matches("<entry>").count(), 1);
+    assert!(by_title.contains("<title>Moby Dick</title>"));
+    assert_eq!(wildcard.matches("<entry>").count(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn search_feed_ignores_case_outside_ascii_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let connection = &mut seeded_connection(&directory)?;
+    let germinal = create_book("Germinal", connection)?;
+
+    create_book("Ästhetik des Widerstands", connection)?;
+    associate_book_with_author(
+        germinal,
+        create_author("Émile Zola", connection)?,
+        connection,
+    )?;
+
+    let by_title = search_feed(&catalog(), "ästhetik", None, 10, connection)?;
+    let by_author = search_feed(&catalog(), "ÉMILE", None, 10, connection)?;
+
+    assert!(by_title.contains("<title>Ästhetik des Widerstands</title>"));
+    assert!(by_author.contains("<title>Germinal</title>"));
+
+    Ok(())
+}
+
+#[test]
+fn catalogs_need_an_absolute_base_url_test() {
+    for base_url in [
+        "",
+        "/",
+        "books.example.com",
+        "/catalog",
+        "https://",
+        "ftp://books",
+    ] {
+        assert!(OpdsCatalog::new(base_url).is_err(), "{base_url}");
+    }
+
+    assert_eq!(
+        OpdsCatalog::new("http://localhost:3000/").unwrap().base_url,
+        "http://localhost:3000"
+    );
+}
+
+#[cfg(feature = "server")]
+mod server {
+    use super::utilities::random_name;
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+    };
+    use diesel_bookstore_assessment::{
+        api::router,
+        connect::{build_pool, connect},
+        opds::{OpdsCatalog, ACQUISITION_TYPE, NAVIGATION_TYPE, OPENSEARCH_TYPE},
+        queries::{
+            author_queries::create_author, book_author_queries::associate_book_with_author,
+            book_queries::create_book,
+        },
+    };
+    use eyre::Result;
+    use http_body_util::BodyExt;
+    use std::env;
+    use tower::ServiceExt;
+
+    async fn get(uri: &str) -> Result<(StatusCode, String, String)> {
+        dotenvy::dotenv().ok();
+
+        let app = router(
+            build_pool(&env::var("DATABASE_URL")?)?,
+            OpdsCatalog::new("http://localhost:3000")?,
+        );
+        let response = app
+            .oneshot(Request::builder().uri(uri).body(Body::empty())?)
+            .await?;
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_owned();
+        let body = String::from_utf8(response.into_body().collect().await?.to_bytes().to_vec())?;
+
+        Ok((status, content_type, body))
+    }
+
+    #[tokio::test]
+    async fn opds_routes_are_mounted_test() -> Result<()> {
+        let connection = &mut connect()?;
+        let book_name = random_name("opds book");
+        let author_name = random_name("opds author");
+        let book_id = create_book(&book_name, connection)?;
+        let author_id = create_author(&author_name, connection)?;
+
+        associate_book_with_author(book_id, author_id, connection)?;
+
+        let (status, content_type, body) = get("/opds").await?;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, NAVIGATION_TYPE);
+        assert!(body.contains(r#"href="http://localhost:3000/opds/authors""#));
+
+        let (status, _, body) = get("/opds/authors").await?;
+
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains(&format!(
+            r#"href="http://localhost:3000/opds/authors/{author_id}""#
+        )));
+
+        let (status, content_type, body) = get(&format!("/opds/authors/{author_id}")).await?;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, ACQUISITION_TYPE);
+        assert!(body.contains(&format!("<title>{book_name}</title>")));
+
+        let (status, _, body) = get(&format!(
+            "/opds/search?q={}",
+            author_name.replace(' ', "%20")
+        ))
+        .await?;
+
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains(&format!("<id>urn:bookstore:book:{book_id}</id>")));
+
+        let (status, content_type, body) = get("/opds/opensearch.xml").await?;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, OPENSEARCH_TYPE);
+        assert!(body.contains(r#"template="http://localhost:3000/opds/search?q={searchTerms}""#));
+
+        let (status, _, _) = get("/opds/authors/0").await?;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        Ok(())
+    }
+}
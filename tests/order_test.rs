@@ -0,0 +1,343 @@
+mod utilities;
+
+use std::thread;
+
+use chrono::{NaiveDate, Utc};
+use diesel_bookstore_assessment::{
+    connect::{connect, establish, DbConnection},
+    models::OrderStatus,
+    queries::{
+        book_queries::create_book,
+        customer_queries::{create_customer, get_customer_by_email},
+        inventory_queries::{create_location, get_stock_level, receive_stock, InsufficientStock},
+        order_queries::{
+            add_to_cart, create_cart, get_order_by_id, get_order_lines, get_order_total,
+            place_order, remove_from_cart, set_order_status, OrderError,
+        },
+        price_queries::set_price,
+    },
+    validation::ValidationError,
+};
+use eyre::Result;
+use tempfile::TempDir;
+use utilities::random_name;
+
+fn new_customer(connection: &mut DbConnection) -> Result<i32> {
+    create_customer(
+        &random_name("customer"),
+        &format!("Reader.{}@Example.com", rand::random::<u32>()),
+        connection,
+    )
+}
+
+/// A priced book with `stock` copies at a new location.
+fn stocked_book(price: i64, stock: i32, connection: &mut DbConnection) -> Result<(i32, i32)> {
+    let book_id = create_book(&random_name("ordered book"), connection)?;
+    // Location names are unique and every run leaves its locations behind, which is more
+    // than `random_name` has room for.
+    let location_id = create_location(&format!("warehouse {}", rand::random::<u64>()), connection)?;
+    let long_ago = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap().into();
+
+    set_price(book_id, "USD", price, long_ago, connection)?;
+    receive_stock(book_id, location_id, stock, connection)?;
+
+    Ok((book_id, location_id))
+}
+
+fn quantity(book_id: i32, location_id: i32, connection: &mut DbConnection) -> Result<i32> {
+    Ok(get_stock_level(book_id, location_id, connection)?
+        .unwrap()
+        .quantity)
+}
+
+#[test]
+fn customers_are_found_by_normalized_email_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let email = format!("Someone.{}@Example.COM", rand::random::<u32>());
+    let customer_id = create_customer(&random_name("customer"), &email, connection)?;
+    let customer = get_customer_by_email(&email, connection)?.unwrap();
+
+    assert_eq!(customer.id, customer_id);
+    assert_eq!(customer.email, email.to_lowercase());
+
+    let error = create_customer("Nobody", "not an email", connection).unwrap_err();
+
+    assert_eq!(
+        error.downcast_ref::<ValidationError>().unwrap().field,
+        "email"
+    );
+    Ok(())
+}
+
+#[test]
+fn cart_lines_are_merged_removed_and_totalled_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let customer_id = new_customer(connection)?;
+    let (first_book, _) = stocked_book(1250, 10, connection)?;
+    let (second_book, _) = stocked_book(999, 10, connection)?;
+    let order_id = create_cart(customer_id, "usd", connection)?;
+
+    add_to_cart(order_id, first_book, 1, connection)?;
+    add_to_cart(order_id, second_book, 1, connection)?;
+
+    let line = add_to_cart(order_id, first_book, 2, connection)?;
+
+    assert_eq!(line.quantity, 3);
+    assert_eq!(get_order_lines(order_id, connection)?.len(), 2);
+
+    let total = get_order_total(order_id, Utc::now().naive_utc(), connection)?;
+
+    assert_eq!(total.currency, "USD");
+    assert_eq!(total.total_minor, 3 * 1250 + 999);
+
+    assert!(remove_from_cart(order_id, second_book, connection)?);
+    assert!(!remove_from_cart(order_id, second_book, connection)?);
+    assert_eq!(
+        get_order_total(order_id, Utc::now().naive_utc(), connection)?.total_minor,
+        3 * 1250
+    );
+    assert!(add_to_cart(order_id, first_book, 0, connection).is_err());
+    Ok(())
+}
+
+#[test]
+fn placing_an_order_sells_stock_and_records_prices_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let customer_id = new_customer(connection)?;
+    let (book_id, location_id) = stocked_book(1500, 5, connection)?;
+    let order_id = create_cart(customer_id, "USD", connection)?;
+
+    add_to_cart(order_id, book_id, 2, connection)?;
+
+    let order = place_order(order_id, location_id, connection)?;
+
+    assert_eq!(order.status, "pending");
+    assert_eq!(order.total_minor, Some(3000));
+    assert_eq!(order.location_id, Some(location_id));
+    assert!(order.placed_at.is_some());
+    assert_eq!(quantity(book_id, location_id, connection)?, 3);
+    assert_eq!(
+        get_order_lines(order_id, connection)?[0].unit_price_minor,
+        Some(1500)
+    );
+
+    // Later price changes do not touch what was charged.
+    set_price(book_id, "USD", 9999, Utc::now().naive_utc(), connection)?;
+    assert_eq!(
+        get_order_total(order_id, Utc::now().naive_utc(), connection)?.total_minor,
+        3000
+    );
+
+    let error = add_to_cart(order_id, book_id, 1, connection).unwrap_err();
+
+    assert_eq!(
+        error.downcast_ref::<OrderError>(),
+        Some(&OrderError::NotACart {
+            order_id,
+            status: OrderStatus::Pending
+        })
+    );
+    assert!(place_order(order_id, location_id, connection).is_err());
+    assert_eq!(quantity(book_id, location_id, connection)?, 3);
+    Ok(())
+}
+
+#[test]
+fn failed_placement_leaves_cart_and_stock_untouched_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let customer_id = new_customer(connection)?;
+    let (plenty, location_id) = stocked_book(500, 10, connection)?;
+    let scarce = create_book(&random_name("scarce book"), connection)?;
+
+    set_price(
+        scarce,
+        "USD",
+        700,
+        NaiveDate::from_ymd_opt(2020, 1, 1).unwrap().into(),
+        connection,
+    )?;
+    receive_stock(scarce, location_id, 1, connection)?;
+
+    let order_id = create_cart(customer_id, "USD", connection)?;
+
+    add_to_cart(order_id, plenty, 4, connection)?;
+    add_to_cart(order_id, scarce, 2, connection)?;
+
+    let error = place_order(order_id, location_id, connection).unwrap_err();
+
+    assert_eq!(
+        error.downcast_ref::<InsufficientStock>().unwrap().book_id,
+        scarce
+    );
+    assert_eq!(quantity(plenty, location_id, connection)?, 10);
+    assert_eq!(quantity(scarce, location_id, connection)?, 1);
+
+    let order = get_order_by_id(order_id, connection)?.unwrap();
+
+    assert_eq!(order.status, "cart");
+    assert_eq!(order.total_minor, None);
+
+    let unpriced = create_book(&random_name("unpriced book"), connection)?;
+
+    remove_from_cart(order_id, scarce, connection)?;
+    add_to_cart(order_id, unpriced, 1, connection)?;
+
+    let error = place_order(order_id, location_id, connection).unwrap_err();
+
+    assert_eq!(
+        error.downcast_ref::<OrderError>(),
+        Some(&OrderError::MissingPrice {
+            book_id: unpriced,
+            currency: "USD".to_owned()
+        })
+    );
+
+    let empty_cart = create_cart(customer_id, "USD", connection)?;
+    let error = place_order(empty_cart, location_id, connection).unwrap_err();
+
+    assert_eq!(
+        error.downcast_ref::<OrderError>(),
+        Some(&OrderError::EmptyCart {
+            order_id: empty_cart
+        })
+    );
+    assert_eq!(
+        get_order_by_id(empty_cart, connection)?.unwrap().status,
+        "cart"
+    );
+    Ok(())
+}
+
+#[test]
+fn order_status_transitions_are_enforced_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let customer_id = new_customer(connection)?;
+    let (book_id, location_id) = stocked_book(800, 6, connection)?;
+    let place = |quantity: i32, connection: &mut DbConnection| -> Result<i32> {
+        let order_id = create_cart(customer_id, "USD", connection)?;
+
+        add_to_cart(order_id, book_id, quantity, connection)?;
+        place_order(order_id, location_id, connection)?;
+
+        Ok(order_id)
+    };
+
+    let shipped = place(1, connection)?;
+
+    set_order_status(shipped, OrderStatus::Paid, connection)?;
+    set_order_status(shipped, OrderStatus::Shipped, connection)?;
+
+    let error = set_order_status(shipped, OrderStatus::Cancelled, connection).unwrap_err();
+
+    assert_eq!(
+        error.downcast_ref::<OrderError>(),
+        Some(&OrderError::InvalidTransition {
+            order_id: shipped,
+            from: OrderStatus::Shipped,
+            to: OrderStatus::Cancelled
+        })
+    );
+
+    // Shipped copies are gone, so refunding them does not restock.
+    set_order_status(shipped, OrderStatus::Refunded, connection)?;
+    assert_eq!(quantity(book_id, location_id, connection)?, 5);
+
+    let cancelled = place(2, connection)?;
+
+    assert_eq!(quantity(book_id, location_id, connection)?, 3);
+    assert_eq!(
+        set_order_status(cancelled, OrderStatus::Cancelled, connection)?.status,
+        "cancelled"
+    );
+    assert_eq!(quantity(book_id, location_id, connection)?, 5);
+    assert!(set_order_status(cancelled, OrderStatus::Paid, connection).is_err());
+
+    let refunded = place(3, connection)?;
+
+    set_order_status(refunded, OrderStatus::Paid, connection)?;
+    set_order_status(refunded, OrderStatus::Refunded, connection)?;
+    assert_eq!(quantity(book_id, location_id, connection)?, 5);
+
+    let cart = create_cart(customer_id, "USD", connection)?;
+
+    assert!(set_order_status(cart, OrderStatus::Paid, connection).is_err());
+    Ok(())
+}
+
+/// One thread places a cart while the others keep adding copies to it until they are told it
+/// is no longer a cart. Every copy left in the order has to have been charged and sold.
+fn add_while_placing(database_url: &str, adders: usize) -> Result<()> {
+    let connection = &mut establish(database_url)?;
+    let customer_id = new_customer(connection)?;
+    let (book_id, location_id) = stocked_book(1000, 10_000, connection)?;
+    let order_id = create_cart(customer_id, "USD", connection)?;
+
+    add_to_cart(order_id, book_id, 1, connection)?;
+
+    thread::scope(|scope| {
+        let placing = scope.spawn(|| -> Result<()> {
+            place_order(order_id, location_id, &mut establish(database_url)?)?;
+
+            Ok(())
+        });
+        let adding = (0..adders)
+            .map(|_| {
+                scope.spawn(|| -> Result<()> {
+                    let connection = &mut establish(database_url)?;
+
+                    for _ in 0..1000 {
+                        match add_to_cart(order_id, book_id, 1, connection) {
+                            Ok(_) => {}
+                            Err(error) if error.downcast_ref::<OrderError>().is_some() => break,
+                            Err(error) => return Err(error),
+                        }
+                    }
+
+                    Ok(())
+                })
+            })
+            .collect::<Vec<_>>();
+
+        placing.join().unwrap()?;
+        adding
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Result<Vec<()>>>()
+    })?;
+
+    let order = get_order_by_id(order_id, connection)?.unwrap();
+    let lines = get_order_lines(order_id, connection)?;
+    let ordered = lines.iter().map(|line| line.quantity).sum::<i32>();
+
+    assert_eq!(order.status, "pending");
+    assert_eq!(order.total_minor, Some(i64::from(ordered) * 1000));
+    assert!(lines.iter().all(|line| line.unit_price_minor == Some(1000)));
+    assert_eq!(
+        quantity(book_id, location_id, connection)?,
+        10_000 - ordered
+    );
+
+    Ok(())
+}
+
+#[test]
+fn carts_cannot_change_while_they_are_placed_test() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    let database_url = std::env::var("DATABASE_URL")?;
+
+    add_while_placing(&database_url, 6)
+}
+
+#[test]
+fn carts_cannot_change_while_they_are_placed_on_sqlite_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let database_url = format!(
+        "sqlite://{}",
+        directory.path().join("bookstore.sqlite3").display()
+    );
+
+    establish(&database_url)?;
+
+    add_while_placing(&database_url, 4)
+}
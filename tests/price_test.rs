@@ -0,0 +1,214 @@
+mod utilities;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use diesel_bookstore_assessment::{
+    connect::connect,
+    import::ImportError,
+    pricing::{format_amount, parse_amount, update_prices_csv, PriceChange},
+    queries::{
+        book_queries::create_book,
+        price_queries::{current_price, get_price_history, set_price},
+    },
+    validation::ValidationError,
+};
+use eyre::Result;
+use utilities::random_name;
+
+fn day(day: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 10, day)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+}
+
+fn amount_at(book_id: i32, currency: &str, at: NaiveDateTime) -> Result<Option<i64>> {
+    let connection = &mut connect()?;
+
+    Ok(current_price(book_id, currency, at, connection)?.map(|price| price.amount_minor))
+}
+
+#[test]
+fn price_history_keeps_earlier_prices_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let book_id = create_book(&random_name("priced book"), connection)?;
+
+    set_price(book_id, "usd", 1999, day(1), connection)?;
+    set_price(book_id, "USD", 1499, day(10), connection)?;
+    set_price(book_id, "USD", 2499, day(20), connection)?;
+    set_price(book_id, "EUR", 1799, day(1), connection)?;
+
+    assert_eq!(amount_at(book_id, "USD", day(5))?, Some(1999));
+    assert_eq!(amount_at(book_id, "USD", day(10))?, Some(1499));
+    assert_eq!(amount_at(book_id, "USD", day(25))?, Some(2499));
+    assert_eq!(amount_at(book_id, "eur", day(25))?, Some(1799));
+    assert_eq!(
+        amount_at(
+            book_id,
+            "USD",
+            NaiveDate::from_ymd_opt(2024, 9, 30).unwrap().into()
+        )?,
+        None
+    );
+    assert_eq!(amount_at(book_id, "GBP", day(5))?, None);
+
+    // A sale slotted in between two scheduled prices ends where the next one starts.
+    set_price(book_id, "USD", 999, day(15), connection)?;
+    set_price(book_id, "USD", 1599, day(10), connection)?;
+
+    let usd = get_price_history(book_id, connection)?
+        .into_iter()
+        .filter(|price| price.currency == "USD")
+        .map(|price| (price.amount_minor, price.effective_from, price.effective_to))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        usd,
+        [
+            (1999, day(1), Some(day(10))),
+            (1599, day(10), Some(day(15))),
+            (999, day(15), Some(day(20))),
+            (2499, day(20), None),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn set_price_rejects_invalid_input_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let book_id = create_book(&random_name("badly priced book"), connection)?;
+
+    for (currency, amount) in [("US", 100), ("U$D", 100), ("", 100), ("USD", -1)] {
+        let error = set_price(book_id, currency, amount, day(1), connection).unwrap_err();
+
+        assert!(
+            error.downcast_ref::<ValidationError>().is_some(),
+            "{currency} {amount}"
+        );
+    }
+
+    assert!(get_price_history(book_id, connection)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn amounts_use_the_currency_minor_unit_test() {
+    assert_eq!(parse_amount("12.99", "USD"), Ok(1299));
+    assert_eq!(parse_amount("12.5", "USD"), Ok(1250));
+    assert_eq!(parse_amount(" 12 ", "EUR"), Ok(1200));
+    assert_eq!(parse_amount("1200", "JPY"), Ok(1200));
+    assert_eq!(parse_amount("1.250", "KWD"), Ok(1250));
+    assert!(parse_amount("12.999", "USD").is_err());
+    assert!(parse_amount("12.5", "JPY").is_err());
+    assert!(parse_amount("-1.00", "USD").is_err());
+    assert!(parse_amount("1,00", "USD").is_err());
+    assert!(parse_amount("", "USD").is_err());
+
+    assert_eq!(format_amount(1299, "USD"), "12.99 USD");
+    assert_eq!(format_amount(5, "EUR"), "0.05 EUR");
+    assert_eq!(format_amount(1200, "JPY"), "1200 JPY");
+    assert_eq!(format_amount(1250, "KWD"), "1.250 KWD");
+}
+
+#[test]
+fn csv_price_update_previews_and_applies_changes_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let book_name = random_name("csv priced book");
+    let repriced = create_book(&book_name, connection)?;
+    let unchanged = create_book(&random_name("csv unchanged book"), connection)?;
+
+    set_price(repriced, "USD", 1999, day(1), connection)?;
+    set_price(unchanged, "USD", 1000, day(1), connection)?;
+
+    let csv = format!(
+        "book_id,currency,price\n{repriced},USD,14.99\n{repriced},eur,13\n{unchanged},USD,10.00\n"
+    );
+    let preview = update_prices_csv(csv.as_bytes(), day(10), true, connection)?;
+
+    assert!(preview.dry_run);
+    assert_eq!(preview.unchanged, 1);
+    assert_eq!(
+        preview.changes,
+        [
+            PriceChange {
+                book_id: repriced,
+                book_name: book_name.clone(),
+                currency: "USD".to_owned(),
+                old_amount: Some(1999),
+                new_amount: 1499,
+            },
+            PriceChange {
+                book_id: repriced,
+                book_name,
+                currency: "EUR".to_owned(),
+                old_amount: None,
+                new_amount: 1300,
+            },
+        ]
+    );
+    assert_eq!(amount_at(repriced, "USD", day(10))?, Some(1999));
+
+    let report = update_prices_csv(csv.as_bytes(), day(10), false, connection)?;
+
+    assert!(!report.dry_run);
+    assert_eq!(report.changes, preview.changes);
+    assert_eq!(amount_at(repriced, "USD", day(5))?, Some(1999));
+    assert_eq!(amount_at(repriced, "USD", day(10))?, Some(1499));
+    assert_eq!(amount_at(repriced, "EUR", day(10))?, Some(1300));
+    assert_eq!(get_price_history(unchanged, connection)?.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn csv_price_update_reports_every_bad_row_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let book_id = create_book(&random_name("csv rejected book"), connection)?;
+    let csv = format!(
+        "book_id,currency,price\n\
+         {book_id},USD,12.999\n\
+         abc,DOLLARS,1\n\
+         {book_id},EUR,5\n\
+         {book_id},eur,6\n\
+         0,USD,1\n"
+    );
+    let error = update_prices_csv(csv.as_bytes(), day(1), false, connection)
+        .unwrap_err()
+        .downcast::<ImportError>()
+        .unwrap();
+    let messages = error
+        .errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<String>>();
+
+    assert_eq!(
+        messages,
+        [
+            "prices line 2: price `12.999` has more than 2 decimal places for USD".to_owned(),
+            "prices line 3: book_id `abc` is not a valid id".to_owned(),
+            "prices line 3: currency must be a three-letter ISO 4217 currency code".to_owned(),
+            format!("prices line 5: book {book_id} is already priced in EUR on line 4"),
+        ]
+    );
+
+    let error = update_prices_csv(
+        "book_id,currency,price\n0,USD,1\n".as_bytes(),
+        day(1),
+        false,
+        connection,
+    )
+    .unwrap_err()
+    .downcast::<ImportError>()
+    .unwrap();
+
+    assert_eq!(
+        error.errors[0].to_string(),
+        "prices line 2: book 0 does not exist"
+    );
+    assert!(get_price_history(book_id, connection)?.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,158 @@
+mod utilities;
+
+use diesel_bookstore_assessment::{
+    connect::{connect, establish},
+    queries::{
+        author_queries::create_author,
+        book_author_queries::{associate_book_with_author, get_all_books_and_authors},
+        book_queries::create_book,
+        customer_queries::create_customer,
+        review_queries::{
+            create_review, delete_review, get_book_with_authors_and_rating, get_review_by_id,
+            get_reviews_for_book, get_top_rated_books, update_review, with_ratings,
+            DuplicateReview,
+        },
+    },
+    validation::ValidationError,
+};
+use eyre::Result;
+use tempfile::TempDir;
+use utilities::random_name;
+
+#[test]
+fn reviews_can_be_created_updated_and_deleted_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let book_id = create_book(&random_name("reviewed book"), connection)?;
+    let author_id = create_author(&random_name("reviewed author"), connection)?;
+    let reader = create_customer(
+        &random_name("reader"),
+        &format!("reader.{}@example.com", rand::random::<u32>()),
+        connection,
+    )?;
+    let critic = create_customer(
+        &random_name("critic"),
+        &format!("critic.{}@example.com", rand::random::<u32>()),
+        connection,
+    )?;
+
+    associate_book_with_author(book_id, author_id, connection)?;
+
+    let unrated = get_book_with_authors_and_rating(book_id, connection)?.unwrap();
+
+    assert_eq!(unrated.rating().unwrap().review_count, 0);
+    assert_eq!(unrated.rating().unwrap().average_rating, None);
+
+    let review_id = create_review(book_id, reader, 4, "  Loved it.\n", connection)?;
+
+    create_review(book_id, critic, 1, "", connection)?;
+
+    let review = get_review_by_id(review_id, connection)?.unwrap();
+
+    assert_eq!((review.rating, review.body.as_str()), (4, "Loved it."));
+    assert_eq!(review.created_at, review.updated_at);
+
+    let error = create_review(book_id, reader, 5, "Again", connection).unwrap_err();
+
+    assert_eq!(
+        error.downcast_ref::<DuplicateReview>(),
+        Some(&DuplicateReview {
+            book_id,
+            customer_id: reader,
+            review_id
+        })
+    );
+
+    let error = create_review(book_id, reader, 6, "Too good", connection).unwrap_err();
+
+    assert_eq!(
+        error.downcast_ref::<ValidationError>().unwrap().field,
+        "rating"
+    );
+
+    update_review(review_id, 5, "Even better the second time.", connection)?;
+
+    let review = get_review_by_id(review_id, connection)?.unwrap();
+
+    assert_eq!(review.rating, 5);
+    assert!(review.updated_at >= review.created_at);
+
+    let rated = get_book_with_authors_and_rating(book_id, connection)?.unwrap();
+
+    assert_eq!(rated.authors().len(), 1);
+    assert_eq!(rated.rating().unwrap().review_count, 2);
+    assert_eq!(rated.rating().unwrap().average_rating, Some(3.0));
+
+    let top_rated = get_top_rated_books(2, 2, i64::from(i32::MAX), connection)?;
+
+    assert!(top_rated.iter().any(|book| book.book_id == book_id));
+    assert!(top_rated
+        .windows(2)
+        .all(|pair| pair[0].bayesian_average >= pair[1].bayesian_average));
+
+    delete_review(review_id, connection)?;
+    assert!(get_review_by_id(review_id, connection)?.is_none());
+    assert_eq!(get_reviews_for_book(book_id, connection)?.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn top_rated_books_use_a_bayesian_average_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let connection = &mut establish(&format!(
+        "sqlite://{}",
+        directory.path().join("reviews.db").display()
+    ))?;
+    let customers = (0..6)
+        .map(|number| {
+            create_customer(
+                &format!("Reader {number}"),
+                &format!("reader{number}@example.com"),
+                connection,
+            )
+        })
+        .collect::<Result<Vec<i32>>>()?;
+    let mut rate = |name: &str, ratings: &[i32]| -> Result<i32> {
+        let book_id = create_book(name, connection)?;
+
+        for (customer_id, rating) in customers.iter().zip(ratings) {
+            create_review(book_id, *customer_id, *rating, "", connection)?;
+        }
+
+        Ok(book_id)
+    };
+
+    let steady = rate("Steady Favourite", &[5, 5, 5, 5, 4, 5])?;
+    let lucky = rate("Two Lucky Reviews", &[5, 5])?;
+    let unloved = rate("Unloved", &[2, 1, 2])?;
+    let single = rate("Single Review", &[5])?;
+
+    rate("Unreviewed", &[])?;
+
+    let top_rated = get_top_rated_books(2, 2, 10, connection)?;
+
+    // Against an overall average of 49 / 12, two fives score about 4.54 and six mostly-fives
+    // about 4.65.
+    assert_eq!(
+        top_rated
+            .iter()
+            .map(|book| (book.book_id, book.review_count))
+            .collect::<Vec<_>>(),
+        vec![(steady, 6), (lucky, 2), (unloved, 3)]
+    );
+    assert_eq!(top_rated[0].book_name, "Steady Favourite");
+    assert_eq!(top_rated[1].average_rating, 5.0);
+    assert!((top_rated[1].bayesian_average - 218.0 / 48.0).abs() < 1e-9);
+    assert!(top_rated.iter().all(|book| book.book_id != single));
+    assert_eq!(get_top_rated_books(2, 2, 1, connection)?.len(), 1);
+    assert!(get_top_rated_books(0, 2, 10, connection).is_err());
+    assert!(get_top_rated_books(2, 0, 10, connection).is_err());
+
+    let books = with_ratings(get_all_books_and_authors(connection)?, connection)?;
+    let counts = books
+        .iter()
+        .map(|book| book.rating().unwrap().review_count)
+        .collect::<Vec<i64>>();
+
+    assert_eq!(counts, vec![6, 2, 3, 1, 0]);
+    Ok(())
+}
@@ -0,0 +1,108 @@
+#![cfg(feature = "serde")]
+
+use diesel_bookstore_assessment::models::{
+    Author, Book, BookAuthor, NewAuthor, NewBook, NewBookAuthor, MAX_NAME_LENGTH,
+};
+use eyre::Result;
+use serde_json::{json, Value};
+
+#[test]
+fn book_json_round_trip_test() -> Result<()> {
+    let book = Book {
+        id: 2,
+        name: "Moby Dick".to_owned(),
+    };
+    let book_json = serde_json::to_value(&book)?;
+
+    assert_eq!(book_json, json!({ "id": 2, "name": "Moby Dick" }));
+    assert_eq!(serde_json::from_value::<Book>(book_json)?, book);
+
+    Ok(())
+}
+
+#[test]
+fn author_json_round_trip_test() -> Result<()> {
+    let author = Author {
+        id: 2,
+        name: "Herman Melville".to_owned(),
+    };
+    let author_json = serde_json::to_value(&author)?;
+
+    assert_eq!(author_json, json!({ "id": 2, "name": "Herman Melville" }));
+    assert_eq!(serde_json::from_value::<Author>(author_json)?, author);
+
+    Ok(())
+}
+
+#[test]
+fn book_author_json_round_trip_test() -> Result<()> {
+    let book_author = BookAuthor {
+        book_id: 3,
+        author_id: 2,
+    };
+    let new_book_author = NewBookAuthor {
+        book_id: 3,
+        author_id: 2,
+    };
+    let book_author_json = serde_json::to_value(&book_author)?;
+
+    assert_eq!(book_author_json, json!({ "book_id": 3, "author_id": 2 }));
+    assert_eq!(
+        serde_json::from_value::<BookAuthor>(book_author_json.clone())?,
+        book_author
+    );
+    assert_eq!(serde_json::to_value(&new_book_author)?, book_author_json);
+    assert_eq!(
+        serde_json::from_value::<NewBookAuthor>(book_author_json)?,
+        new_book_author
+    );
+
+    Ok(())
+}
+
+#[test]
+fn new_book_and_author_json_round_trip_test() -> Result<()> {
+    let new_book = NewBook {
+        name: "Omoo".to_owned(),
+    };
+    let new_author = NewAuthor {
+        name: "Washington Irving".to_owned(),
+    };
+    let new_book_json = serde_json::to_value(&new_book)?;
+    let new_author_json = serde_json::to_value(&new_author)?;
+
+    assert_eq!(new_book_json, json!({ "name": "Omoo" }));
+    assert_eq!(new_author_json, json!({ "name": "Washington Irving" }));
+    assert_eq!(serde_json::from_value::<NewBook>(new_book_json)?, new_book);
+    assert_eq!(
+        serde_json::from_value::<NewAuthor>(new_author_json)?,
+        new_author
+    );
+
+    Ok(())
+}
+
+#[test]
+fn new_book_and_author_reject_invalid_names_test() {
+    let invalid_names: [Value; 4] = [
+        json!({ "name": "" }),
+        json!({ "name": "  \t " }),
+        json!({ "name": "x".repeat(MAX_NAME_LENGTH + 1) }),
+        json!({}),
+    ];
+
+    for invalid_name in invalid_names {
+        assert!(serde_json::from_value::<NewBook>(invalid_name.clone()).is_err());
+        assert!(serde_json::from_value::<NewAuthor>(invalid_name).is_err());
+    }
+}
+
+#[test]
+fn new_book_accepts_names_up_to_the_column_limit_test() -> Result<()> {
+    let name = "é".repeat(MAX_NAME_LENGTH);
+    let new_book: NewBook = serde_json::from_value(json!({ "name": name }))?;
+
+    assert_eq!(new_book.name, name);
+
+    Ok(())
+}
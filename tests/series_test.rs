@@ -0,0 +1,112 @@
+mod utilities;
+
+use diesel_bookstore_assessment::{
+    connect::connect,
+    queries::{
+        author_queries::create_author,
+        book_author_queries::associate_book_with_author,
+        book_queries::create_book,
+        series_queries::{
+            add_book_to_series, create_series, get_next_book_in_series,
+            get_previous_book_in_series, get_series_for_book, get_series_reading_order,
+            remove_book_from_series, SeriesPositionTaken,
+        },
+    },
+    validation::ValidationError,
+};
+use eyre::Result;
+use utilities::random_name;
+
+#[test]
+fn series_are_listed_in_reading_order_with_authors_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let series_id = create_series(&random_name("sea novels"), connection)?;
+    let author_id = create_author(&random_name("Herman Melville"), connection)?;
+    let typee = create_book(&random_name("Typee"), connection)?;
+    let omoo = create_book(&random_name("Omoo"), connection)?;
+    let novella = create_book(&random_name("The Encantadas"), connection)?;
+    let mardi = create_book(&random_name("Mardi"), connection)?;
+
+    for book_id in [typee, omoo, novella, mardi] {
+        associate_book_with_author(book_id, author_id, connection)?;
+    }
+
+    add_book_to_series(mardi, series_id, 3.0, connection)?;
+    add_book_to_series(typee, series_id, 1.0, connection)?;
+    add_book_to_series(novella, series_id, 2.5, connection)?;
+    add_book_to_series(omoo, series_id, 2.0, connection)?;
+
+    let reading_order = get_series_reading_order(series_id, connection)?.unwrap();
+
+    assert_eq!(reading_order.series.id, series_id);
+    assert_eq!(
+        reading_order
+            .entries
+            .iter()
+            .map(|entry| (entry.position, entry.book.book().id))
+            .collect::<Vec<_>>(),
+        vec![(1.0, typee), (2.0, omoo), (2.5, novella), (3.0, mardi)]
+    );
+    assert!(reading_order
+        .entries
+        .iter()
+        .all(|entry| entry.book.authors()[0].id == author_id));
+    assert_eq!(get_series_for_book(novella, connection)?[0].1, 2.5);
+    assert!(get_series_reading_order(-1, connection)?.is_none());
+    Ok(())
+}
+
+#[test]
+fn next_and_previous_follow_positions_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let series_id = create_series(&random_name("trilogy"), connection)?;
+    let first = create_book(&random_name("first"), connection)?;
+    let second = create_book(&random_name("second"), connection)?;
+    let third = create_book(&random_name("third"), connection)?;
+    let outsider = create_book(&random_name("standalone"), connection)?;
+
+    add_book_to_series(first, series_id, 1.0, connection)?;
+    add_book_to_series(second, series_id, 2.0, connection)?;
+    add_book_to_series(third, series_id, 3.0, connection)?;
+
+    let next = |book_id, connection: &mut _| -> Result<Option<i32>> {
+        Ok(get_next_book_in_series(book_id, series_id, connection)?.map(|book| book.id))
+    };
+    let previous = |book_id, connection: &mut _| -> Result<Option<i32>> {
+        Ok(get_previous_book_in_series(book_id, series_id, connection)?.map(|book| book.id))
+    };
+
+    assert_eq!(next(first, connection)?, Some(second));
+    assert_eq!(previous(first, connection)?, None);
+    assert_eq!(next(third, connection)?, None);
+    assert_eq!(previous(third, connection)?, Some(second));
+    assert_eq!(next(outsider, connection)?, None);
+
+    // Moving a book keeps a single entry for it, and its old position frees up.
+    add_book_to_series(third, series_id, 0.5, connection)?;
+    assert_eq!(next(third, connection)?, Some(first));
+    assert_eq!(previous(second, connection)?, Some(first));
+
+    let error = add_book_to_series(outsider, series_id, 2.0, connection).unwrap_err();
+
+    assert_eq!(
+        error.downcast_ref::<SeriesPositionTaken>(),
+        Some(&SeriesPositionTaken {
+            series_id,
+            position: 2.0,
+            book_id: second
+        })
+    );
+
+    let error = add_book_to_series(outsider, series_id, f64::NAN, connection).unwrap_err();
+
+    assert_eq!(
+        error.downcast_ref::<ValidationError>().unwrap().field,
+        "position"
+    );
+
+    remove_book_from_series(first, series_id, connection)?;
+    assert_eq!(next(third, connection)?, Some(second));
+    assert_eq!(next(first, connection)?, None);
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use diesel_bookstore_assessment::{
+    connect::{establish, DbConnection, UnsupportedDatabaseUrl},
+    queries::{
+        author_queries::{create_author, delete_author, get_author_by_id, update_author},
+        book_author_queries::{
+            associate_book_with_author, get_all_authors_and_books, get_all_books_and_authors,
+            get_author_with_books, get_book_with_authors,
+        },
+        book_queries::{create_book, delete_book, get_all_books, get_book_by_id, update_book},
+    },
+};
+use eyre::Result;
+use tempfile::TempDir;
+
+fn sqlite_connection(directory: &TempDir) -> Result<DbConnection> {
+    let database_path = directory.path().join("bookstore.sqlite3");
+
+    establish(&format!("sqlite://{}", database_path.display()))
+}
+
+#[test]
+fn sqlite_connection_is_migrated_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let connection = &mut sqlite_connection(&directory)?;
+
+    assert!(matches!(connection, DbConnection::Sqlite(_)));
+    assert!(get_all_books(connection)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn sqlite_book_crud_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let connection = &mut sqlite_connection(&directory)?;
+    let book_id = create_book("Moby Dick", connection)?;
+
+    assert!(get_book_by_id(book_id, connection)?.is_some_and(|book| book.name == "Moby Dick"));
+
+    update_book(book_id, "Omoo", connection)?;
+
+    assert!(get_book_by_id(book_id, connection)?.is_some_and(|book| book.name == "Omoo"));
+
+    delete_book(book_id, connection)?;
+
+    assert!(get_book_by_id(book_id, connection)?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn sqlite_author_crud_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let connection = &mut sqlite_connection(&directory)?;
+    let author_id = create_author("Herman Melville", connection)?;
+
+    update_author(author_id, "Washington Irving", connection)?;
+
+    assert!(get_author_by_id(author_id, connection)?
+        .is_some_and(|author| author.name == "Washington Irving"));
+
+    delete_author(author_id, connection)?;
+
+    assert!(get_author_by_id(author_id, connection)?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn sqlite_books_with_authors_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let connection = &mut sqlite_connection(&directory)?;
+    let moby_dick_id = create_book("Moby Dick", connection)?;
+    let omoo_id = create_book("Omoo", connection)?;
+    let melville_id = create_author("Herman Melville", connection)?;
+
+    associate_book_with_author(moby_dick_id, melville_id, connection)?;
+    associate_book_with_author(omoo_id, melville_id, connection)?;
+
+    let author_with_books = get_author_with_books(melville_id, connection)?.unwrap();
+    let book_with_authors = get_book_with_authors(omoo_id, connection)?.unwrap();
+    let all_books_and_authors = get_all_books_and_authors(connection)?;
+    let all_authors_and_books = get_all_authors_and_books(connection)?;
+
+    assert_eq!(author_with_books.books().len(), 2);
+    assert_eq!(book_with_authors.authors().len(), 1);
+    assert_eq!(all_books_and_authors.len(), 2);
+    assert!(all_books_and_authors
+        .iter()
+        .all(|book_with_authors| book_with_authors.authors().len() == 1));
+    assert_eq!(all_authors_and_books.len(), 1);
+    assert_eq!(all_authors_and_books[0].books().len(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn sqlite_enforces_foreign_keys_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let connection = &mut sqlite_connection(&directory)?;
+    let book_id = create_book("Rip Van Winkle", connection)?;
+
+    assert!(associate_book_with_author(book_id, 404, connection).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn sqlite_accepts_paths_file_uris_and_memory_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let path = directory.path().join("plain.sqlite3");
+    let uri = format!("file:{}", directory.path().join("uri.sqlite3").display());
+
+    for database_url in [path.display().to_string(), uri, ":memory:".to_owned()] {
+        let connection = &mut establish(&database_url)?;
+
+        assert!(matches!(connection, DbConnection::Sqlite(_)));
+        create_book("Typee", connection)?;
+        assert_eq!(get_all_books(connection)?.len(), 1, "{database_url}");
+    }
+
+    // A second connection to a database this process already migrated.
+    let connection = &mut establish(&path.display().to_string())?;
+
+    assert_eq!(get_all_books(connection)?.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn unknown_database_url_schemes_are_refused_test() {
+    for (database_url, scheme) in [
+        ("mysql://localhost/bookstore", "mysql"),
+        ("postgre://localhost/bookstore", "postgre"),
+        ("https://example.com/bookstore.sqlite3", "https"),
+    ] {
+        let Err(error) = establish(database_url) else {
+            panic!("{database_url} connected");
+        };
+
+        assert_eq!(
+            error.downcast_ref::<UnsupportedDatabaseUrl>(),
+            Some(&UnsupportedDatabaseUrl {
+                scheme: scheme.to_owned()
+            })
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use diesel_bookstore_assessment::{
+    connect::{establish, DbConnection},
+    models::{Author, AuthorWithBooks, Book, BookWithAuthors},
+    queries::{
+        author_queries::{create_author, get_all_authors},
+        book_author_queries::{
+            associate_book_with_author, get_all_authors_and_books, get_all_books_and_authors,
+        },
+        book_queries::{create_book, get_all_books},
+        stream_queries::{
+            stream_authors, stream_authors_with_books, stream_books, stream_books_with_authors,
+        },
+    },
+    validation::ValidationError,
+};
+use eyre::Result;
+use tempfile::TempDir;
+
+fn seeded_connection(directory: &TempDir) -> Result<DbConnection> {
+    let database_path = directory.path().join("bookstore.sqlite3");
+    let mut connection = establish(&format!("sqlite://{}", database_path.display()))?;
+    let melville = create_author("Herman Melville", &mut connection)?;
+    let irving = create_author("Washington Irving", &mut connection)?;
+
+    for name in ["Moby Dick", "Omoo", "Typee", "Rip Van Winkle", "Mardi"] {
+        let book_id = create_book(name, &mut connection)?;
+        let author_id = if name == "Rip Van Winkle" {
+            irving
+        } else {
+            melville
+        };
+
+        associate_book_with_author(book_id, author_id, &mut connection)?;
+    }
+
+    create_book("Anonymous Pamphlet", &mut connection)?;
+
+    Ok(connection)
+}
+
+#[test]
+fn stream_books_yields_every_book_in_id_order_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let connection = &mut seeded_connection(&directory)?;
+    let expected = get_all_books(connection)?;
+
+    for batch_size in [1, 2, 4, 6, 100] {
+        let streamed = stream_books(batch_size, connection).collect::<Result<Vec<Book>>>()?;
+
+        assert_eq!(streamed, expected);
+    }
+
+    let streamed = stream_authors(1, connection).collect::<Result<Vec<Author>>>()?;
+
+    assert_eq!(streamed, get_all_authors(connection)?);
+
+    for batch_size in [0, -1] {
+        let mut streamed = stream_books(batch_size, connection);
+        let error = streamed.next().expect("batch size error").unwrap_err();
+
+        assert_eq!(
+            error.downcast_ref::<ValidationError>().unwrap().field,
+            "batch_size"
+        );
+        assert!(streamed.next().is_none());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn stream_books_with_authors_groups_per_batch_test() -> Result<()> {
+    let directory = TempDir::new()?;
+    let connection = &mut seeded_connection(&directory)?;
+    let expected = get_all_books_and_authors(connection)?;
+
+    for batch_size in [1, 4, 100] {
+        let streamed = stream_books_with_authors(batch_size, connection)
+            .collect::<Result<Vec<BookWithAuthors>>>()?;
+
+        assert_eq!(streamed, expected);
+    }
+
+    let (book, authors) = stream_books_with_authors(2, connection)
+        .last()
+        .expect("last book")?
+        .into_parts();
+
+    assert_eq!(book.name, "Anonymous Pamphlet");
+    assert!(authors.is_empty());
+
+    let streamed =
+        stream_authors_with_books(1, connection).collect::<Result<Vec<AuthorWithBooks>>>()?;
+
+    assert_eq!(streamed, get_all_authors_and_books(connection)?);
+    assert_eq!(streamed[0].books().len(), 4);
+
+    Ok(())
+}
@@ -0,0 +1,272 @@
+mod utilities;
+
+use diesel_bookstore_assessment::{
+    connect::connect,
+    models::MAX_NAME_LENGTH,
+    queries::{
+        author_queries::{create_author, get_author_by_id, update_author},
+        book_queries::{create_book, get_book_by_id, update_book},
+    },
+    validation::{
+        normalize, validate_isbn, validate_isni, validate_language_tag, validate_name,
+        validate_orcid, validate_partial_date, validate_viaf, validate_wikidata_qid,
+        ValidationError, ValidationReason,
+    },
+};
+use eyre::Result;
+use utilities::random_name;
+
+#[test]
+fn normalize_trims_and_collapses_whitespace_test() {
+    assert_eq!(normalize("  Moby \t\n  Dick  "), "Moby Dick");
+}
+
+#[test]
+fn normalize_composes_unicode_test() {
+    assert_eq!(normalize("Cafe\u{301}"), "Caf\u{e9}");
+}
+
+#[test]
+fn validate_name_rejects_blank_names_test() {
+    for blank_name in ["", "   ", "\t\n", "\u{3000}"] {
+        assert_eq!(
+            validate_name("name", blank_name),
+            Err(ValidationError {
+                field: "name",
+                reason: ValidationReason::Empty,
+            })
+        );
+    }
+}
+
+#[test]
+fn validate_name_rejects_long_names_test() {
+    let long_name = "a".repeat(MAX_NAME_LENGTH + 1);
+
+    assert_eq!(
+        validate_name("name", &long_name),
+        Err(ValidationError {
+            field: "name",
+            reason: ValidationReason::TooLong {
+                max: MAX_NAME_LENGTH,
+                actual: MAX_NAME_LENGTH + 1,
+            },
+        })
+    );
+}
+
+#[test]
+fn validate_name_counts_characters_after_normalizing_test() {
+    let decomposed_name = "e\u{301}".repeat(MAX_NAME_LENGTH);
+
+    assert_eq!(
+        validate_name("name", &decomposed_name),
+        Ok("\u{e9}".repeat(MAX_NAME_LENGTH))
+    );
+}
+
+#[test]
+fn create_book_stores_normalized_name_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let book_name = random_name("normalized book");
+    let book_id = create_book(
+        &format!("  {}  ", book_name.replace(' ', "   ")),
+        connection,
+    )?;
+
+    assert!(get_book_by_id(book_id, connection)?.is_some_and(|book| book.name == book_name));
+
+    Ok(())
+}
+
+#[test]
+fn create_and_update_reject_invalid_names_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let book_name = random_name("valid book");
+    let author_name = random_name("valid author");
+    let book_id = create_book(&book_name, connection)?;
+    let author_id = create_author(&author_name, connection)?;
+    let long_name = "a".repeat(MAX_NAME_LENGTH + 1);
+
+    let error = create_book("   ", connection).unwrap_err();
+
+    assert!(error.downcast_ref::<ValidationError>().is_some());
+    assert!(create_author("", connection).is_err());
+    assert!(update_book(book_id, &long_name, connection).is_err());
+    assert!(update_author(author_id, "\n", connection).is_err());
+    assert!(get_book_by_id(book_id, connection)?.is_some_and(|book| book.name == book_name));
+    assert!(
+        get_author_by_id(author_id, connection)?.is_some_and(|author| author.name == author_name)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn validate_isbn_normalizes_to_isbn13_test() {
+    for isbn in [
+        "9780142437247",
+        "978-0-14-243724-7",
+        "0142437247",
+        "0-14-243724-7",
+    ] {
+        assert_eq!(validate_isbn("isbn", isbn), Ok("9780142437247".to_owned()));
+    }
+
+    assert_eq!(
+        validate_isbn("isbn", "0-8044-2957-X"),
+        Ok("9780804429573".to_owned())
+    );
+}
+
+#[test]
+fn validate_isbn_rejects_bad_checksums_test() {
+    for isbn in [
+        "9780142437248",
+        "0142437248",
+        "1234567890123",
+        "97801424372",
+        "abc",
+    ] {
+        assert_eq!(
+            validate_isbn("isbn", isbn),
+            Err(ValidationError {
+                field: "isbn",
+                reason: ValidationReason::Invalid {
+                    expected: "a valid ISBN-10 or ISBN-13",
+                },
+            }),
+            "{isbn}"
+        );
+    }
+
+    assert_eq!(
+        validate_isbn("isbn", " - ").unwrap_err().to_string(),
+        "isbn must not be empty"
+    );
+}
+
+#[test]
+fn validate_language_tag_uses_conventional_case_test() {
+    for (tag, expected) in [
+        ("de", "de"),
+        ("DE_at", "de-AT"),
+        (" zh-hant-tw ", "zh-Hant-TW"),
+        ("sr-Latn-RS", "sr-Latn-RS"),
+        ("es-419", "es-419"),
+        ("en-US-x-TWAIN", "en-US-x-twain"),
+    ] {
+        assert_eq!(
+            validate_language_tag("language", tag),
+            Ok(expected.to_owned())
+        );
+    }
+
+    for tag in ["d", "deut-AT", "de--AT", "de-AT-", "fr-toolongsubtag", "ελ"] {
+        assert_eq!(
+            validate_language_tag("language", tag),
+            Err(ValidationError {
+                field: "language",
+                reason: ValidationReason::Invalid {
+                    expected: "a BCP 47 language tag",
+                },
+            }),
+            "{tag}"
+        );
+    }
+
+    assert_eq!(
+        validate_language_tag("language", "  ").unwrap_err().reason,
+        ValidationReason::Empty
+    );
+}
+
+#[test]
+fn validate_partial_date_accepts_partial_and_circa_dates_test() {
+    for (date, expected) in [
+        ("1835-11-30", "1835-11-30"),
+        ("1835-11", "1835-11"),
+        (" 1835 ", "1835"),
+        ("c. 1340", "1340~"),
+        ("circa 800", "0800~"),
+        ("1564~", "1564~"),
+        ("-0384", "-0384"),
+    ] {
+        assert_eq!(
+            validate_partial_date("birth_date", date),
+            Ok(expected.to_owned())
+        );
+    }
+
+    for date in [
+        "1835-13",
+        "1835-02-30",
+        "35-11",
+        "1835/11/30",
+        "1835-11-30-1",
+        "soon",
+    ] {
+        assert!(
+            matches!(
+                validate_partial_date("birth_date", date)
+                    .unwrap_err()
+                    .reason,
+                ValidationReason::Invalid { .. }
+            ),
+            "{date}"
+        );
+    }
+}
+
+#[test]
+fn author_identifiers_are_checked_and_normalized_test() {
+    for orcid in [
+        "0000-0002-1825-0097",
+        "0000000218250097",
+        "https://orcid.org/0000-0002-1825-0097",
+    ] {
+        assert_eq!(
+            validate_orcid("orcid", orcid),
+            Ok("0000-0002-1825-0097".to_owned())
+        );
+    }
+
+    assert_eq!(
+        validate_isni("isni", "0000 0001 2146 438x"),
+        Ok("000000012146438X".to_owned())
+    );
+    assert_eq!(
+        validate_viaf("viaf", "https://viaf.org/viaf/50566653/"),
+        Ok("50566653".to_owned())
+    );
+    assert_eq!(
+        validate_wikidata_qid("wikidata", "q7245"),
+        Ok("Q7245".to_owned())
+    );
+
+    for (result, expected) in [
+        (
+            validate_orcid("orcid", "0000-0002-1825-0098"),
+            "a valid ORCID iD",
+        ),
+        (
+            validate_orcid("orcid", "0000-0002-1825"),
+            "a valid ORCID iD",
+        ),
+        (validate_isni("isni", "0000000356908457"), "a valid ISNI"),
+        (validate_viaf("viaf", "0123"), "a VIAF id"),
+        (
+            validate_wikidata_qid("wikidata", "P31"),
+            "a Wikidata item id such as Q42",
+        ),
+        (
+            validate_wikidata_qid("wikidata", "Q0"),
+            "a Wikidata item id such as Q42",
+        ),
+    ] {
+        assert_eq!(
+            result.unwrap_err().reason,
+            ValidationReason::Invalid { expected }
+        );
+    }
+}
@@ -0,0 +1,181 @@
+mod utilities;
+
+use diesel_bookstore_assessment::{
+    connect::connect,
+    queries::{
+        author_queries::create_author,
+        book_author_queries::{
+            associate_book_with_author, book_author_exists, dissociate_book_from_author,
+            get_author_with_books, get_book_with_authors,
+        },
+        book_queries::create_book,
+        work_queries::{
+            add_edition_contributor, add_edition_to_work, create_work, credit_author_on_work,
+            get_book_credits, get_work_with_editions, remove_author_from_work,
+            remove_edition_from_work,
+        },
+    },
+};
+use eyre::Result;
+use utilities::random_name;
+
+#[test]
+fn editions_share_the_authors_of_their_work_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let work_id = create_work(&random_name("Der Zauberberg"), connection)?;
+    let mann = create_author(&random_name("Thomas Mann"), connection)?;
+    let translator = create_author(&random_name("John E. Woods"), connection)?;
+    let german = create_book(&random_name("Der Zauberberg (1924)"), connection)?;
+    let english = create_book(&random_name("The Magic Mountain"), connection)?;
+
+    associate_book_with_author(german, mann, connection)?;
+    add_edition_to_work(german, work_id, connection)?;
+    add_edition_to_work(english, work_id, connection)?;
+    add_edition_contributor(english, translator, " Translator ", connection)?;
+
+    // The credit moved up to the work, so the English edition picked it up as well.
+    let english_edition = get_book_with_authors(english, connection)?.unwrap();
+
+    assert_eq!(
+        english_edition
+            .authors()
+            .iter()
+            .map(|author| author.id)
+            .collect::<Vec<i32>>(),
+        vec![mann]
+    );
+    assert!(book_author_exists(english, mann, connection)?);
+    assert!(!book_author_exists(english, translator, connection)?);
+
+    let credits = get_book_credits(english, connection)?
+        .into_iter()
+        .map(|credit| (credit.author.id, credit.role, credit.from_work))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        credits,
+        vec![
+            (mann, "author".to_owned(), true),
+            (translator, "translator".to_owned(), false)
+        ]
+    );
+
+    let author_with_books = get_author_with_books(mann, connection)?.unwrap();
+    let mut book_ids = author_with_books
+        .books()
+        .iter()
+        .map(|book| book.id)
+        .collect::<Vec<i32>>();
+
+    book_ids.sort();
+    assert_eq!(book_ids, vec![german, english]);
+    assert!(get_author_with_books(translator, connection)?
+        .unwrap()
+        .books()
+        .is_empty());
+    Ok(())
+}
+
+#[test]
+fn works_are_loaded_with_their_editions_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let work_id = create_work(&random_name("Don Quixote"), connection)?;
+    let cervantes = create_author(&random_name("Miguel de Cervantes"), connection)?;
+    let illustrator = create_author(&random_name("Gustave Doré"), connection)?;
+    let first = create_book(&random_name("El ingenioso hidalgo"), connection)?;
+    let second = create_book(&random_name("Don Quixote, illustrated"), connection)?;
+    let unrelated = create_book(&random_name("Novelas ejemplares"), connection)?;
+
+    add_edition_to_work(first, work_id, connection)?;
+    add_edition_to_work(second, work_id, connection)?;
+
+    credit_author_on_work(work_id, cervantes, connection)?;
+    add_edition_contributor(second, illustrator, "illustrator", connection)?;
+
+    let work = get_work_with_editions(work_id, connection)?.unwrap();
+
+    assert_eq!(work.work.id, work_id);
+    assert_eq!(
+        work.authors
+            .iter()
+            .map(|author| author.id)
+            .collect::<Vec<i32>>(),
+        vec![cervantes]
+    );
+    assert_eq!(
+        work.editions
+            .iter()
+            .map(|edition| edition.book().id)
+            .collect::<Vec<i32>>(),
+        vec![first, second]
+    );
+    assert!(work
+        .editions
+        .iter()
+        .all(|edition| edition.authors().len() == 1 && edition.authors()[0].id == cervantes));
+    assert!(get_work_with_editions(-1, connection)?.is_none());
+
+    // A book leaving its work keeps the authors as its own credits.
+    remove_edition_from_work(second, connection)?;
+
+    let standalone = get_book_with_authors(second, connection)?.unwrap();
+
+    assert_eq!(standalone.authors()[0].id, cervantes);
+    assert_eq!(get_book_credits(second, connection)?.len(), 2);
+    assert_eq!(
+        get_work_with_editions(work_id, connection)?
+            .unwrap()
+            .editions
+            .len(),
+        1
+    );
+    assert!(get_book_with_authors(unrelated, connection)?
+        .unwrap()
+        .authors()
+        .is_empty());
+    Ok(())
+}
+
+#[test]
+fn dissociating_an_author_keeps_other_credits_test() -> Result<()> {
+    let connection = &mut connect()?;
+    let work_id = create_work(&random_name("Pale Fire"), connection)?;
+    let nabokov = create_author(&random_name("Vladimir Nabokov"), connection)?;
+    let english = create_book(&random_name("Pale Fire (1962)"), connection)?;
+    let russian = create_book(&random_name("Бледный огонь"), connection)?;
+
+    add_edition_to_work(english, work_id, connection)?;
+    add_edition_to_work(russian, work_id, connection)?;
+    credit_author_on_work(work_id, nabokov, connection)?;
+
+    // The same person can hold two roles on one edition.
+    associate_book_with_author(russian, nabokov, connection)?;
+    add_edition_contributor(russian, nabokov, "translator", connection)?;
+
+    assert!(dissociate_book_from_author(russian, nabokov, connection)?);
+    assert!(!dissociate_book_from_author(russian, nabokov, connection)?);
+
+    // The work credit and the translator credit are untouched.
+    let credits = get_book_credits(russian, connection)?
+        .into_iter()
+        .map(|credit| (credit.author.id, credit.role, credit.from_work))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        credits,
+        vec![
+            (nabokov, "author".to_owned(), true),
+            (nabokov, "translator".to_owned(), false)
+        ]
+    );
+    assert!(book_author_exists(english, nabokov, connection)?);
+    assert!(book_author_exists(russian, nabokov, connection)?);
+
+    assert!(remove_author_from_work(work_id, nabokov, connection)?);
+    assert!(!book_author_exists(english, nabokov, connection)?);
+    assert!(get_book_with_authors(russian, connection)?
+        .unwrap()
+        .authors()
+        .is_empty());
+    Ok(())
+}